@@ -0,0 +1,75 @@
+//! Compares decoding packets on the calling thread, as `--parse-threads 1` does,
+//! with decoding them on a `ParsePool` of several threads.
+//!
+//! Run with `cargo bench --bench parse_threads`. Each run decodes the same TCP
+//! packet repeatedly and prints the packet rate of the serial path and of pools of
+//! increasing size.
+
+use pcap::PacketHeader;
+use sniff_rs::analyzer::{analyzer::Analyzer, definitions::ParseOptions, parse_pool::ParsePool};
+use std::{hint::black_box, thread, time::Instant};
+
+const PACKETS: usize = 200_000;
+
+/// A TCP SYN from 192.168.1.10:50000 to 93.184.216.34:80 in an Ethernet frame.
+fn tcp_frame() -> Vec<u8> {
+    vec![
+        0x00, 0x11, 0x22, 0x33, 0x44, 0x55, // destination MAC
+        0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, // source MAC
+        0x08, 0x00, // EtherType IPv4
+        0x45, 0x00, 0x00, 0x28, 0x00, 0x01, 0x40, 0x00, 0x40, 0x06, 0x00, 0x00, // IPv4 header
+        192, 168, 1, 10, // source address
+        93, 184, 216, 34, // destination address
+        0xc3, 0x50, 0x00, 0x50, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, // TCP header
+        0x50, 0x02, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00,
+    ]
+}
+
+fn report(name: &str, started: Instant) {
+    let elapsed = started.elapsed();
+    println!(
+        "{:<10} {:>8.0} packets/s ({:?} for {} packets)",
+        name,
+        PACKETS as f64 / elapsed.as_secs_f64(),
+        elapsed,
+        PACKETS
+    );
+}
+
+fn main() {
+    let packet = tcp_frame();
+    let header = PacketHeader {
+        ts: libc::timeval {
+            tv_sec: 0,
+            tv_usec: 0,
+        },
+        caplen: packet.len() as u32,
+        len: packet.len() as u32,
+    };
+    let options = ParseOptions::default();
+
+    let started = Instant::now();
+    for _ in 0..PACKETS {
+        black_box(Analyzer::decode(black_box(&packet), &options).ok());
+    }
+    report("serial", started);
+
+    let cores = thread::available_parallelism().map_or(4, usize::from);
+    for threads in [2, 4, cores] {
+        let pool_options = options.clone();
+        let mut pool = ParsePool::new(threads, move |_, data| {
+            Analyzer::decode(data, &pool_options).ok().flatten()
+        });
+
+        let started = Instant::now();
+        let mut decoded = 0;
+        for _ in 0..PACKETS {
+            pool.submit(header, packet.clone());
+            decoded += pool.ready().len();
+        }
+        decoded += pool.finish().len();
+        report(&format!("{} threads", threads), started);
+
+        assert_eq!(decoded, PACKETS);
+    }
+}
@@ -1,205 +1,2732 @@
-use log::{error, info};
+use log::{error, info, warn};
 use net_sift::parsers::{
     definitions::{DeepParser, LayeredData},
+    errors::ParserError,
     ethernet_frame::EthernetFrame,
 };
-use pcap::{Activated, Active, Capture, Packet, Savefile};
+use pcap::{Active, Capture, Device, Linktype, PacketHeader, Stat};
+use serde_json::{json, Value};
+#[cfg(feature = "pcapng")]
+use std::fs::File;
 use std::{
-    path::{Path, PathBuf},
-    sync::mpsc::channel,
+    borrow::Cow,
+    fs::{self, OpenOptions},
+    io::{self, BufWriter, ErrorKind, Write},
+    net::IpAddr,
+    ops::ControlFlow,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, RecvTimeoutError},
+        Arc,
+    },
     thread,
+    time::{Duration, Instant},
 };
 
-use super::{definitions::ReadPacketResult, error::AnalyzerError, pcap_interface::PcapInterface};
-use crate::logger::format_packets::format_packets;
+#[cfg(feature = "pcapng")]
+use super::pcapng::PcapngWriter;
+use super::{
+    arp::ArpPacket,
+    capture_files::CaptureFiles,
+    checksum,
+    dashboard::Dashboard,
+    definitions::{
+        ActiveHours, CaptureLimits, CaptureOptions, CaptureSchedule, DecodeLevel, ExtractProtocol,
+        FileFormat, OutputFormat, ParseOptions, ParsedFrame, ReadPacketResult, SaveTarget,
+        StreamOptions,
+    },
+    direction::PacketDirection,
+    encapsulation::{self, IpTunnel, ETHERNET_HEADER_LEN},
+    error::AnalyzerError,
+    fifo::FifoWriter,
+    flows::{Direction, Endpoint, FlowKey, IP_PROTOCOL_TCP},
+    geneve::{GenevePacket, ETHER_TYPE_TRANSPARENT_BRIDGING},
+    http_objects,
+    http_stream::PcapHttpServer,
+    interrupt, local_time,
+    loopback::{self, LoopbackFrame},
+    memory::MemoryGuard,
+    metrics::MetricsServer,
+    parse_pool::ParsePool,
+    pcap_interface::PcapInterface,
+    pcap_writer::{PcapWriter, DEFAULT_SNAPLEN},
+    pppoe::PppoeSession,
+    rate::RateMeter,
+    reassembly::TcpReassembler,
+    ring_file::RingWriter,
+    rotation::FileRotation,
+    scans::ScanAlert,
+    sink::PacketSink,
+    state::CaptureState,
+    vlan,
+    wifi::{self, WifiFrame},
+};
+use crate::logger::format_packets::{
+    format_bytes, format_capture_stats, format_cast_summary, format_checksums,
+    format_checksums_json, format_conversation, format_datalinks, format_delta, format_fitted,
+    format_flow, format_flow_key, format_geneve, format_interfaces, format_layers, format_loopback,
+    format_packets, format_packets_csv, format_packets_json, format_payload, format_payload_text,
+    format_pppoe, format_protocol_summary, format_rate, format_raw_ether_frame, format_raw_packet,
+    format_stream_direction, format_stream_text, format_timestamp, format_tunnel, format_wifi,
+    hexdump, CSV_HEADER,
+};
+
+/// The number of flows listed in the summary at the end of a capture.
+const FLOW_SUMMARY_LEN: usize = 10;
+
+/// Shown instead of a parse error for packets that are shorter than on the wire.
+const TRUNCATED_MARKER: &str = "<truncated, need full capture>";
+
+/// How long a stopped capture waits for its reader threads to send their last
+/// packet counts. They stop at their next packet or periodic packet count, unless
+/// they are waiting to reconnect an interface, whose counts were already sent.
+const READER_STOP_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The number of nested IP tunnels followed before the rest of a packet is left
+/// undecoded, so that a crafted packet cannot recurse without bound.
+const MAX_TUNNEL_DEPTH: usize = 8;
+
+/// Shown in place of the inner packet of a tunnel nested deeper than `MAX_TUNNEL_DEPTH`.
+const TUNNEL_DEPTH_MARKER: &str = "<too many nested tunnels>";
+
+/// The Ethernet frame of a packet that failed to decode, with its capture header,
+/// so that it can be saved for study.
+struct FailedFrame {
+    header: PacketHeader,
+    frame: Vec<u8>,
+}
+
+/// A packet decoded into its output form.
+enum DecodedPacket {
+    /// A JSON object, with the frame that failed if it holds a parse error.
+    Json(Value, Option<FailedFrame>),
+    /// A row of the CSV output.
+    Row(String),
+    Text(String),
+    /// A packet that failed to decode because it was cut short by the snaplen.
+    Truncated(String, FailedFrame),
+    /// A packet that failed to decode, with its hexdump if one was requested.
+    Error {
+        error: String,
+        dump: String,
+        failed: FailedFrame,
+    },
+    /// A packet that produces no output.
+    Empty,
+}
+
+impl DecodedPacket {
+    /// Drops the packets that decoded successfully if `options` only asks for the
+    /// ones that failed. JSON objects are kept if they hold a parse error.
+    fn failures_only(self, options: &ParseOptions) -> Self {
+        if !options.only_errors {
+            return self;
+        }
+
+        match self {
+            DecodedPacket::Json(_, None) => DecodedPacket::Empty,
+            DecodedPacket::Row(_) | DecodedPacket::Text(_) => DecodedPacket::Empty,
+            decoded => decoded,
+        }
+    }
+
+    /// Cuts the text of a decoded packet to fit a terminal, as requested in
+    /// `options`. Compact output is abbreviated while the packet is formatted.
+    fn fitted(self, options: &ParseOptions) -> Self {
+        if options.width.is_none() {
+            return self;
+        }
+
+        match self {
+            DecodedPacket::Text(log_msg) => DecodedPacket::Text(format_fitted(&log_msg, options)),
+            DecodedPacket::Truncated(log_msg, failed) => {
+                DecodedPacket::Truncated(format_fitted(&log_msg, options), failed)
+            }
+            decoded => decoded,
+        }
+    }
+}
+
+/// The link layer an Ethernet frame was rebuilt from, shown in place of its
+/// synthetic Ethernet header.
+enum RebuiltLink<'a> {
+    Loopback(&'a LoopbackFrame<'a>),
+    Wifi(&'a WifiFrame<'a>),
+}
+
+impl RebuiltLink<'_> {
+    /// The link layer as text.
+    fn text(&self, options: &ParseOptions) -> String {
+        match self {
+            RebuiltLink::Loopback(frame) => format_loopback(frame, options),
+            RebuiltLink::Wifi(frame) => format_wifi(frame, options),
+        }
+    }
+
+    /// The key and value of the link layer in JSON output.
+    fn json(&self) -> (&'static str, Value) {
+        match self {
+            RebuiltLink::Loopback(frame) => ("loopback", json!({ "family": frame.family })),
+            RebuiltLink::Wifi(frame) => ("wifi", Analyzer::wifi_json(frame)),
+        }
+    }
+}
+
+/// A capture handle streamed by `stream`.
+struct StreamSource {
+    /// The label its packets are logged with.
+    mode: String,
+    /// The interface the handle is opened on, reopened by `--reconnect`.
+    interface: String,
+    capture_handle: Capture<Active>,
+}
 
 pub struct Analyzer;
 
 impl Analyzer {
-    fn capture_handle(interface: &str) -> Result<Capture<Active>, AnalyzerError> {
+    /// Opens a capture handle on an interface.
+    ///
+    /// # Returns
+    /// The handle, along with the addresses of the interface.
+    fn capture_handle(
+        interface: &str,
+        capture_options: &CaptureOptions,
+    ) -> Result<(Capture<Active>, Vec<IpAddr>), AnalyzerError> {
         let device = PcapInterface::find_device(interface)?;
-        Ok(PcapInterface::capture_handle(device)?)
+        let addresses = device
+            .addresses
+            .iter()
+            .map(|address| address.addr)
+            .collect();
+        Ok((
+            PcapInterface::capture_handle(device, capture_options)?,
+            addresses,
+        ))
     }
-    /// Captures network packets and saves them to a .pcap file.
+    /// Captures network packets and saves them to a .pcap file or named pipe.
     ///
     /// This function captures packets from a specified network interface and
     /// saves them to a file. It stops capturing after reaching a defined limit
-    /// of packets.
+    /// of packets, if any, or when Ctrl-C is pressed. A repeated capture runs the
+    /// bounded capture several times, saving each run to a numbered file, e.g.
+    /// `capture-001.pcap`. Ctrl-C stops the capture cleanly, both during and
+    /// between runs.
     ///
     /// # Arguments
-    /// * `path` - The directory path where the .pcap file will be saved.
-    /// * `file_name` - The name of the .pcap file.
-    /// * `limit` - The maximum number of packets to capture.
+    /// * `target` - Where the captured packets are saved.
+    /// * `limits` - The conditions under which the capture stops.
+    /// * `schedule` - How many times the capture is run and how far apart.
     /// * `interface_name` - The name of the network interface to capture packets from.
+    /// * `capture_options` - The promiscuous mode and filter of the capture handle.
+    /// * `options` - Options controlling how captured packets are decoded.
+    ///
+    /// # Returns
+    /// The number of packets saved across all runs.
     ///
     /// # Remarks
     /// The function will terminate early and log an error if it encounters issues
     /// such as an invalid path, failure in opening the capture handle, or errors
     /// in reading packets.
-    pub fn basic_capture(path: &str, file_name: &str, limit: usize, interface: &str) {
+    pub fn basic_capture(
+        target: &SaveTarget,
+        limits: &CaptureLimits,
+        schedule: &CaptureSchedule,
+        interface: &str,
+        capture_options: &CaptureOptions,
+        options: &ParseOptions,
+    ) -> usize {
         // Check if the path exists and is a directory
-        let path = Path::new(path);
-        if !path.exists() || !path.is_dir() {
-            error!("Path does not exist or is not a directory");
-            return;
+        if let SaveTarget::File {
+            dir_name, format, ..
+        } = target
+        {
+            let path = Path::new(dir_name);
+            if !path.exists() || !path.is_dir() {
+                error!("Path does not exist or is not a directory");
+                return 0;
+            }
+
+            if *format != FileFormat::Pcap && schedule.rotate.is_some() {
+                error!("Rotation is only supported for pcap files");
+                return 0;
+            }
+        }
+
+        if let Err(err) = interrupt::install() {
+            warn!("Failed to handle Ctrl-C: {:?}", err.to_string());
+        }
+
+        Self::print_csv_header(options);
+
+        let mut total_packets = 0;
+        for run in 1..=schedule.runs {
+            let run_start = Instant::now();
+            let target = match schedule.runs {
+                1 => target.clone(),
+                _ => target.numbered(run),
+            };
+
+            match Self::capture_run(
+                &target,
+                limits,
+                schedule,
+                interface,
+                capture_options,
+                options,
+            ) {
+                Some(packets) => total_packets += packets,
+                None => return total_packets,
+            }
+
+            if run == schedule.runs || interrupt::interrupted() {
+                break;
+            }
+
+            let next_run = run_start + schedule.interval;
+            info!(
+                "Finished run {} of {}, next run in {}",
+                run,
+                schedule.runs,
+                humantime::format_duration(next_run.saturating_duration_since(Instant::now()))
+            );
+
+            if !interrupt::sleep_until(next_run) {
+                break;
+            }
+        }
+
+        if interrupt::interrupted() {
+            info!("Capture interrupted");
         }
 
+        total_packets
+    }
+
+    /// Runs a single bounded capture to `target`.
+    ///
+    /// # Returns
+    /// The number of packets saved, or `None` if the capture could not be started.
+    fn capture_run(
+        target: &SaveTarget,
+        limits: &CaptureLimits,
+        schedule: &CaptureSchedule,
+        interface: &str,
+        capture_options: &CaptureOptions,
+        options: &ParseOptions,
+    ) -> Option<usize> {
+        // PCAPNG files keep the nanosecond timestamps that libpcap can capture
+        let capture_options = &CaptureOptions {
+            nanosecond_timestamps: Self::nanosecond_timestamps(target),
+            ..capture_options.clone()
+        };
+
         // Open a capture handle
-        let capture_handle = match Self::capture_handle(interface) {
-            Ok(c) => c,
-            Err(err) => {
-                error!("{:?}", err.to_string());
-                return;
+        let (capture_handle, local_addresses) =
+            match Self::capture_handle(interface, capture_options) {
+                Ok(c) => c,
+                Err(err) => {
+                    error!("{:?}", err.to_string());
+                    return None;
+                }
+            };
+        let options = &ParseOptions {
+            local_addresses,
+            ..options.clone()
+        };
+
+        // Split the capture into numbered files if requested
+        let rotation = match (target, schedule.rotate) {
+            (
+                SaveTarget::File {
+                    dir_name,
+                    file_name,
+                    ..
+                },
+                Some(limit),
+            ) => Some(FileRotation::new(
+                dir_name,
+                file_name,
+                limit,
+                capture_handle.get_datalink(),
+            )),
+            _ => None,
+        };
+
+        // Create or open the .pcap file or FIFO
+        let (sink, destination) = match target {
+            SaveTarget::File { .. } if rotation.is_some() => {
+                let rotation = rotation.as_ref()?;
+                match rotation.open() {
+                    Ok(f) => (PacketSink::Savefile(f), rotation.pattern()),
+                    Err(err) => {
+                        error!("{:?}", err.to_string());
+                        return None;
+                    }
+                }
+            }
+            SaveTarget::File {
+                dir_name,
+                file_name,
+                format,
+            } => {
+                let new_path =
+                    Path::new(dir_name).join(format!("{}.{}", file_name, format.extension()));
+                let sink = match format {
+                    FileFormat::Pcap => capture_handle
+                        .savefile(&new_path)
+                        .map(PacketSink::Savefile)
+                        .map_err(|err| err.to_string()),
+                    #[cfg(feature = "pcapng")]
+                    FileFormat::Pcapng => File::create(&new_path)
+                        .and_then(|file| {
+                            PcapngWriter::new(
+                                BufWriter::new(file),
+                                capture_handle.get_datalink(),
+                                capture_options.file_snaplen(),
+                                interface,
+                            )
+                        })
+                        .map(PacketSink::Pcapng)
+                        .map_err(|err| err.to_string()),
+                };
+
+                match sink {
+                    Ok(sink) => (sink, new_path.display().to_string()),
+                    Err(err) => {
+                        error!("{:?}", err);
+                        return None;
+                    }
+                }
+            }
+            SaveTarget::Ring { path, size } => {
+                match RingWriter::create(
+                    path,
+                    *size,
+                    capture_handle.get_datalink(),
+                    capture_options.file_snaplen(),
+                ) {
+                    Ok(ring) => (PacketSink::Ring(ring), path.clone()),
+                    Err(err) => {
+                        error!("Failed to create ring file {}: {:?}", path, err.to_string());
+                        return None;
+                    }
+                }
+            }
+            SaveTarget::Fifo { path, mode } => {
+                match FifoWriter::open(
+                    path,
+                    *mode,
+                    capture_handle.get_datalink(),
+                    capture_options.file_snaplen(),
+                ) {
+                    Ok(fifo) => (PacketSink::Fifo(fifo), path.clone()),
+                    Err(err) => {
+                        error!("Failed to open FIFO {}: {:?}", path, err.to_string());
+                        return None;
+                    }
+                }
+            }
+            SaveTarget::Stdout => {
+                match PcapWriter::new(
+                    io::stdout(),
+                    capture_handle.get_datalink(),
+                    capture_options.file_snaplen(),
+                ) {
+                    Ok(stdout) => (PacketSink::Stdout(stdout), "stdout".to_string()),
+                    Err(err) => {
+                        error!("Failed to write to stdout: {:?}", err.to_string());
+                        return None;
+                    }
+                }
             }
         };
 
-        // Create or open the .pcap file
-        let new_path = path.join(format!("{}.pcap", file_name));
-        let pcap_file = match capture_handle.savefile(new_path.clone()) {
-            Ok(f) => f,
-            Err(err) => {
-                error!("{:?}", err.to_string());
-                return;
+        Some(Self::capture_and_process_packets(
+            capture_handle,
+            interface,
+            capture_options,
+            sink,
+            destination,
+            limits,
+            schedule.active_hours.as_ref(),
+            rotation,
+            options,
+        ))
+    }
+
+    /// Decodes a captured packet into a JSON record with its capture timestamp, as
+    /// exported by `--json-out`. Loopback and Wi-Fi frames are decoded like the
+    /// Ethernet frame they carry.
+    ///
+    /// # Returns
+    /// The record, or `None` for frames that carry nothing to decode.
+    fn json_record(
+        header: &PacketHeader,
+        packets: &[u8],
+        linktype: Linktype,
+        options: &ParseOptions,
+    ) -> Option<Value> {
+        let frame = Self::link_ethernet(packets, linktype)?;
+
+        let truncated = (header.len as usize) > packets.len();
+        let mut record = Self::decode_json(&frame, truncated, options)?;
+        record["timestamp"] = json!(format_timestamp(header));
+        Some(record)
+    }
+
+    /// Captures packets on an interface and hands each one, parsed, to a closure,
+    /// for embedding the capture in another program. Nothing is logged, so the
+    /// closure decides what to do with each packet.
+    ///
+    /// # Arguments
+    /// * `interface` - The name of the network interface to capture on.
+    /// * `filter` - A BPF expression selecting the packets to capture, if any.
+    /// * `f` - The closure called with each packet, which returns
+    ///   `ControlFlow::Break` to stop the capture.
+    ///
+    /// # Returns
+    /// `Ok(())` once the closure stops the capture, or an `AnalyzerError` if the
+    /// capture could not be opened or failed.
+    pub fn capture_with<F>(
+        interface: &str,
+        filter: Option<&str>,
+        mut f: F,
+    ) -> Result<(), AnalyzerError>
+    where
+        F: FnMut(&ParsedFrame) -> ControlFlow<()>,
+    {
+        let capture_options = CaptureOptions {
+            filter: filter.map(str::to_string),
+            ..Default::default()
+        };
+        let (capture_handle, _) = Self::capture_handle(interface, &capture_options)?;
+        let linktype = capture_handle.get_datalink();
+
+        for packet in PcapInterface::packets(capture_handle) {
+            let (header, data) = packet.map_err(AnalyzerError::CaptureFailed)?;
+            if f(&Self::parsed_frame(&header, &data, linktype)).is_break() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parses a captured packet for `capture_with`. Packets of datalink types
+    /// other than Ethernet, loopback and Wi-Fi are passed unparsed, as are Wi-Fi
+    /// frames that carry no readable Ethernet frame.
+    fn parsed_frame<'a>(
+        header: &'a PacketHeader,
+        data: &'a [u8],
+        linktype: Linktype,
+    ) -> ParsedFrame<'a> {
+        let ethernet = if Self::decodes_link(linktype) {
+            Self::link_ethernet(data, linktype)
+        } else {
+            None
+        };
+
+        let (vlan_tags, frame) = match ethernet {
+            Some(ethernet) => {
+                let (vlan_tags, untagged) = vlan::strip_tags(&ethernet);
+                let frame = match EthernetFrame::from_bytes(&untagged, false)
+                    .and_then(|frame| frame.parse_next_layer())
+                {
+                    Ok(LayeredData::EthernetFrameData(frame)) => Some(frame),
+                    _ => None,
+                };
+                (vlan_tags, frame)
             }
+            None => (Vec::new(), None),
         };
 
-        Self::capture_and_process_packets(capture_handle, pcap_file, new_path, limit);
+        ParsedFrame {
+            header,
+            data,
+            vlan_tags,
+            frame,
+        }
+    }
+
+    /// Returns `true` if packets saved to `target` keep nanosecond timestamps,
+    /// which only PCAPNG files do.
+    fn nanosecond_timestamps(target: &SaveTarget) -> bool {
+        match target {
+            #[cfg(feature = "pcapng")]
+            SaveTarget::File {
+                format: FileFormat::Pcapng,
+                ..
+            } => true,
+            _ => false,
+        }
     }
 
     /// Captures network packets and writes them to a file.
     ///
     /// The function captures packets in a separate thread and processes them
     /// in the main thread, writing each packet to a file and performing custom
-    /// packet parsing. Capturing stops when the limit is reached or an error occurs.
+    /// packet parsing. Capturing stops when the packet limit, byte budget or
+    /// duration is reached, the memory ceiling is exceeded, Ctrl-C is pressed, the
+    /// packets can no longer be saved or an error occurs.
     ///
     /// # Arguments
     /// * `capture_handle` - A handle to the packet capture device/interface.
-    /// * `pcap_file` - File object to save the captured packets.
-    /// * `new_path` - Path to the file where packets will be saved.
-    /// * `limit` - The maximum number of packets to capture and process.
-    fn capture_and_process_packets<T: Activated + 'static>(
-        capture_handle: Capture<T>,
-        mut pcap_file: Savefile,
-        new_path: PathBuf,
-        limit: usize,
-    ) {
+    /// * `interface` - The name of the interface the handle is opened on.
+    /// * `capture_options` - The options the handle is opened with, also used to
+    ///   reopen it after a capture error if they ask to reconnect.
+    /// * `sink` - The file or pipe to save the captured packets to.
+    /// * `destination` - A description of where packets are saved, for logging.
+    /// * `limits` - The conditions under which the capture stops.
+    /// * `active_hours` - The daily window outside which packets are counted but not saved.
+    /// * `rotation` - The numbered files the capture is split into, if any; `sink`
+    ///   is then the first of them.
+    /// * `options` - Options controlling how captured packets are decoded, and the
+    ///   file they are also appended to as JSON records, if any.
+    ///
+    /// # Returns
+    /// The number of packets saved.
+    fn capture_and_process_packets(
+        capture_handle: Capture<Active>,
+        interface: &str,
+        capture_options: &CaptureOptions,
+        mut sink: PacketSink,
+        destination: String,
+        limits: &CaptureLimits,
+        active_hours: Option<&ActiveHours>,
+        mut rotation: Option<FileRotation>,
+        options: &ParseOptions,
+    ) -> usize {
+        let linktype = capture_handle.get_datalink();
+        let decode = Self::decodes_datalink(linktype);
+
+        // Optionally export the decoded packets alongside the saved capture
+        let mut json_out = match &options.json_out {
+            Some(path) => match OpenOptions::new().create(true).append(true).open(path) {
+                Ok(file) => Some(BufWriter::new(file)),
+                Err(err) => {
+                    error!(
+                        "Failed to open JSON output {}: {:?}",
+                        path.display(),
+                        err.to_string()
+                    );
+                    return 0;
+                }
+            },
+            None => None,
+        };
+
         // Setup for reading packets
         let (send_packets, recv_packets) = channel::<ReadPacketResult>();
 
         // Spawn a thread to read packets
+        let interface = interface.to_string();
+        let capture_options = capture_options.clone();
+        let stop_reader = Arc::new(AtomicBool::new(false));
+        let reader_stopped = Arc::clone(&stop_reader);
         thread::spawn(move || {
-            PcapInterface::read_packets(capture_handle, send_packets);
+            PcapInterface::read_interface_packets(
+                capture_handle,
+                &interface,
+                &capture_options,
+                &reader_stopped,
+                |result| {
+                    send_packets.send(result).is_ok() && !reader_stopped.load(Ordering::SeqCst)
+                },
+            );
         });
 
         // Process packets
+        let mut state = CaptureState::default();
+        state.errors_file = match Self::create_errors_file(options) {
+            Ok(errors_file) => errors_file,
+            Err(err) => {
+                error!("Failed to create errors file: {:?}", err.to_string());
+                return 0;
+            }
+        };
+        let mut parse_pool = Self::parse_pool(options);
+        let mut memory_guard = limits.max_memory.map(MemoryGuard::new);
         let mut total_packets = 0;
-        while let Ok(message) = recv_packets.recv() {
+        let mut total_bytes: u64 = 0;
+        let started = Instant::now();
+        loop {
+            if interrupt::interrupted() {
+                break;
+            }
+
+            // Checked on every wake-up so that the capture ends on an idle interface too
+            if let Some(duration) = limits.duration.filter(|&d| started.elapsed() >= d) {
+                info!(
+                    "Capture duration of {}s elapsed, captured {} packets",
+                    duration.as_secs(),
+                    total_packets
+                );
+                break;
+            }
+
+            // Wake up periodically so that Ctrl-C is noticed on an idle interface, and
+            // the packets decoded since the last one was captured are shown
+            let message = match recv_packets.recv_timeout(interrupt::POLL_INTERVAL) {
+                Ok(message) => message,
+                Err(RecvTimeoutError::Timeout) => {
+                    Self::drain_parse_pool(&mut parse_pool, "CAPTURE", &mut state);
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+
             match message {
-                ReadPacketResult::Success(message) => {
-                    let packet = Packet::new(&message.0, &message.1);
+                ReadPacketResult::Success(mut message) => {
+                    if !Self::within_active_hours(&message.0, active_hours, &mut state) {
+                        continue;
+                    }
+
+                    if let Some(rotation) = rotation.as_mut() {
+                        if rotation.is_full(message.0.caplen) {
+                            if let Err(err) = json_out.as_mut().map_or(Ok(()), Write::flush) {
+                                error!("Failed to write JSON output: {:?}", err.to_string());
+                                break;
+                            }
+                            match rotation.next() {
+                                Ok(savefile) => {
+                                    sink = PacketSink::Savefile(savefile);
+                                    state.packet_index = 0;
+                                }
+                                Err(err) => {
+                                    error!("Failed to rotate capture file: {:?}", err.to_string());
+                                    break;
+                                }
+                            }
+                            info!("Continuing capture in {}", rotation.path().display());
+                        }
+                        rotation.record(message.0.caplen);
+                    }
+
+                    if let Err(err) = sink.write(&message.0, &message.1) {
+                        // A FIFO reader going away is a normal way for a capture to end
+                        if err.kind() == ErrorKind::BrokenPipe {
+                            warn!("Reader of {} disconnected, stopping capture", destination);
+                        } else {
+                            error!("Failed to save packet: {:?}", err.to_string());
+                        }
+                        break;
+                    }
+                    total_bytes += message.1.len() as u64;
+
+                    // Packets are decoded with the microsecond timestamps libpcap
+                    // captures by default
+                    if sink.nanosecond_timestamps() {
+                        message.0.ts.tv_usec /= 1000;
+                    }
+                    Self::count_packet(&message.0, &mut state);
 
-                    pcap_file.write(&packet);
-                    Self::parse_packets(&message.1, "CAPTURE");
+                    if let Some(writer) = json_out.as_mut().filter(|_| decode) {
+                        let record = Self::json_record(&message.0, &message.1, linktype, options);
+                        if let Err(err) =
+                            record.map_or(Ok(()), |record| writeln!(writer, "{}", record))
+                        {
+                            error!("Failed to write JSON output: {:?}", err.to_string());
+                            break;
+                        }
+                    }
+
+                    if decode {
+                        Self::parse_link_packet(
+                            &mut parse_pool,
+                            message,
+                            linktype,
+                            "CAPTURE",
+                            options,
+                            &mut state,
+                        );
+                    }
                     total_packets += 1;
 
-                    if total_packets >= limit {
-                        info!("Saved {} packets to file {:?}", total_packets, new_path);
+                    if limits.packets != 0 && total_packets >= limits.packets {
+                        break;
+                    }
+
+                    if let Some(budget) = limits.bytes.filter(|&budget| total_bytes >= budget) {
+                        info!(
+                            "Saved {} in {} packets, reaching the budget of {}",
+                            format_bytes(total_bytes),
+                            total_packets,
+                            format_bytes(budget)
+                        );
+                        break;
+                    }
+
+                    if Self::memory_exceeded(&mut memory_guard) {
                         break;
                     }
                 }
                 ReadPacketResult::Error(e) => error!("Error: {:?}\n", e),
+                ReadPacketResult::Stats(stats) => {
+                    state.capture_stats = Some(stats);
+                    Self::drain_parse_pool(&mut parse_pool, "CAPTURE", &mut state);
+                }
+            }
+        }
+
+        Self::stop_readers(&recv_packets, &stop_reader, |message| {
+            if let ReadPacketResult::Stats(stats) = message {
+                state.capture_stats = Some(stats);
             }
+        });
+
+        Self::finish_parse_pool(&mut parse_pool, "CAPTURE", &mut state);
+
+        if let Err(err) = json_out.as_mut().map_or(Ok(()), Write::flush) {
+            error!("Failed to write JSON output: {:?}", err.to_string());
         }
+
+        // Close the file before reporting it, so that an interrupted capture is complete
+        drop(sink);
+        info!("Saved {} packets to {:?}", total_packets, destination);
+
+        // An empty capture is otherwise easily mistaken for a successful one
+        if total_packets == 0 {
+            warn!("Captured 0 packets - check your interface and capture privileges");
+        }
+
+        Self::log_summary(&state, options);
+        total_packets
     }
 
-    /// Captures live network packets on the specified interface.
-    /// The function locates the specified network interface and opens a capture handle
-    /// for it. Upon successful acquisition of the capture handle, it initiates the
-    /// streaming of captured packets. If any error occurs during device finding or
-    /// handle creation, the error is logged and the function returns early.
+    /// Captures live network packets on the specified interfaces.
+    /// The function locates the specified network interfaces and opens a capture
+    /// handle for each of them, then streams their packets as one capture. With
+    /// several interfaces, each packet is labelled with the interface it was captured
+    /// on. An interface that cannot be opened is logged and skipped, and the function
+    /// returns early if none could be opened.
     ///
     /// # Arguments
-    /// * `interface` - The name of the network interface to capture packets from.
-    pub fn live_capture(interface: &str) {
-        // Open a capture handle
-        let capture_handle = match Self::capture_handle(interface) {
-            Ok(c) => c,
-            Err(err) => {
-                error!("{:?}", err.to_string());
-                return;
+    /// * `interfaces` - The names of the network interfaces to capture packets from.
+    /// * `capture_options` - The promiscuous mode and filter of the capture handles.
+    /// * `options` - Options controlling how captured packets are decoded.
+    /// * `stream_options` - Options controlling where the live stream is sent.
+    pub fn live_capture(
+        interfaces: &[String],
+        capture_options: &CaptureOptions,
+        options: &ParseOptions,
+        stream_options: &StreamOptions,
+    ) {
+        let mut sources = Vec::new();
+        let mut local_addresses = Vec::new();
+        for interface in interfaces {
+            match Self::capture_handle(interface, capture_options) {
+                Ok((capture_handle, addresses)) => {
+                    local_addresses.extend(addresses);
+                    sources.push(StreamSource {
+                        mode: if interfaces.len() == 1 {
+                            "LIVE".to_string()
+                        } else {
+                            interface.clone()
+                        },
+                        interface: interface.clone(),
+                        capture_handle,
+                    });
+                }
+                Err(err) => error!("{}: {:?}", interface, err.to_string()),
             }
+        }
+
+        if sources.is_empty() {
+            return;
+        }
+
+        let options = ParseOptions {
+            local_addresses,
+            ..options.clone()
         };
 
-        Self::stream(capture_handle);
+        Self::stream(sources, capture_options, &options, stream_options);
     }
 
-    /// Streams and processes network packets from a capture handle.
+    /// Streams and processes network packets from one or more capture handles.
     ///
     /// # Arguments
-    /// * `capture_handle` - A handle for capturing packets, compliant with `Activated` and `'static`.
+    /// * `sources` - The handles for capturing packets, with the interfaces they are opened on.
+    /// * `capture_options` - The options the handles are opened with, also used to
+    ///   reopen them after a capture error if they ask to reconnect.
+    /// * `options` - Options controlling how captured packets are decoded.
+    /// * `stream_options` - Options controlling where the live stream is sent.
     ///
-    /// The function sets up a channel for packet communication and spawns a new thread
-    /// to read packets using the provided `capture_handle`. Packets read are sent over the
-    /// channel to the main thread for processing. The main thread continuously receives
-    /// packets and processes them until an error occurs or there are no more packets.
+    /// The function sets up a channel for packet communication and spawns a thread per
+    /// capture handle to read its packets. Packets read are sent over the channel,
+    /// tagged with the index of their source, to the main thread for processing. The
+    /// main thread continuously receives packets and processes them until an error
+    /// occurs, there are no more packets or Ctrl-C is pressed, and then logs a summary
+    /// of the stream. If a stats interval is set, the packet and byte rates are logged
+    /// alongside, or instead of, the decoded packets.
 
-    fn stream<T: Activated + 'static>(capture_handle: Capture<T>) {
-        let (send_packets, recv_packets) = channel::<ReadPacketResult>();
+    fn stream(
+        sources: Vec<StreamSource>,
+        capture_options: &CaptureOptions,
+        options: &ParseOptions,
+        stream_options: &StreamOptions,
+    ) {
+        let linktypes = sources
+            .iter()
+            .map(|source| source.capture_handle.get_datalink())
+            .collect::<Vec<Linktype>>();
 
-        thread::spawn(move || {
-            PcapInterface::read_packets(capture_handle, send_packets);
-        });
+        // Optionally serve the capture over HTTP for remote viewers
+        let http_server = match &stream_options.serve_http {
+            Some(addr) => {
+                match PcapHttpServer::start(addr, linktypes[0], capture_options.file_snaplen()) {
+                    Ok(server) => Some(server),
+                    Err(err) => {
+                        error!("Failed to serve capture on {}: {:?}", addr, err.to_string());
+                        return;
+                    }
+                }
+            }
+            None => None,
+        };
+
+        // Optionally serve live statistics to a browser
+        let mut dashboard = match &stream_options.dashboard {
+            Some(addr) => match Dashboard::start(addr) {
+                Ok(dashboard) => Some(dashboard),
+                Err(err) => {
+                    error!(
+                        "Failed to serve dashboard on {}: {:?}",
+                        addr,
+                        err.to_string()
+                    );
+                    return;
+                }
+            },
+            None => None,
+        };
+
+        // Optionally export the capture counters to Prometheus
+        let mut metrics = match &stream_options.metrics_addr {
+            Some(addr) => match MetricsServer::start(addr) {
+                Ok(metrics) => Some(metrics),
+                Err(err) => {
+                    error!("Failed to serve metrics on {}: {:?}", addr, err.to_string());
+                    return;
+                }
+            },
+            None => None,
+        };
+
+        if let Err(err) = interrupt::install() {
+            warn!("Failed to handle Ctrl-C: {:?}", err.to_string());
+        }
+
+        let decodes = linktypes
+            .iter()
+            .map(|&linktype| Self::decodes_datalink(linktype))
+            .collect::<Vec<bool>>();
+        let mut source_stats: Vec<Option<Stat>> = vec![None; sources.len()];
+        let (send_packets, recv_packets) = channel::<(usize, ReadPacketResult)>();
+
+        let stop_readers = Arc::new(AtomicBool::new(false));
+
+        let mut modes = Vec::new();
+        for (index, source) in sources.into_iter().enumerate() {
+            modes.push(source.mode);
+
+            let send_packets = send_packets.clone();
+            let capture_options = capture_options.clone();
+            let readers_stopped = Arc::clone(&stop_readers);
+            thread::spawn(move || {
+                PcapInterface::read_interface_packets(
+                    source.capture_handle,
+                    &source.interface,
+                    &capture_options,
+                    &readers_stopped,
+                    |result| {
+                        send_packets.send((index, result)).is_ok()
+                            && !readers_stopped.load(Ordering::SeqCst)
+                    },
+                );
+            });
+        }
+        // The channel disconnects once every reader thread has stopped
+        drop(send_packets);
+
+        Self::print_csv_header(options);
+
+        // Packets decoded on the pool are emitted with a single label
+        let mut parse_pool = if modes.len() == 1 {
+            Self::parse_pool(options)
+        } else {
+            if options.parse_threads > 1 {
+                warn!("Packets of several interfaces are decoded on a single thread");
+            }
+            None
+        };
+
+        let mut state = CaptureState::default();
+        state.errors_file = match Self::create_errors_file(options) {
+            Ok(errors_file) => errors_file,
+            Err(err) => {
+                error!("Failed to create errors file: {:?}", err.to_string());
+                return;
+            }
+        };
+        let mut memory_guard = stream_options.max_memory.map(MemoryGuard::new);
+        let mut rate_meter = stream_options.stats_interval.map(RateMeter::new);
+        loop {
+            if interrupt::interrupted() {
+                info!("Stream interrupted");
+                break;
+            }
+
+            // Checked on every wake-up so that an idle interface still reports its rate
+            if let Some((packets, bytes)) = rate_meter.as_mut().and_then(RateMeter::report) {
+                info!("{}", format_rate(packets, bytes));
+            }
+
+            // Wake up periodically so that Ctrl-C is noticed on an idle interface, and
+            // the packets decoded since the last one was captured are shown
+            let (index, message) = match recv_packets.recv_timeout(interrupt::POLL_INTERVAL) {
+                Ok(message) => message,
+                Err(RecvTimeoutError::Timeout) => {
+                    Self::drain_parse_pool(&mut parse_pool, &modes[0], &mut state);
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            let linktype = linktypes[index];
 
-        while let Ok(message) = recv_packets.recv() {
             match message {
                 ReadPacketResult::Success(message) => {
-                    Self::parse_packets(&message.1, "LIVE");
+                    if !Self::within_active_hours(
+                        &message.0,
+                        stream_options.active_hours.as_ref(),
+                        &mut state,
+                    ) {
+                        continue;
+                    }
+                    Self::count_packet(&message.0, &mut state);
+
+                    // The served pcap stream has the datalink type of the first interface
+                    if let Some(server) = http_server.as_ref().filter(|_| linktype == linktypes[0])
+                    {
+                        server.broadcast(&message.0, &message.1);
+                    }
+
+                    if let Some(meter) = rate_meter.as_mut() {
+                        meter.record(message.0.len);
+                    }
+
+                    if decodes[index] {
+                        if stream_options.stats_only {
+                            Self::track_link_packet(&message.1, linktype, options, &mut state);
+                        } else {
+                            Self::parse_link_packet(
+                                &mut parse_pool,
+                                message,
+                                linktype,
+                                &modes[index],
+                                options,
+                                &mut state,
+                            );
+                        }
+                    }
+
+                    if let Some(dashboard) = dashboard.as_mut() {
+                        dashboard.publish(&state);
+                    }
+
+                    if let Some(metrics) = metrics.as_mut() {
+                        metrics.publish(&state);
+                    }
+
+                    if Self::memory_exceeded(&mut memory_guard) {
+                        break;
+                    }
+                }
+                ReadPacketResult::Error(e) => error!("{}: Error: {:?}\n", modes[index], e),
+                ReadPacketResult::Stats(stats) => {
+                    source_stats[index] = Some(stats);
+                    state.capture_stats = Self::combined_stats(&source_stats);
+                    Self::drain_parse_pool(&mut parse_pool, &modes[0], &mut state);
+
+                    // Drops are worth exporting on an idle interface too
+                    if let Some(metrics) = metrics.as_mut() {
+                        metrics.publish(&state);
+                    }
                 }
-                ReadPacketResult::Error(e) => error!("Error: {:?}\n", e),
             }
         }
-    }
 
-    pub fn show_default_interface() {
-        match PcapInterface::default_interface() {
-            Ok(Some(device)) => info!("Default interface: {}", device.name),
-            Ok(None) => info!("No interface found"),
-            Err(e) => error!("Failed to find default interface {:?}", e.to_string()),
+        Self::stop_readers(&recv_packets, &stop_readers, |(index, message)| {
+            if let ReadPacketResult::Stats(stats) = message {
+                source_stats[index] = Some(stats);
+            }
+        });
+        state.capture_stats = Self::combined_stats(&source_stats);
+
+        Self::finish_parse_pool(&mut parse_pool, &modes[0], &mut state);
+
+        // Keep the JSON and CSV output free of log lines so it can be piped
+        if options.format == OutputFormat::Text {
+            Self::log_summary(&state, options);
         }
     }
 
-    pub fn list_interfaces() {
-        match PcapInterface::devices() {
-            Ok(interfaces) => {
-                let interfaces = interfaces
-                    .into_iter()
-                    .map(|interface| interface.name)
-                    .collect::<Vec<String>>();
+    /// Reads and decodes every packet of one or more .pcap files.
+    ///
+    /// Packets are decoded with the same pipeline as live captures and emitted in
+    /// the output format selected in `options`, which allows the JSON output of a
+    /// known capture to be diffed against a golden file. Multiple files, such as a
+    /// rotated capture set, are read as one capture in timestamp order, with stats
+    /// and flows carried across file boundaries. Reading stops cleanly at the end
+    /// of the last file.
+    ///
+    /// # Arguments
+    /// * `paths` - The paths of the .pcap files to read, which must share a datalink type.
+    /// * `options` - Options controlling how packets are decoded and emitted.
+    pub fn analyze_files(paths: &[String], options: &ParseOptions) {
+        let mut files = match CaptureFiles::open(paths) {
+            Ok(files) => files,
+            Err(err) => {
+                error!("{:?}", err.to_string());
+                return;
+            }
+        };
+
+        let linktype = files.linktype();
+        if !Self::decodes_datalink(linktype) {
+            return;
+        }
+
+        Self::print_csv_header(options);
 
-                info!("{:?}", interfaces)
+        let mut state = CaptureState::default();
+        state.errors_file = match Self::create_errors_file(options) {
+            Ok(errors_file) => errors_file,
+            Err(err) => {
+                error!("Failed to create errors file: {:?}", err.to_string());
+                return;
             }
-            Err(e) => error!("Failed to list interfaces {:}", e.to_string()),
+        };
+        while let Some(packet) = files.next_packet() {
+            Self::count_packet(&packet.0, &mut state);
+            Self::parse_link_packet(&mut None, packet, linktype, "FILE", options, &mut state);
         }
-    }
 
-    fn parse_packets(packets: &[u8], mode: &str) {
-        let ethernet_frame = EthernetFrame::from_bytes(packets, false);
+        // Keep the JSON and CSV output free of log lines so it can be diffed and piped
+        if options.format == OutputFormat::Text {
+            Self::log_summary(&state, options);
+        }
+    }
 
-        match ethernet_frame {
-            Ok(frame) => {
-                let layered_data = frame.parse_next_layer();
+    /// Replays the packets of a saved .pcap file through the same decode pipeline
+    /// as a live capture, labelling each packet with the `FILE` mode.
+    ///
+    /// A missing or unreadable file is logged as an `AnalyzerError`, and replay stops
+    /// cleanly at the end of the file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the .pcap file to replay.
+    /// * `options` - Options controlling how packets are decoded and emitted.
+    pub fn read_file(path: &str, options: &ParseOptions) {
+        Self::analyze_files(&[path.to_string()], options);
+    }
 
-                // The parsing of network packets begins with the Ethernet frame, which is the
-                // foundational layer. Other enum variants representing different layers or
-                // types of data are not considered at this stage.
-                if let Ok(LayeredData::EthernetFrameData(frame)) = layered_data {
-                    let mut log_msg = format_packets(frame);
-                    log_msg.push_str(&format!(" | {} bytes", packets.len()));
+    /// Extracts the objects transferred in a capture, such as HTTP response bodies,
+    /// and writes each of them to a file.
+    ///
+    /// The TCP connections of the capture are reassembled first, so objects spanning
+    /// many segments, including out-of-order and retransmitted ones, are recovered.
+    ///
+    /// # Arguments
+    /// * `paths` - The paths of the .pcap files to read, which must share a datalink type.
+    /// * `protocol` - The protocol to extract objects from.
+    /// * `out_dir` - The directory to write the objects to, created if missing.
+    pub fn extract_objects(paths: &[String], protocol: ExtractProtocol, out_dir: &str) {
+        if let Err(err) = fs::create_dir_all(out_dir) {
+            error!("Failed to create {}: {:?}", out_dir, err.to_string());
+            return;
+        }
 
-                    info!("{}: {} | {} bytes\n", mode, log_msg, packets.len());
-                }
+        let mut files = match CaptureFiles::open(paths) {
+            Ok(files) => files,
+            Err(err) => {
+                error!("{:?}", err.to_string());
+                return;
             }
-            Err(e) => error!("Error parsing packet {:?}", e.to_string()),
+        };
+
+        let mut reassembler = TcpReassembler::default();
+        while let Some((_, data)) = files.next_packet() {
+            reassembler.record(&data);
         }
+
+        let mut extracted = 0;
+        for conversation in reassembler.finish() {
+            let objects = match protocol {
+                ExtractProtocol::Http => http_objects::extract(&conversation),
+            };
+
+            if !objects.is_empty() && !conversation.complete {
+                warn!(
+                    "Segments of {} are missing, later objects may be lost",
+                    format_flow_key(&conversation.key)
+                );
+            }
+
+            for object in objects {
+                extracted += 1;
+                let path = Path::new(out_dir).join(http_objects::file_name(extracted, &object));
+
+                match fs::write(&path, &object.body) {
+                    Ok(()) => info!(
+                        "Extracted {} ({}, {}) to {}",
+                        object.url,
+                        object.content_type.as_deref().unwrap_or("unknown type"),
+                        format_bytes(object.body.len() as u64),
+                        path.display()
+                    ),
+                    Err(err) => error!("Failed to write {}: {:?}", path.display(), err.to_string()),
+                }
+            }
+        }
+
+        info!("Extracted {} objects to {}", extracted, out_dir);
+    }
+
+    /// Reassembles one TCP connection of a capture and prints the bytes sent in each
+    /// direction, the client's first, like Wireshark's "Follow TCP Stream".
+    ///
+    /// Segments are ordered by their sequence numbers, so out-of-order segments are
+    /// placed and retransmissions are shown once. A direction with missing segments
+    /// ends at the first gap.
+    ///
+    /// # Arguments
+    /// * `paths` - The paths of the .pcap files to read, which must share a datalink type.
+    /// * `source` - The address and port the connection was opened from.
+    /// * `destination` - The address and port the connection was opened to.
+    /// * `hex` - Print the bytes as a hexdump instead of as text.
+    pub fn follow_stream(paths: &[String], source: Endpoint, destination: Endpoint, hex: bool) {
+        let mut files = match CaptureFiles::open(paths) {
+            Ok(files) => files,
+            Err(err) => {
+                error!("{:?}", err.to_string());
+                return;
+            }
+        };
+
+        let (key, direction) = FlowKey::new(IP_PROTOCOL_TCP, source, destination);
+        let mut reassembler = TcpReassembler::default();
+        while let Some((_, data)) = files.next_packet() {
+            let (_, frame) = vlan::strip_tags(&data);
+            if FlowKey::from_frame(&frame).is_some_and(|(frame_key, _)| frame_key == key) {
+                reassembler.record(&frame);
+            }
+        }
+
+        let conversation = match reassembler.finish().pop() {
+            Some(conversation) => conversation,
+            None => {
+                warn!("No packets of {} found", format_flow_key(&key));
+                return;
+            }
+        };
+
+        if !conversation.complete {
+            warn!(
+                "Segments of {} are missing, the stream ends at the first gap",
+                format_flow_key(&key)
+            );
+        }
+
+        let (sent, received) = match direction {
+            Direction::AToB => (conversation.a_to_b, conversation.b_to_a),
+            Direction::BToA => (conversation.b_to_a, conversation.a_to_b),
+        };
+
+        for (from, to, data) in [(source, destination, sent), (destination, source, received)] {
+            info!("{}", format_stream_direction(&from, &to, data.len()));
+
+            if hex {
+                println!("{}", hexdump(&data).trim_start_matches('\n'));
+            } else {
+                println!("{}", format_stream_text(&data));
+            }
+        }
+    }
+
+    /// Shows the default network interface, the one `capture` uses when no
+    /// interface is given, as picked by `PcapInterface::best_interface`.
+    ///
+    /// # Arguments
+    /// * `json` - Print a JSON array holding the interface to stdout instead of logging it.
+    ///
+    /// # Returns
+    /// `Err(AnalyzerError::NoInterfaceFound)` if there is no default interface, or
+    /// `Err(AnalyzerError::DeviceLookupFailed)` if the lookup failed.
+    pub fn show_default_interface(json: bool) -> Result<(), AnalyzerError> {
+        let device = match PcapInterface::best_interface() {
+            Ok(Some(device)) => device,
+            Ok(None) => return Err(AnalyzerError::NoInterfaceFound),
+            Err(e) => {
+                error!("Failed to find default interface {:?}", e.to_string());
+                return Err(AnalyzerError::DeviceLookupFailed);
+            }
+        };
+
+        if json {
+            println!("{}", json!([Self::device_json(&device)]));
+        } else {
+            info!("Default interface: {}", device.name);
+        }
+
+        Ok(())
+    }
+
+    /// Lists all network interfaces as a table of their descriptions, addresses
+    /// with netmasks and status flags.
+    ///
+    /// # Arguments
+    /// * `json` - Print a JSON array of the interfaces to stdout instead of the table.
+    ///
+    /// # Returns
+    /// `Err(AnalyzerError::NoInterfaceFound)` if there are no interfaces, or
+    /// `Err(AnalyzerError::DeviceLookupFailed)` if they could not be enumerated.
+    pub fn list_interfaces(json: bool) -> Result<(), AnalyzerError> {
+        let devices = match PcapInterface::devices() {
+            Ok(devices) if devices.is_empty() => return Err(AnalyzerError::NoInterfaceFound),
+            Ok(devices) => devices,
+            Err(e) => {
+                error!("Failed to list interfaces {:}", e.to_string());
+                return Err(AnalyzerError::DeviceLookupFailed);
+            }
+        };
+
+        if json {
+            let devices = devices
+                .iter()
+                .map(Self::device_json)
+                .collect::<Vec<Value>>();
+            println!("{}", json!(devices));
+        } else {
+            // Printed rather than logged so that the table columns line up
+            println!("{}", format_interfaces(&devices));
+        }
+
+        Ok(())
+    }
+
+    /// Lists the datalink types an interface supports, such as `EN10MB` and
+    /// `IEEE802_11_RADIO` for a Wi-Fi interface that can capture in monitor mode.
+    ///
+    /// # Arguments
+    /// * `interface` - The name of the interface, which is opened to query it.
+    /// * `json` - Print a JSON array of the datalink types to stdout instead of the table.
+    ///
+    /// # Returns
+    /// `Err(AnalyzerError)` if the interface could not be found or opened, or its
+    /// datalink types could not be listed.
+    pub fn list_datalinks(interface: &str, json: bool) -> Result<(), AnalyzerError> {
+        let (capture_handle, _) = Self::capture_handle(interface, &CaptureOptions::default())?;
+        let default = capture_handle.get_datalink();
+        let datalinks = capture_handle
+            .list_datalinks()
+            .map_err(AnalyzerError::FailedToListDatalinks)?;
+
+        if json {
+            let datalinks = datalinks
+                .iter()
+                .map(|datalink| {
+                    json!({
+                        "name": datalink.get_name().ok(),
+                        "description": datalink.get_description().ok(),
+                        "value": datalink.0,
+                        "default": *datalink == default,
+                    })
+                })
+                .collect::<Vec<Value>>();
+            println!("{}", json!(datalinks));
+        } else {
+            // Printed rather than logged so that the table columns line up
+            println!("{}", format_datalinks(&datalinks, default));
+        }
+
+        Ok(())
+    }
+
+    /// Describes a network interface as a JSON object for machine consumption.
+    fn device_json(device: &Device) -> Value {
+        let addresses = device
+            .addresses
+            .iter()
+            .map(|address| address.addr.to_string())
+            .collect::<Vec<String>>();
+
+        json!({
+            "name": device.name,
+            "description": device.desc,
+            "addresses": addresses,
+            "loopback": device.flags.is_loopback(),
+            "up": device.flags.is_up(),
+            "running": device.flags.is_running(),
+        })
+    }
+
+    fn parse_packets(
+        header: &PacketHeader,
+        packets: &[u8],
+        mode: &str,
+        options: &ParseOptions,
+        state: &mut CaptureState,
+    ) {
+        Self::track_packet(packets, options, state);
+        let decoded = Self::decode_packet(header, packets, options);
+        Self::emit(
+            decoded.failures_only(options).fitted(options),
+            (state.packet_index, state.packet_delta),
+            mode,
+            state,
+        );
+    }
+
+    /// Checks whether packets of a datalink type can be decoded, which requires
+    /// Ethernet, BSD loopback or 802.11 framing. Otherwise every packet would fail
+    /// to decode, so a single warning is logged instead.
+    ///
+    /// # Returns
+    /// `true` if packets of this datalink type should be decoded.
+    fn decodes_datalink(linktype: Linktype) -> bool {
+        if Self::decodes_link(linktype) {
+            return true;
+        }
+
+        warn!(
+            "Datalink type {} is not supported, packets will not be decoded",
+            linktype
+                .get_name()
+                .unwrap_or_else(|_| linktype.0.to_string())
+        );
+        false
+    }
+
+    /// Returns `true` for the datalink types whose frames are decoded.
+    fn decodes_link(linktype: Linktype) -> bool {
+        linktype == Linktype::ETHERNET || loopback::is_loopback(linktype) || wifi::is_wifi(linktype)
+    }
+
+    /// Returns the Ethernet frame of a packet: loopback and Wi-Fi frames are
+    /// rebuilt as the Ethernet frame they carry, while packets of other datalink
+    /// types are taken as they are.
+    ///
+    /// # Returns
+    /// The Ethernet frame, or `None` for loopback and Wi-Fi frames that carry no
+    /// readable packet.
+    fn link_ethernet(packets: &[u8], linktype: Linktype) -> Option<Cow<'_, [u8]>> {
+        if loopback::is_loopback(linktype) {
+            LoopbackFrame::from_bytes(packets, linktype)?
+                .to_ethernet()
+                .map(Cow::Owned)
+        } else if wifi::is_wifi(linktype) {
+            WifiFrame::from_bytes(packets, linktype)?
+                .to_ethernet()
+                .map(Cow::Owned)
+        } else {
+            Some(Cow::Borrowed(packets))
+        }
+    }
+
+    /// Parses a packet according to the datalink type of its capture: loopback
+    /// frames with `parse_loopback`, Wi-Fi frames with `parse_wifi`, Ethernet
+    /// frames like `parse_packets_in`.
+    fn parse_link_packet(
+        parse_pool: &mut Option<ParsePool<DecodedPacket>>,
+        packet: (PacketHeader, Vec<u8>),
+        linktype: Linktype,
+        mode: &str,
+        options: &ParseOptions,
+        state: &mut CaptureState,
+    ) {
+        if loopback::is_loopback(linktype) {
+            Self::parse_loopback(&packet.0, &packet.1, linktype, mode, options, state);
+        } else if wifi::is_wifi(linktype) {
+            Self::parse_wifi(&packet.0, &packet.1, linktype, mode, options, state);
+        } else {
+            Self::parse_packets_in(parse_pool, packet, mode, options, state);
+        }
+    }
+
+    /// Parses a DLT_NULL or DLT_LOOP frame, as captured on BSD and macOS loopback
+    /// interfaces.
+    ///
+    /// The IP packet following the address family header is rebuilt into an
+    /// Ethernet frame, then tracked and decoded like one, but shown without the
+    /// synthetic Ethernet header. Frames of other address families are skipped.
+    fn parse_loopback(
+        header: &PacketHeader,
+        packets: &[u8],
+        linktype: Linktype,
+        mode: &str,
+        options: &ParseOptions,
+        state: &mut CaptureState,
+    ) {
+        let frame = match LoopbackFrame::from_bytes(packets, linktype) {
+            Some(frame) => frame,
+            None => return,
+        };
+        let inner = match frame.to_ethernet() {
+            Some(inner) => inner,
+            None => return,
+        };
+
+        Self::track_packet(&inner, options, state);
+
+        let decoded = Self::decode_frame(
+            header,
+            packets,
+            &inner,
+            Some(&RebuiltLink::Loopback(&frame)),
+            options,
+        );
+        Self::emit(
+            decoded.failures_only(options).fitted(options),
+            (state.packet_index, state.packet_delta),
+            mode,
+            state,
+        );
+    }
+
+    /// Parses an 802.11 frame, as captured by a monitor-mode interface with or
+    /// without a radiotap header.
+    ///
+    /// The radio information and frame type are always shown. The Ethernet frame of
+    /// an unencrypted data frame is rebuilt, then tracked and decoded like one.
+    /// Management, control and encrypted data frames carry nothing else to decode.
+    /// Those are rejected by a post-parse filter, and have no row in CSV output.
+    fn parse_wifi(
+        header: &PacketHeader,
+        packets: &[u8],
+        linktype: Linktype,
+        mode: &str,
+        options: &ParseOptions,
+        state: &mut CaptureState,
+    ) {
+        let frame = match WifiFrame::from_bytes(packets, linktype) {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        let decoded = match frame.to_ethernet() {
+            Some(inner) => {
+                Self::track_packet(&inner, options, state);
+                Self::decode_frame(
+                    header,
+                    packets,
+                    &inner,
+                    Some(&RebuiltLink::Wifi(&frame)),
+                    options,
+                )
+            }
+            None => Self::decode_wifi_header(header, packets, &frame, options),
+        };
+
+        Self::emit(
+            decoded.failures_only(options).fitted(options),
+            (state.packet_index, state.packet_delta),
+            mode,
+            state,
+        );
+    }
+
+    /// Decodes an 802.11 frame that carries no Ethernet frame, showing only its
+    /// radio information and frame type.
+    fn decode_wifi_header(
+        header: &PacketHeader,
+        packets: &[u8],
+        frame: &WifiFrame,
+        options: &ParseOptions,
+    ) -> DecodedPacket {
+        if !options.filter.is_empty() {
+            return DecodedPacket::Empty;
+        }
+
+        match options.format {
+            OutputFormat::Csv => DecodedPacket::Empty,
+            OutputFormat::Json => DecodedPacket::Json(
+                json!({
+                    "wifi": Self::wifi_json(frame),
+                    "length": packets.len(),
+                }),
+                None,
+            ),
+            OutputFormat::Text => DecodedPacket::Text(format!(
+                "{} | {} | {} bytes{}",
+                format_timestamp(header),
+                format_wifi(frame, options),
+                packets.len(),
+                Self::hexdump_if(packets, options)
+            )),
+        }
+    }
+
+    /// The radio information and 802.11 header of a Wi-Fi frame as JSON.
+    fn wifi_json(frame: &WifiFrame) -> Value {
+        let radiotap = frame.radiotap.unwrap_or_default();
+        json!({
+            "type": wifi::frame_name(frame.frame_type, frame.subtype),
+            "frequency": radiotap.frequency,
+            "channel": radiotap.channel(),
+            "signal": radiotap.signal,
+            "addresses": frame
+                .addresses
+                .iter()
+                .map(encapsulation::format_mac)
+                .collect::<Vec<String>>(),
+            "protected": frame.is_protected(),
+        })
+    }
+
+    /// Updates the capture state with a packet of a capture with the given datalink
+    /// type, without decoding it.
+    fn track_link_packet(
+        packets: &[u8],
+        linktype: Linktype,
+        options: &ParseOptions,
+        state: &mut CaptureState,
+    ) {
+        if let Some(frame) = Self::link_ethernet(packets, linktype) {
+            Self::track_packet(&frame, options, state);
+        }
+    }
+
+    /// Starts a pool of parse threads if more than one was requested.
+    fn parse_pool(options: &ParseOptions) -> Option<ParsePool<DecodedPacket>> {
+        if options.parse_threads <= 1 {
+            return None;
+        }
+
+        let pool_options = options.clone();
+        Some(ParsePool::new(
+            options.parse_threads,
+            move |header, data| {
+                Self::decode_packet(header, data, &pool_options)
+                    .failures_only(&pool_options)
+                    .fitted(&pool_options)
+            },
+        ))
+    }
+
+    /// Parses a packet like `parse_packets`, decoding it on the parse pool if there
+    /// is one. Capture state is still tracked on the calling thread, and decoded
+    /// packets are emitted in capture order as they become ready.
+    fn parse_packets_in(
+        parse_pool: &mut Option<ParsePool<DecodedPacket>>,
+        (header, data): (PacketHeader, Vec<u8>),
+        mode: &str,
+        options: &ParseOptions,
+        state: &mut CaptureState,
+    ) {
+        let pool = match parse_pool {
+            Some(pool) => pool,
+            None => return Self::parse_packets(&header, &data, mode, options, state),
+        };
+
+        Self::track_packet(&data, options, state);
+        pool.submit(header, data);
+        state
+            .pool_packets
+            .push_back((state.packet_index, state.packet_delta));
+
+        Self::drain_parse_pool(parse_pool, mode, state);
+    }
+
+    /// Emits the packets the parse pool has finished decoding, if there is one,
+    /// without waiting for the others.
+    fn drain_parse_pool(
+        parse_pool: &mut Option<ParsePool<DecodedPacket>>,
+        mode: &str,
+        state: &mut CaptureState,
+    ) {
+        if let Some(pool) = parse_pool {
+            for decoded in pool.ready() {
+                Self::emit_pooled(decoded, mode, state);
+            }
+        }
+    }
+
+    /// Emits the packets still being decoded on the parse pool, if there is one.
+    fn finish_parse_pool(
+        parse_pool: &mut Option<ParsePool<DecodedPacket>>,
+        mode: &str,
+        state: &mut CaptureState,
+    ) {
+        if let Some(pool) = parse_pool {
+            for decoded in pool.finish() {
+                Self::emit_pooled(decoded, mode, state);
+            }
+        }
+    }
+
+    /// Updates the capture state with a packet: ARP bindings, talkers, flows and stats.
+    /// VLAN tagged frames are tracked by their inner packet.
+    fn track_packet(packets: &[u8], options: &ParseOptions, state: &mut CaptureState) {
+        let (_, untagged) = vlan::strip_tags(packets);
+        let packets = &*untagged;
+
+        Self::detect_arp_conflict(packets, state);
+        Self::detect_top_talker(packets, options, state);
+        Self::detect_scan(packets, options, state);
+        state.flows.record(packets);
+        state.conversations.record(packets);
+        state.stats.record(packets);
+    }
+
+    /// Decodes a packet into the output selected in `options`. This does not depend
+    /// on the capture state, so it can run on any thread.
+    fn decode_packet(
+        header: &PacketHeader,
+        packets: &[u8],
+        options: &ParseOptions,
+    ) -> DecodedPacket {
+        Self::decode_frame(header, packets, packets, None, options)
+    }
+
+    /// Decodes the Ethernet frame of a packet into the output selected in `options`.
+    ///
+    /// # Arguments
+    /// * `header` - The capture header of the packet.
+    /// * `packets` - The raw bytes of the packet, as captured.
+    /// * `frame` - The Ethernet frame of the packet: the packet itself, or the frame
+    ///   rebuilt from its link layer.
+    /// * `link` - The link layer `frame` was rebuilt from, if it was.
+    /// * `options` - Options controlling how the packet is decoded.
+    fn decode_frame(
+        header: &PacketHeader,
+        packets: &[u8],
+        frame: &[u8],
+        link: Option<&RebuiltLink>,
+        options: &ParseOptions,
+    ) -> DecodedPacket {
+        if Self::filtered_out(frame, options) {
+            return DecodedPacket::Empty;
+        }
+
+        if options.format == OutputFormat::Csv {
+            return DecodedPacket::Row(format_packets_csv(frame, header));
+        }
+
+        // A packet cut short by the snaplen fails to decode like a malformed one
+        let truncated = (header.len as usize) > packets.len();
+
+        // Packets are only marked on live captures, whose local addresses are known
+        let direction = (!options.local_addresses.is_empty())
+            .then(|| PacketDirection::of_frame(frame, &options.local_addresses));
+
+        // The checksums of a truncated packet cannot be recomputed
+        let checksums = (options.verify_checksums && !truncated).then(|| checksum::verify(frame));
+
+        // The link layer a frame was rebuilt from is shown in place of its Ethernet header
+        let link_text = match link {
+            Some(link) => format!("{} | ", link.text(options)),
+            None => String::new(),
+        };
+
+        if options.format == OutputFormat::Json {
+            let mut value = match (Self::decode_json(frame, truncated, options), link) {
+                (Some(value), _) => value,
+                (None, Some(_)) => json!({}),
+                (None, None) => return DecodedPacket::Empty,
+            };
+            if let Some(link) = link {
+                if let Some(layers) = value.as_object_mut() {
+                    layers.remove("ethernet");
+                }
+                let (key, link_value) = link.json();
+                value[key] = link_value;
+                value["length"] = json!(packets.len());
+            }
+            if let Some(direction) = direction {
+                value["direction"] = json!(direction.name());
+            }
+            if let Some(checksums) = &checksums {
+                value["checksums"] = format_checksums_json(checksums);
+            }
+            let failed = value
+                .get("error")
+                .map(|_| Self::failed_frame(header, packets, frame));
+            return DecodedPacket::Json(value, failed);
+        }
+
+        let log_msg = match link {
+            // The rebuilt frame always has a full Ethernet header
+            Some(_) => Self::decode_inner(
+                encapsulation::ether_type(frame).unwrap_or_default(),
+                &frame[ETHERNET_HEADER_LEN..],
+                0,
+                options,
+            )
+            .map(Some),
+            None => Self::decode(frame, options),
+        };
+
+        match log_msg {
+            Ok(Some(log_msg)) => {
+                let marker = match direction {
+                    Some(direction) => format!("{} ", direction.marker()),
+                    None => String::new(),
+                };
+                let mut log_msg = format!(
+                    "{}{} | {}{} | {} bytes",
+                    marker,
+                    format_timestamp(header),
+                    link_text,
+                    log_msg,
+                    packets.len()
+                );
+
+                if let Some(checksums) = checksums.filter(|checksums| !checksums.is_empty()) {
+                    log_msg.push_str(&format!(" {}", format_checksums(&checksums)));
+                }
+
+                log_msg.push_str(&Self::payload_if(frame, options));
+
+                // The hexdump of the whole packet already includes the payload
+                if options.hex {
+                    log_msg.push_str(&hexdump(packets));
+                } else if options.decode_level == DecodeLevel::Verbose {
+                    log_msg.push_str(&format_payload(frame));
+                }
+
+                DecodedPacket::Text(log_msg)
+            }
+            Ok(None) => DecodedPacket::Empty,
+            Err(_) if truncated => DecodedPacket::Truncated(
+                format!(
+                    "{} | {}{} | {} of {} bytes{}",
+                    format_timestamp(header),
+                    link_text,
+                    TRUNCATED_MARKER,
+                    packets.len(),
+                    header.len,
+                    Self::hexdump_if(packets, options)
+                ),
+                Self::failed_frame(header, packets, frame),
+            ),
+            Err(e) => Self::failed(e, header, packets, frame, options),
+        }
+    }
+
+    /// A packet that failed to decode.
+    ///
+    /// # Arguments
+    /// * `e` - The error the frame failed to decode with.
+    /// * `header` - The capture header of the packet.
+    /// * `packets` - The raw bytes of the packet, as captured.
+    /// * `frame` - The Ethernet frame that failed to decode.
+    /// * `options` - Options controlling whether a hexdump is shown.
+    fn failed(
+        e: ParserError,
+        header: &PacketHeader,
+        packets: &[u8],
+        frame: &[u8],
+        options: &ParseOptions,
+    ) -> DecodedPacket {
+        DecodedPacket::Error {
+            error: e.to_string(),
+            dump: Self::hexdump_if(packets, options),
+            failed: Self::failed_frame(header, packets, frame),
+        }
+    }
+
+    /// Keeps the Ethernet frame of a packet that failed to decode with its capture
+    /// header, whose lengths are adjusted to the frame for the loopback and Wi-Fi
+    /// frames it was rebuilt from.
+    fn failed_frame(header: &PacketHeader, packets: &[u8], frame: &[u8]) -> FailedFrame {
+        let mut frame_header = *header;
+        frame_header.caplen = frame.len() as u32;
+        frame_header.len = (header.len as usize + frame.len()).saturating_sub(packets.len()) as u32;
+
+        FailedFrame {
+            header: frame_header,
+            frame: frame.to_vec(),
+        }
+    }
+
+    /// Creates the capture file that packets failing to decode are saved to, if
+    /// `options` names one. They are saved as Ethernet frames, whatever the
+    /// datalink type of the capture.
+    fn create_errors_file(options: &ParseOptions) -> io::Result<Option<PcapWriter<fs::File>>> {
+        options
+            .errors_file
+            .as_ref()
+            .map(|path| {
+                fs::File::create(path)
+                    .and_then(|file| PcapWriter::new(file, Linktype::ETHERNET, DEFAULT_SNAPLEN))
+            })
+            .transpose()
+    }
+
+    /// Returns `true` if the post-parse filter of `options` rejects an Ethernet
+    /// frame. Frames that net_sift cannot parse down to the IP layer never match a
+    /// filter that is set.
+    fn filtered_out(packets: &[u8], options: &ParseOptions) -> bool {
+        if options.filter.is_empty() {
+            return false;
+        }
+
+        let (_, untagged) = vlan::strip_tags(packets);
+        match EthernetFrame::from_bytes(&untagged, false).and_then(|frame| frame.parse_next_layer())
+        {
+            Ok(LayeredData::EthernetFrameData(frame)) => !options.filter.matches(&frame.data),
+            _ => true,
+        }
+    }
+
+    /// Returns the payload of a packet as text if `options` asks for it, or an empty
+    /// string.
+    fn payload_if(packets: &[u8], options: &ParseOptions) -> String {
+        match options.payload {
+            Some(max_len) => format_payload_text(packets, max_len),
+            None => String::new(),
+        }
+    }
+
+    /// Returns the hexdump of a packet if `options` asks for one, or an empty string.
+    fn hexdump_if(packets: &[u8], options: &ParseOptions) -> String {
+        // Packets that fail to decode are only shown to be studied
+        if options.hex || options.only_errors {
+            hexdump(packets)
+        } else {
+            String::new()
+        }
+    }
+
+    /// Prints the header row of the CSV output, ahead of the first packet.
+    fn print_csv_header(options: &ParseOptions) {
+        if options.format == OutputFormat::Csv {
+            println!("{}", CSV_HEADER);
+        }
+    }
+
+    /// Writes a decoded packet to the log, or to stdout for JSON and CSV output.
+    /// Log lines start with the index of the packet, e.g. `#42`, so that it can be
+    /// found in the saved capture.
+    fn emit(
+        decoded: DecodedPacket,
+        (index, delta): (u64, i64),
+        mode: &str,
+        state: &mut CaptureState,
+    ) {
+        match decoded {
+            DecodedPacket::Json(value, failed) => {
+                println!("{}", value);
+
+                if let Some(failed) = failed {
+                    Self::save_failed(&failed, state);
+                }
+            }
+            DecodedPacket::Row(row) => println!("{}", row),
+            DecodedPacket::Text(log_msg) => {
+                info!("{}: #{} {} {}\n", mode, index, format_delta(delta), log_msg)
+            }
+            DecodedPacket::Truncated(log_msg, failed) => {
+                info!("{}: #{} {} {}\n", mode, index, format_delta(delta), log_msg);
+                Self::save_failed(&failed, state);
+
+                if !state.truncation_reported {
+                    state.truncation_reported = true;
+                    warn!("Packets are truncated by the capture snaplen; increase it to decode them fully");
+                }
+            }
+            DecodedPacket::Error {
+                error,
+                dump,
+                failed,
+            } => {
+                error!("Error parsing packet #{} {:?}{}", index, error, dump);
+                Self::save_failed(&failed, state);
+            }
+            DecodedPacket::Empty => {}
+        }
+    }
+
+    /// Saves the frame of a packet that failed to decode to the errors file, if
+    /// there is one.
+    fn save_failed(failed: &FailedFrame, state: &mut CaptureState) {
+        if let Some(errors_file) = state.errors_file.as_mut() {
+            if let Err(err) = errors_file.write_packet(&failed.header, &failed.frame) {
+                error!("Failed to save packet: {:?}", err.to_string());
+            }
+        }
+    }
+
+    /// Emits a packet decoded on the parse pool with the index and delta it was
+    /// submitted with.
+    fn emit_pooled(decoded: DecodedPacket, mode: &str, state: &mut CaptureState) {
+        let packet = state.pool_packets.pop_front().unwrap_or_default();
+        Self::emit(decoded, packet, mode, state);
+    }
+
+    /// Counts a packet of the capture, advancing its index and measuring the time
+    /// since the previous packet from their capture timestamps.
+    fn count_packet(header: &PacketHeader, state: &mut CaptureState) {
+        let timestamp = i64::from(header.ts.tv_sec)
+            .saturating_mul(1_000_000)
+            .saturating_add(i64::from(header.ts.tv_usec));
+
+        state.packet_index += 1;
+        state.packet_delta = state
+            .last_timestamp
+            .map_or(0, |last| timestamp.saturating_sub(last));
+        state.last_timestamp = Some(timestamp);
+    }
+
+    /// Logs the end-of-capture summary: the destination breakdown of frames, the
+    /// flows that carried the most traffic, with per-direction counters, and the
+    /// top talkers requested in `options`.
+    fn log_summary(state: &CaptureState, options: &ParseOptions) {
+        if state.stats.packets > 0 {
+            info!("{}", format_protocol_summary(&state.stats));
+            info!("{}", format_cast_summary(&state.stats));
+        }
+
+        if let Some(stats) = &state.capture_stats {
+            info!("{}", format_capture_stats(stats));
+        }
+
+        if state.inactive_packets > 0 {
+            info!(
+                "Skipped {} packets outside active hours",
+                state.inactive_packets
+            );
+        }
+
+        if !state.flows.is_empty() {
+            info!("Top flows:");
+            for (key, stats) in state.flows.top(FLOW_SUMMARY_LEN) {
+                info!("{}", format_flow(key, stats));
+            }
+        }
+
+        if options.top_talkers > 0 && !state.conversations.is_empty() {
+            info!("Top talkers:");
+            for (hosts, counter) in state.conversations.top(options.top_talkers) {
+                info!("{}", format_conversation(hosts, counter));
+            }
+        }
+    }
+
+    /// Tells the reader threads to stop once the capture stopped, and waits for
+    /// their channel to disconnect, so that the summary includes the packets
+    /// dropped at the end. A reader still blocked reading a packet sends its last
+    /// packet counts only after it returns.
+    ///
+    /// # Arguments
+    /// * `recv_packets` - The channel the reader threads send their results to.
+    /// * `stop` - The flag the reader threads check after sending each result.
+    /// * `keep` - Called with each result sent until the readers stopped.
+    fn stop_readers<T>(recv_packets: &Receiver<T>, stop: &AtomicBool, mut keep: impl FnMut(T)) {
+        stop.store(true, Ordering::SeqCst);
+
+        let deadline = Instant::now() + READER_STOP_TIMEOUT;
+        while let Ok(message) =
+            recv_packets.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        {
+            keep(message);
+        }
+    }
+
+    /// Adds up the packet counts of several capture handles, or returns `None` if
+    /// none of them has reported any.
+    fn combined_stats(stats: &[Option<Stat>]) -> Option<Stat> {
+        stats.iter().flatten().copied().reduce(|total, stats| Stat {
+            received: total.received.saturating_add(stats.received),
+            dropped: total.dropped.saturating_add(stats.dropped),
+            if_dropped: total.if_dropped.saturating_add(stats.if_dropped),
+        })
+    }
+
+    /// Checks whether a packet was captured within the active hours, if any are
+    /// configured. Packets outside them are only counted, and the transitions
+    /// between active and idle are logged.
+    fn within_active_hours(
+        header: &PacketHeader,
+        active_hours: Option<&ActiveHours>,
+        state: &mut CaptureState,
+    ) -> bool {
+        let active = match active_hours {
+            Some(hours) => local_time::minute_of_day(header.ts.tv_sec)
+                .map_or(true, |minute| hours.contains(minute)),
+            None => return true,
+        };
+
+        if active == state.idle {
+            state.idle = !active;
+            if active {
+                info!("Entered active hours, resuming capture");
+            } else {
+                info!("Outside active hours, idling");
+            }
+        }
+
+        if !active {
+            state.inactive_packets += 1;
+        }
+
+        active
+    }
+
+    /// Returns `true` and logs a warning if the process has exceeded its memory ceiling.
+    fn memory_exceeded(memory_guard: &mut Option<MemoryGuard>) -> bool {
+        match memory_guard.as_mut().and_then(MemoryGuard::exceeded) {
+            Some(rss) => {
+                warn!(
+                    "Memory usage of {} bytes exceeds the configured limit, stopping capture",
+                    rss
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Checks ARP replies for an IP address already claimed by another MAC address
+    /// and logs a warning when a conflict is found.
+    fn detect_arp_conflict(packets: &[u8], state: &mut CaptureState) {
+        let conflict = ArpPacket::from_frame(packets)
+            .and_then(|packet| state.arp_bindings.observe(&packet, Instant::now()));
+
+        if let Some(conflict) = conflict {
+            warn!(
+                "IP conflict: {} claimed by both {} and {}",
+                conflict.ip,
+                encapsulation::format_mac(&conflict.existing_mac),
+                encapsulation::format_mac(&conflict.claiming_mac)
+            );
+        }
+    }
+
+    /// Checks the sender of a frame against the talker alert threshold, if one is
+    /// configured, and logs a warning when a host exceeds it.
+    fn detect_top_talker(packets: &[u8], options: &ParseOptions, state: &mut CaptureState) {
+        let alert = match &options.talker_alert {
+            Some(alert) => alert,
+            None => return,
+        };
+
+        if let Some(exceeded) = state.talkers.observe(packets, alert, Instant::now()) {
+            warn!(
+                "Top talker alert: {} sent {} in {} packets within {:?}",
+                exceeded.host,
+                format_bytes(exceeded.counter.bytes),
+                exceeded.counter.packets,
+                alert.window
+            );
+        }
+    }
+
+    /// Checks the TCP SYNs of a frame against the scan thresholds, if scan detection
+    /// is enabled, and logs a warning when a source looks like a port scanner or
+    /// SYN flooder.
+    fn detect_scan(packets: &[u8], options: &ParseOptions, state: &mut CaptureState) {
+        let thresholds = match &options.scan_detection {
+            Some(thresholds) => thresholds,
+            None => return,
+        };
+
+        match state.syns.observe(packets, thresholds, Instant::now()) {
+            Some(ScanAlert::PortScan { source, ports }) => warn!(
+                "Possible port scan: {} sent unanswered SYNs to {} ports within {:?}",
+                source, ports, thresholds.window
+            ),
+            Some(ScanAlert::SynFlood {
+                source,
+                destination,
+                port,
+                syns,
+            }) => warn!(
+                "Possible SYN flood: {} sent {} unanswered SYNs to {}:{} within {:?}",
+                source, syns, destination, port, thresholds.window
+            ),
+            None => {}
+        }
+    }
+
+    /// Decodes a raw Ethernet frame into a formatted log message.
+    ///
+    /// VLAN tags are shown with the Ethernet header of the frame they are removed
+    /// from. PPPoE sessions and IP tunnels are decapsulated and the inner
+    /// packet is decoded recursively, so that both the outer and inner layers are shown.
+    ///
+    /// # Arguments
+    /// * `packets` - The raw bytes of the captured Ethernet frame.
+    /// * `options` - Options controlling how the frame is decoded.
+    ///
+    /// # Returns
+    /// * `Ok(Some(String))` - The formatted layers of the frame.
+    /// * `Ok(None)` - The frame was parsed but carries nothing to display.
+    /// * `Err(ParserError)` - The frame could not be parsed.
+    pub fn decode(packets: &[u8], options: &ParseOptions) -> Result<Option<String>, ParserError> {
+        let (vlan_tags, untagged) = vlan::strip_tags(packets);
+        let packets = &*untagged;
+
+        // net_sift does not know about PPPoE, so the session header is decoded here
+        if let Some(session) = PppoeSession::from_frame(packets) {
+            let mut log_msg = format!(
+                "{} | {}",
+                format_raw_ether_frame(packets, &vlan_tags, options),
+                format_pppoe(&session, options)
+            );
+
+            if let Some(ether_type) = session.inner_ether_type() {
+                log_msg.push_str(&format!(
+                    " >> {}",
+                    Self::decode_inner(ether_type, session.payload, 0, options)?
+                ));
+            }
+
+            return Ok(Some(log_msg));
+        }
+
+        let frame = EthernetFrame::from_bytes(packets, false)?;
+        let tunnel = encapsulation::decapsulate_ip(packets);
+
+        // The parsing of network packets begins with the Ethernet frame, which is the
+        // foundational layer. Other enum variants representing different layers or
+        // types of data are not considered at this stage.
+        let mut log_msg = match (frame.parse_next_layer(), &tunnel) {
+            (Ok(LayeredData::EthernetFrameData(frame)), _) => {
+                format_packets(&frame, packets, &vlan_tags, options)
+            }
+            // net_sift may reject the tunnel protocol of the outer packet, in which case
+            // only the tunnel header is shown ahead of the inner packet.
+            (_, Some(tunnel)) => format_tunnel(tunnel, options),
+            // Otherwise fall back to the raw IP header, e.g. for IPsec packets.
+            (_, None) => match format_raw_packet(packets, &vlan_tags, options) {
+                Some(log_msg) => log_msg,
+                None => return Ok(None),
+            },
+        };
+
+        if let Some(tunnel) = tunnel {
+            log_msg.push_str(&format!(" >> {}", Self::decode_tunnel(&tunnel, 0, options)));
+        }
+
+        if let Some(geneve) = GenevePacket::from_frame(packets) {
+            log_msg.push_str(&format!(" >> {}", Self::decode_geneve(&geneve, options)?));
+        }
+
+        Ok(Some(log_msg))
+    }
+
+    /// Decodes the Geneve header and the frame it encapsulates. Inner Ethernet
+    /// frames go through the full decoding pipeline, so nested overlays are followed.
+    fn decode_geneve(geneve: &GenevePacket, options: &ParseOptions) -> Result<String, ParserError> {
+        let mut log_msg = format_geneve(geneve, options);
+
+        let inner = match geneve.inner_ip_ether_type() {
+            Some(ether_type) => Some(Self::decode_inner(ether_type, geneve.payload, 0, options)?),
+            None if geneve.protocol_type == ETHER_TYPE_TRANSPARENT_BRIDGING => {
+                Self::decode(geneve.payload, options)?
+            }
+            None => None,
+        };
+
+        if let Some(inner) = inner {
+            log_msg.push_str(&format!(" >> {}", inner));
+        }
+
+        Ok(log_msg)
+    }
+
+    /// Decodes a raw Ethernet frame into a JSON object, one key per layer.
+    ///
+    /// Frames that cannot be parsed are emitted as an object holding the parse
+    /// error, or the truncation marker if the frame was cut short by the snaplen,
+    /// so that every packet of a capture produces exactly one line.
+    fn decode_json(packets: &[u8], truncated: bool, options: &ParseOptions) -> Option<Value> {
+        let (vlan_tags, untagged) = vlan::strip_tags(packets);
+        let layered_data =
+            EthernetFrame::from_bytes(&untagged, false).and_then(|frame| frame.parse_next_layer());
+
+        let mut value = match layered_data {
+            Ok(LayeredData::EthernetFrameData(frame)) => {
+                format_packets_json(&frame, &untagged, &vlan_tags, options)
+            }
+            Ok(_) => return None,
+            Err(_) if truncated => json!({ "error": TRUNCATED_MARKER, "truncated": true }),
+            Err(e) => json!({ "error": e.to_string() }),
+        };
+
+        value["length"] = json!(packets.len());
+        Some(value)
+    }
+
+    /// Decodes the inner packet of an IP tunnel, following nested tunnels.
+    ///
+    /// An inner packet that fails to decode is replaced by the error, so that the
+    /// headers of the outer packet are still shown.
+    ///
+    /// # Arguments
+    /// * `tunnel` - The tunnel whose inner packet is decoded.
+    /// * `depth` - The number of tunnels already followed to reach `tunnel`.
+    /// * `options` - Options controlling how the packet is decoded.
+    fn decode_tunnel(tunnel: &IpTunnel, depth: usize, options: &ParseOptions) -> String {
+        if depth >= MAX_TUNNEL_DEPTH {
+            return TUNNEL_DEPTH_MARKER.to_string();
+        }
+
+        Self::decode_inner(tunnel.inner_ether_type, tunnel.payload, depth + 1, options)
+            .unwrap_or_else(|e| format!("<malformed: {}>", e))
+    }
+
+    /// Decodes a decapsulated IP packet, following any tunnels nested inside it.
+    ///
+    /// # Arguments
+    /// * `ether_type` - The EtherType identifying the protocol of `payload`.
+    /// * `payload` - The bytes of the inner packet.
+    /// * `depth` - The number of tunnels already followed to reach `payload`.
+    /// * `options` - Options controlling how the packet is decoded.
+    fn decode_inner(
+        ether_type: u16,
+        payload: &[u8],
+        depth: usize,
+        options: &ParseOptions,
+    ) -> Result<String, ParserError> {
+        let inner = encapsulation::wrap_in_ethernet(ether_type, payload);
+        let frame = EthernetFrame::from_bytes(&inner, false)?;
+        let nested = encapsulation::decapsulate_ip(&inner);
+
+        let mut log_msg = match (frame.parse_next_layer(), &nested) {
+            (Ok(LayeredData::EthernetFrameData(frame)), _) => {
+                format_layers(&frame.data, &inner, options)
+            }
+            // As for the outer packet, net_sift may reject the tunnel protocol, in
+            // which case only the tunnel header is shown.
+            (Ok(_), _) | (Err(_), Some(_)) => String::new(),
+            (Err(e), None) => return Err(e),
+        };
+
+        if let Some(nested) = nested {
+            if !log_msg.is_empty() {
+                log_msg.push_str(" | ");
+            }
+            log_msg.push_str(&format!(
+                "{} >> {}",
+                format_tunnel(&nested, options),
+                Self::decode_tunnel(&nested, depth, options)
+            ));
+        }
+
+        Ok(log_msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The capture time of the canned packets, 2024-01-05T10:15:02.512034Z.
+    fn packet_header(packet: &[u8]) -> PacketHeader {
+        PacketHeader {
+            ts: libc::timeval {
+                tv_sec: 1_704_449_702,
+                tv_usec: 512_034,
+            },
+            caplen: packet.len() as u32,
+            len: packet.len() as u32,
+        }
+    }
+
+    /// An Ethernet header from 66:77:88:99:aa:bb to 00:11:22:33:44:55.
+    fn ethernet_header(ether_type: u16) -> Vec<u8> {
+        let mut frame = vec![
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, // destination MAC
+            0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, // source MAC
+        ];
+        frame.extend_from_slice(&ether_type.to_be_bytes());
+        frame
+    }
+
+    /// A TCP SYN from 192.168.1.10:50000 to 93.184.216.34:80 with DF set.
+    fn ipv4_tcp_frame() -> Vec<u8> {
+        let mut frame = ethernet_header(encapsulation::ETHER_TYPE_IPV4);
+        frame.extend_from_slice(&[
+            0x45, 0x00, 0x00, 0x28, 0x00, 0x01, 0x40, 0x00, 0x40, 0x06, 0x00, 0x00, // header
+            192, 168, 1, 10, // source address
+            93, 184, 216, 34, // destination address
+        ]);
+        frame.extend_from_slice(&[
+            0xc3, 0x50, 0x00, 0x50, // ports
+            0x00, 0x00, 0x00, 0x01, // sequence number
+            0x00, 0x00, 0x00, 0x00, // acknowledgment number
+            0x50, 0x02, 0xff, 0xff, // data offset, flags and window
+            0x00, 0x00, 0x00, 0x00, // checksum and urgent pointer
+        ]);
+        frame
+    }
+
+    /// An ARP request from 192.168.1.10 asking for the MAC address of 192.168.1.1.
+    fn arp_request_frame() -> Vec<u8> {
+        let mut frame = ethernet_header(0x0806);
+        frame.extend_from_slice(&[
+            0x00, 0x01, 0x08, 0x00, 0x06, 0x04, 0x00, 0x01, // Ethernet/IPv4 request
+            0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 192, 168, 1, 10, // sender
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 192, 168, 1, 1, // target
+        ]);
+        frame
+    }
+
+    /// An NTP request in a UDP datagram from [2001:db8::1]:50000 to [2001:db8::2]:123.
+    fn ipv6_udp_frame() -> Vec<u8> {
+        let mut frame = ethernet_header(encapsulation::ETHER_TYPE_IPV6);
+        frame.extend_from_slice(&[
+            0x60, 0x00, 0x00, 0x00, 0x00, 0x10, 0x11, 0x40, // header, next header UDP
+            0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, // source address
+            0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, // destination address
+        ]);
+        frame.extend_from_slice(&[
+            0xc3, 0x50, 0x00, 0x7b, 0x00, 0x10, 0x00, 0x00, // ports, length and checksum
+            0x23, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // payload
+        ]);
+        frame
+    }
+
+    /// An ESP packet from 192.0.2.1 to 198.51.100.1 with SPI 0x1000 and sequence
+    /// number 7, which net_sift does not parse.
+    fn ipv4_esp_frame() -> Vec<u8> {
+        let mut frame = ethernet_header(encapsulation::ETHER_TYPE_IPV4);
+        frame.extend_from_slice(&[
+            0x45, 0x00, 0x00, 0x20, 0x00, 0x01, 0x00, 0x00, 0x40, 0x32, 0x00, 0x00, // header
+            192, 0, 2, 1, // source address
+            198, 51, 100, 1, // destination address
+        ]);
+        frame.extend_from_slice(&[
+            0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x07, // SPI and sequence number
+            0xde, 0xad, 0xbe, 0xef, // encrypted payload
+        ]);
+        frame
+    }
+
+    /// Wraps an IP packet in an IPv4 header from 10.0.0.1 to 10.0.0.2.
+    fn ipv4_tunnel(protocol: u8, inner: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0x45, 0x00];
+        packet.extend_from_slice(&(20 + inner.len() as u16).to_be_bytes());
+        packet.extend_from_slice(&[
+            0x00, 0x01, 0x00, 0x00, 0x40, protocol, 0x00, 0x00, // header
+            10, 0, 0, 1, // source address
+            10, 0, 0, 2, // destination address
+        ]);
+        packet.extend_from_slice(inner);
+        packet
+    }
+
+    /// Wraps an IP packet in an IPv6 header from 2001:db8::a to 2001:db8::b.
+    fn ipv6_tunnel(next_header: u8, inner: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0x60, 0x00, 0x00, 0x00];
+        packet.extend_from_slice(&(inner.len() as u16).to_be_bytes());
+        packet.extend_from_slice(&[next_header, 0x40]);
+        packet.extend_from_slice(&[
+            0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0a, // source
+            0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0b, // destination
+        ]);
+        packet.extend_from_slice(inner);
+        packet
+    }
+
+    /// The TCP SYN of `ipv4_tcp_frame` carried in `depth` nested IP-in-IP tunnels.
+    fn nested_ip_in_ip_frame(depth: usize) -> Vec<u8> {
+        let mut packet = ipv4_tcp_frame()[ETHERNET_HEADER_LEN..].to_vec();
+        for _ in 0..depth {
+            packet = ipv4_tunnel(encapsulation::IP_PROTOCOL_IPV4, &packet);
+        }
+
+        let mut frame = ethernet_header(encapsulation::ETHER_TYPE_IPV4);
+        frame.extend_from_slice(&packet);
+        frame
+    }
+
+    /// Decodes a canned frame the way `parse_packets` does for a live capture.
+    fn decode_text(packet: &[u8], options: &ParseOptions) -> String {
+        match Analyzer::decode_packet(&packet_header(packet), packet, options) {
+            DecodedPacket::Text(log_msg) => log_msg,
+            DecodedPacket::Error { error, .. } => {
+                panic!("failed to decode canned packet: {}", error)
+            }
+            _ => panic!("canned packet is not decoded as text"),
+        }
+    }
+
+    #[test]
+    fn decodes_ipv4_tcp_packet() {
+        let packet = ipv4_tcp_frame();
+        let log_msg = decode_text(&packet, &ParseOptions::default());
+
+        // Addresses without a known vendor are shown the way net_sift formats them
+        let frame = EthernetFrame::from_bytes(&packet, false).unwrap();
+        assert!(log_msg.starts_with(&format!(
+            "2024-01-05T10:15:02.512034Z | Ethernet: Src {:?}, Dest {:?}, Prot ",
+            frame.header.mac_source.to_string(),
+            frame.header.mac_destination.to_string()
+        )));
+        assert!(log_msg.contains("IPv4: Ver 4, Src 192.168.1.10, Dest 93.184.216.34, "));
+        assert!(log_msg.contains("[DF]"));
+        assert!(log_msg.contains(
+            "TCP: Src Port: 50000 Dest Port: 80(http) Seq: 1 Flags: [SYN] (0x02) Win: 65535"
+        ));
+        assert!(log_msg.ends_with(" | 54 bytes"));
+    }
+
+    #[test]
+    fn shows_vendor_of_known_ethernet_address() {
+        // 00:00:0c is a Cisco OUI
+        let mut packet = ipv4_tcp_frame();
+        packet[6..9].copy_from_slice(&[0x00, 0x00, 0x0c]);
+        let log_msg = decode_text(&packet, &ParseOptions::default());
+
+        let frame = EthernetFrame::from_bytes(&packet, false).unwrap();
+        assert!(log_msg.contains(&format!(
+            "Ethernet: Src {:?} (Cisco), Dest {:?}, Prot ",
+            frame.header.mac_source.to_string(),
+            frame.header.mac_destination.to_string()
+        )));
+
+        // The raw Ethernet header is formatted the same way
+        let mut packet = ipv4_esp_frame();
+        packet[6..9].copy_from_slice(&[0x00, 0x00, 0x0c]);
+        assert!(decode_text(&packet, &ParseOptions::default()).contains(
+            "Ethernet: Src \"00:00:0c:99:aa:bb\" (Cisco), Dest \"00:11:22:33:44:55\", Prot 0x0800"
+        ));
+    }
+
+    #[test]
+    fn decodes_esp_packet_to_full_line() {
+        let packet = ipv4_esp_frame();
+
+        assert_eq!(
+            decode_text(&packet, &ParseOptions::default()),
+            "2024-01-05T10:15:02.512034Z | \
+             Ethernet: Src \"66:77:88:99:aa:bb\", Dest \"00:11:22:33:44:55\", Prot 0x0800 | \
+             IPv4: Src 192.0.2.1, Dest 198.51.100.1, Prot 50 | \
+             ESP: SPI 0x00001000, Seq 7 | 46 bytes"
+        );
+    }
+
+    #[test]
+    fn labels_tunnels_by_outer_and_inner_version() {
+        let ipv4_tcp = &ipv4_tcp_frame()[ETHERNET_HEADER_LEN..];
+        let ipv6_udp = &ipv6_udp_frame()[ETHERNET_HEADER_LEN..];
+        let options = ParseOptions::default();
+
+        // The outer 6in4 tunnel makes the IPv6 tunnel inside it a nested one,
+        // whose header is always shown
+        let mut frame = ethernet_header(encapsulation::ETHER_TYPE_IPV4);
+        frame.extend_from_slice(&ipv4_tunnel(
+            encapsulation::IP_PROTOCOL_IPV6,
+            &ipv6_tunnel(encapsulation::IP_PROTOCOL_IPV4, ipv4_tcp),
+        ));
+        let log_msg = decode_text(&frame, &options);
+
+        assert!(log_msg.contains("Tunnel (4in6): Src 2001:db8::a, Dest 2001:db8::b >> "));
+        assert!(log_msg.contains("TCP: Src Port: 50000 Dest Port: 80(http)"));
+
+        let mut frame = ethernet_header(encapsulation::ETHER_TYPE_IPV4);
+        frame.extend_from_slice(&ipv4_tunnel(
+            encapsulation::IP_PROTOCOL_IPV6,
+            &ipv6_tunnel(encapsulation::IP_PROTOCOL_IPV6, ipv6_udp),
+        ));
+        let log_msg = decode_text(&frame, &options);
+
+        assert!(log_msg.contains("Tunnel (6in6): Src 2001:db8::a, Dest 2001:db8::b >> "));
+        assert!(log_msg.contains("UDP: Src Port 50000, Dest Port 123(ntp)"));
+        assert!(!log_msg.contains("Tunnel (IP-in-IP)"));
+    }
+
+    #[test]
+    fn stops_following_tunnels_at_max_depth() {
+        let options = ParseOptions::default();
+
+        let log_msg = decode_text(&nested_ip_in_ip_frame(MAX_TUNNEL_DEPTH), &options);
+        assert!(log_msg.contains("TCP: Src Port: 50000 Dest Port: 80(http)"));
+        assert!(!log_msg.contains(TUNNEL_DEPTH_MARKER));
+
+        let log_msg = decode_text(&nested_ip_in_ip_frame(MAX_TUNNEL_DEPTH + 1), &options);
+        assert!(log_msg.contains(TUNNEL_DEPTH_MARKER));
+        assert!(!log_msg.contains("TCP:"));
+    }
+
+    #[test]
+    fn keeps_outer_header_of_malformed_tunnel() {
+        // The inner IPv4 header claims 20 bytes but only 4 are present
+        let mut frame = ethernet_header(encapsulation::ETHER_TYPE_IPV4);
+        frame.extend_from_slice(&ipv4_tunnel(
+            encapsulation::IP_PROTOCOL_IPV4,
+            &[0x45, 0x00, 0x00, 0x14],
+        ));
+        let log_msg = decode_text(&frame, &ParseOptions::default());
+
+        assert!(log_msg.contains("Src 10.0.0.1"));
+        assert!(log_msg.contains(" >> <malformed: "));
+        assert!(log_msg.ends_with(" | 38 bytes"));
+    }
+
+    #[test]
+    fn decodes_arp_packet() {
+        let packet = arp_request_frame();
+        let log_msg = decode_text(&packet, &ParseOptions::default());
+
+        assert!(log_msg.contains(
+            "ARP: Request who-has 192.168.1.1 tell 192.168.1.10 (66:77:88:99:aa:bb), HType 1, PType 0x0800"
+        ));
+        assert!(log_msg.ends_with(" | 42 bytes"));
+    }
+
+    #[test]
+    fn decodes_ipv6_udp_packet() {
+        let packet = ipv6_udp_frame();
+        let log_msg = decode_text(&packet, &ParseOptions::default());
+
+        assert!(log_msg.contains("IPV6: Ver: 6 Src: 2001:db8::1 Dest: 2001:db8::2 Next: "));
+        assert!(log_msg.contains("UDP: Src Port 50000, Dest Port 123(ntp)"));
+        assert!(log_msg.ends_with(" | 70 bytes"));
+    }
+
+    #[test]
+    fn decodes_loopback_packet_like_ethernet() {
+        // DLT_NULL stores the family in host byte order, which the parser detects
+        let mut packet = 2u32.to_ne_bytes().to_vec();
+        packet.extend_from_slice(&ipv4_tcp_frame()[ETHERNET_HEADER_LEN..]);
+        let options = ParseOptions {
+            verify_checksums: true,
+            local_addresses: vec![IpAddr::from([192, 168, 1, 10])],
+            ..ParseOptions::default()
+        };
+
+        let frame = LoopbackFrame::from_bytes(&packet, Linktype::NULL).unwrap();
+        let inner = frame.to_ethernet().unwrap();
+        let decoded = Analyzer::decode_frame(
+            &packet_header(&packet),
+            &packet,
+            &inner,
+            Some(&RebuiltLink::Loopback(&frame)),
+            &options,
+        );
+        let log_msg = match decoded {
+            DecodedPacket::Text(log_msg) => log_msg,
+            _ => panic!("loopback packet is not decoded as text"),
+        };
+
+        assert!(log_msg
+            .starts_with("> 2024-01-05T10:15:02.512034Z | Loopback: Family IPv4 (2) | IPv4: "));
+        assert!(!log_msg.contains("Ethernet"));
+        assert!(log_msg.contains(" | 44 bytes [IPv4 checksum BAD: 0x0000 != "));
+    }
+
+    #[test]
+    fn decodes_wifi_data_frame_like_ethernet() {
+        let mut packet = vec![
+            0x08, 0x02, 0x00, 0x00, // data frame from the distribution system
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, // receiver, the destination
+            0x02, 0x00, 0x00, 0x00, 0x00, 0x01, // transmitter, the access point
+            0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, // source
+            0x10, 0x00, // sequence control
+            0xaa, 0xaa, 0x03, 0x00, 0x00, 0x00, 0x08, 0x00, // LLC/SNAP header, IPv4
+        ];
+        packet.extend_from_slice(&ipv4_tcp_frame()[ETHERNET_HEADER_LEN..]);
+        let options = ParseOptions {
+            local_addresses: vec![IpAddr::from([93, 184, 216, 34])],
+            ..ParseOptions::default()
+        };
+
+        let frame = WifiFrame::from_bytes(&packet, Linktype::IEEE802_11).unwrap();
+        let inner = frame.to_ethernet().unwrap();
+        let decoded = Analyzer::decode_frame(
+            &packet_header(&packet),
+            &packet,
+            &inner,
+            Some(&RebuiltLink::Wifi(&frame)),
+            &options,
+        );
+        let log_msg = match decoded {
+            DecodedPacket::Text(log_msg) => log_msg,
+            _ => panic!("Wi-Fi data frame is not decoded as text"),
+        };
+
+        assert!(log_msg.starts_with("< 2024-01-05T10:15:02.512034Z | 802.11: Data, "));
+        assert!(log_msg.contains(" | IPv4: Ver 4, Src 192.168.1.10, Dest 93.184.216.34, "));
+        assert!(!log_msg.contains("Ethernet"));
+        assert!(log_msg.ends_with(" | 72 bytes"));
+    }
+
+    #[test]
+    fn saves_malformed_frame_to_errors_file() {
+        let path = std::env::temp_dir().join(format!("wyre-errors-{}.pcap", std::process::id()));
+        let options = ParseOptions {
+            only_errors: true,
+            errors_file: Some(path.clone()),
+            ..ParseOptions::default()
+        };
+        let mut state = CaptureState {
+            errors_file: Analyzer::create_errors_file(&options).unwrap(),
+            ..CaptureState::default()
+        };
+
+        // Cut short within the Ethernet header, but complete on the wire
+        let packet = ipv4_tcp_frame()[..10].to_vec();
+        let decoded = Analyzer::decode_packet(&packet_header(&packet), &packet, &options)
+            .failures_only(&options);
+        assert!(matches!(decoded, DecodedPacket::Error { .. }));
+        Analyzer::emit(decoded, (1, 0), "Test", &mut state);
+        drop(state);
+
+        let saved = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // The global header, then a single record of the frame
+        assert_eq!(saved.len(), 24 + 16 + packet.len());
+        assert_eq!(saved[20..24], 1u32.to_le_bytes());
+        assert_eq!(saved[24..28], 1_704_449_702u32.to_le_bytes());
+        assert_eq!(saved[32..36], 10u32.to_le_bytes());
+        assert_eq!(saved[36..40], 10u32.to_le_bytes());
+        assert_eq!(saved[40..], packet);
+    }
+
+    #[test]
+    fn keeps_frame_of_json_failure() {
+        let packet = ipv4_tcp_frame()[..10].to_vec();
+        let options = ParseOptions {
+            format: OutputFormat::Json,
+            only_errors: true,
+            ..ParseOptions::default()
+        };
+
+        match Analyzer::decode_packet(&packet_header(&packet), &packet, &options)
+            .failures_only(&options)
+        {
+            DecodedPacket::Json(value, Some(failed)) => {
+                assert!(value.get("error").is_some());
+                assert_eq!(failed.frame, packet);
+            }
+            _ => panic!("malformed frame is not kept with its JSON error"),
+        }
+    }
+
+    #[test]
+    fn compacts_headers_but_not_payload() {
+        let payload = b"Src Port: 1, Dest 2";
+        let mut packet = ipv4_tcp_frame();
+        let total_len = (packet.len() - ETHERNET_HEADER_LEN + payload.len()) as u16;
+        packet[16..18].copy_from_slice(&total_len.to_be_bytes());
+        packet.extend_from_slice(payload);
+        let options = ParseOptions {
+            compact: true,
+            payload: Some(256),
+            ..ParseOptions::default()
+        };
+
+        let log_msg = decode_text(&packet, &options);
+
+        assert!(log_msg.contains(" | Eth S:\"66:77:88:99:aa:bb\" D:\"00:11:22:33:44:55\" "));
+        assert!(log_msg.contains(" | TCP: S:50000 D:80(http) Seq: 1 F:[SYN] (0x02) Win: 65535"));
+        assert!(log_msg.ends_with(" | 73 bytes | Payload (19 bytes): Src Port: 1, Dest 2"));
     }
 }
@@ -0,0 +1,165 @@
+use super::encapsulation::{self, read_u16, ETHERNET_HEADER_LEN};
+use std::{
+    collections::HashMap,
+    net::Ipv4Addr,
+    time::{Duration, Instant},
+};
+
+pub const ETHER_TYPE_ARP: u16 = 0x0806;
+
+pub const ARP_REQUEST: u16 = 1;
+pub const ARP_REPLY: u16 = 2;
+
+/// The length of an ARP packet for Ethernet hardware and IPv4 protocol addresses.
+const ARP_ETHERNET_IPV4_LEN: usize = 28;
+
+const DEFAULT_BINDINGS_CAPACITY: usize = 1024;
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// An ARP packet for Ethernet/IPv4 address resolution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArpPacket {
+    pub hardware_type: u16,
+    pub protocol_type: u16,
+    pub operation: u16,
+    pub sender_mac: [u8; 6],
+    pub sender_ip: Ipv4Addr,
+    pub target_mac: [u8; 6],
+    pub target_ip: Ipv4Addr,
+}
+
+impl ArpPacket {
+    /// Parses the ARP packet of a raw Ethernet frame.
+    ///
+    /// # Returns
+    /// `Some(ArpPacket)` if the EtherType is ARP and the packet is an Ethernet/IPv4
+    /// one, `None` otherwise.
+    pub fn from_frame(frame: &[u8]) -> Option<Self> {
+        if encapsulation::ether_type(frame)? != ETHER_TYPE_ARP {
+            return None;
+        }
+
+        Self::from_bytes(frame.get(ETHERNET_HEADER_LEN..)?)
+    }
+
+    /// Parses an ARP packet from the payload of an Ethernet frame.
+    ///
+    /// Only Ethernet (6 byte) hardware addresses and IPv4 (4 byte) protocol
+    /// addresses are supported; any other combination returns `None`.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() < ARP_ETHERNET_IPV4_LEN || data[4] != 6 || data[5] != 4 {
+            return None;
+        }
+
+        let mut sender_mac = [0u8; 6];
+        let mut target_mac = [0u8; 6];
+        sender_mac.copy_from_slice(&data[8..14]);
+        target_mac.copy_from_slice(&data[18..24]);
+
+        Some(Self {
+            hardware_type: read_u16(data, 0)?,
+            protocol_type: read_u16(data, 2)?,
+            operation: read_u16(data, 6)?,
+            sender_mac,
+            sender_ip: Ipv4Addr::new(data[14], data[15], data[16], data[17]),
+            target_mac,
+            target_ip: Ipv4Addr::new(data[24], data[25], data[26], data[27]),
+        })
+    }
+}
+
+/// Two hosts claiming the same IP address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArpConflict {
+    pub ip: Ipv4Addr,
+    pub existing_mac: [u8; 6],
+    pub claiming_mac: [u8; 6],
+}
+
+#[derive(Debug, Clone)]
+struct Binding {
+    mac: [u8; 6],
+    last_seen: Instant,
+}
+
+/// Tracks IP-to-MAC bindings learnt from ARP replies to detect duplicate IP addresses.
+///
+/// The table is bounded: bindings that have not been refreshed within the idle
+/// timeout are expired, and once the capacity is reached the least recently seen
+/// binding is evicted to make room for new ones.
+#[derive(Debug)]
+pub struct ArpBindings {
+    bindings: HashMap<Ipv4Addr, Binding>,
+    capacity: usize,
+    idle_timeout: Duration,
+}
+
+impl Default for ArpBindings {
+    fn default() -> Self {
+        Self::new(DEFAULT_BINDINGS_CAPACITY, DEFAULT_IDLE_TIMEOUT)
+    }
+}
+
+impl ArpBindings {
+    pub fn new(capacity: usize, idle_timeout: Duration) -> Self {
+        Self {
+            bindings: HashMap::new(),
+            capacity,
+            idle_timeout,
+        }
+    }
+
+    /// Records the sender binding of an ARP reply.
+    ///
+    /// # Returns
+    /// `Some(ArpConflict)` if the sender IP is currently bound to a different MAC
+    /// address. The binding is then updated to the most recent claimant.
+    pub fn observe(&mut self, packet: &ArpPacket, now: Instant) -> Option<ArpConflict> {
+        if packet.operation != ARP_REPLY || packet.sender_ip.is_unspecified() {
+            return None;
+        }
+
+        self.expire(now);
+
+        let conflict = match self.bindings.get(&packet.sender_ip) {
+            Some(binding) if binding.mac != packet.sender_mac => Some(ArpConflict {
+                ip: packet.sender_ip,
+                existing_mac: binding.mac,
+                claiming_mac: packet.sender_mac,
+            }),
+            _ => None,
+        };
+
+        if !self.bindings.contains_key(&packet.sender_ip) && self.bindings.len() >= self.capacity {
+            self.evict_oldest();
+        }
+
+        self.bindings.insert(
+            packet.sender_ip,
+            Binding {
+                mac: packet.sender_mac,
+                last_seen: now,
+            },
+        );
+
+        conflict
+    }
+
+    fn expire(&mut self, now: Instant) {
+        let idle_timeout = self.idle_timeout;
+        self.bindings
+            .retain(|_, binding| now.duration_since(binding.last_seen) < idle_timeout);
+    }
+
+    fn evict_oldest(&mut self) {
+        let oldest = self
+            .bindings
+            .iter()
+            .min_by_key(|(_, binding)| binding.last_seen)
+            .map(|(ip, _)| *ip);
+
+        if let Some(ip) = oldest {
+            self.bindings.remove(&ip);
+        }
+    }
+}
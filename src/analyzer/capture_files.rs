@@ -0,0 +1,123 @@
+use super::{error::AnalyzerError, ring_file::RingReader};
+use log::error;
+use pcap::{Capture, Linktype, Offline, PacketHeader};
+
+/// The reader of a single file: libpcap for .pcap files, or `RingReader` for the
+/// single-file rings written by `--ring-file`.
+enum CaptureSource {
+    Pcap(Capture<Offline>),
+    Ring(RingReader),
+}
+
+struct CaptureFile {
+    path: String,
+    source: CaptureSource,
+    /// The next unread packet of the file, read ahead to order packets across files.
+    next: Option<(PacketHeader, Vec<u8>)>,
+}
+
+impl CaptureFile {
+    /// Reads ahead the next packet of the file, or clears it at the end of the file.
+    fn advance(&mut self) {
+        self.next = match &mut self.source {
+            CaptureSource::Pcap(capture) => match capture.next_packet() {
+                Ok(packet) => Some((*packet.header, packet.data.to_vec())),
+                Err(pcap::Error::NoMorePackets) => None,
+                Err(err) => {
+                    error!("Error reading {}: {:?}", self.path, err.to_string());
+                    None
+                }
+            },
+            CaptureSource::Ring(ring) => ring.next_packet(),
+        };
+    }
+}
+
+/// A set of .pcap files read as one logical capture, such as the files of a
+/// rotated capture.
+///
+/// All files must share the datalink type of the first. Packets are returned in
+/// timestamp order across files; for a rotated set, where files do not overlap,
+/// this reads the files one after another. Packets with equal timestamps are
+/// returned in the order the files were given.
+pub struct CaptureFiles {
+    files: Vec<CaptureFile>,
+    linktype: Linktype,
+}
+
+impl CaptureFiles {
+    /// Opens every file of the set and checks that their datalink types match.
+    ///
+    /// # Arguments
+    /// * `paths` - The paths of the .pcap or ring files, in the order they were written.
+    ///
+    /// # Returns
+    /// A `Result` with the opened set, or an `AnalyzerError` if a file cannot be
+    /// opened or has a different datalink type than the first file.
+    pub fn open(paths: &[String]) -> Result<Self, AnalyzerError> {
+        let mut files = Vec::with_capacity(paths.len());
+        let mut linktype: Option<Linktype> = None;
+
+        for path in paths {
+            let source =
+                match RingReader::open(path).map_err(AnalyzerError::FailedToOpenRingFile)? {
+                    Some(ring) => CaptureSource::Ring(ring),
+                    None => CaptureSource::Pcap(
+                        Capture::from_file(path).map_err(AnalyzerError::FailedToOpenCaptureFile)?,
+                    ),
+                };
+            let datalink = match &source {
+                CaptureSource::Pcap(capture) => capture.get_datalink(),
+                CaptureSource::Ring(ring) => ring.linktype(),
+            };
+
+            match linktype {
+                Some(expected) if expected != datalink => {
+                    return Err(AnalyzerError::MismatchedDatalink(
+                        path.clone(),
+                        datalink.0,
+                        expected.0,
+                    ))
+                }
+                _ => linktype = Some(datalink),
+            }
+
+            let mut file = CaptureFile {
+                path: path.clone(),
+                source,
+                next: None,
+            };
+            file.advance();
+            files.push(file);
+        }
+
+        Ok(Self {
+            files,
+            // An empty set has no packets, so its datalink type is never used
+            linktype: linktype.unwrap_or(Linktype::ETHERNET),
+        })
+    }
+
+    /// The datalink type shared by the files.
+    pub fn linktype(&self) -> Linktype {
+        self.linktype
+    }
+
+    /// Returns the earliest unread packet across all files, or `None` once every
+    /// file has been read.
+    pub fn next_packet(&mut self) -> Option<(PacketHeader, Vec<u8>)> {
+        let file = self
+            .files
+            .iter_mut()
+            .filter(|file| file.next.is_some())
+            .min_by_key(|file| {
+                file.next
+                    .as_ref()
+                    .map(|(header, _)| (header.ts.tv_sec, header.ts.tv_usec))
+            })?;
+
+        let packet = file.next.take();
+        file.advance();
+        packet
+    }
+}
@@ -0,0 +1,233 @@
+use super::{
+    encapsulation::{self, read_u16, ETHERNET_HEADER_LEN, ETHER_TYPE_IPV4},
+    flows::{IP_PROTOCOL_TCP, IP_PROTOCOL_UDP},
+    vlan,
+};
+use std::net::IpAddr;
+
+/// The offset of the checksum field in each header.
+const IPV4_CHECKSUM_OFFSET: usize = 10;
+const TCP_CHECKSUM_OFFSET: usize = 16;
+const UDP_CHECKSUM_OFFSET: usize = 6;
+
+/// The checksum of one header, as stored in the packet and as recomputed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Checksum {
+    /// The header the checksum covers, e.g. `TCP`.
+    pub layer: &'static str,
+    pub stored: u16,
+    pub computed: u16,
+}
+
+impl Checksum {
+    pub fn is_valid(&self) -> bool {
+        self.stored == self.computed
+    }
+}
+
+/// Recomputes the IPv4 header checksum and the TCP or UDP checksum of a raw
+/// Ethernet frame. VLAN tags are skipped.
+///
+/// Packets cut short by the snapshot length cannot be verified, so callers should
+/// only pass complete frames.
+///
+/// # Returns
+/// The checksums that could be verified, outermost first. UDP datagrams over IPv4
+/// without a checksum, which is optional there, are left out, as are the TCP and
+/// UDP checksums of fragments, which cover the whole reassembled datagram.
+pub fn verify(frame: &[u8]) -> Vec<Checksum> {
+    let (_, untagged) = vlan::strip_tags(frame);
+    let frame = &*untagged;
+    let mut checksums = Vec::new();
+
+    if encapsulation::ether_type(frame) == Some(ETHER_TYPE_IPV4) {
+        if let Some(checksum) = verify_ipv4_header(&frame[ETHERNET_HEADER_LEN..]) {
+            checksums.push(checksum);
+        }
+    }
+
+    if is_fragment(frame) {
+        return checksums;
+    }
+
+    let (header, segment) = match encapsulation::parse_ip(frame) {
+        Some(ip) => ip,
+        None => return checksums,
+    };
+
+    let (layer, offset) = match header.protocol {
+        IP_PROTOCOL_TCP => ("TCP", TCP_CHECKSUM_OFFSET),
+        IP_PROTOCOL_UDP => ("UDP", UDP_CHECKSUM_OFFSET),
+        _ => return checksums,
+    };
+    let stored = match read_u16(segment, offset) {
+        Some(stored) => stored,
+        None => return checksums,
+    };
+    if layer == "UDP" && stored == 0 && header.source_address.is_ipv4() {
+        return checksums;
+    }
+
+    let sum = pseudo_header_sum(
+        header.source_address,
+        header.destination_address,
+        header.protocol,
+        segment.len(),
+    ) + sum_words(segment, offset);
+    let computed = match fold(sum) {
+        // A computed UDP checksum of zero is sent as all ones, as zero means none
+        0 if layer == "UDP" => 0xffff,
+        computed => computed,
+    };
+
+    checksums.push(Checksum {
+        layer,
+        stored,
+        computed,
+    });
+    checksums
+}
+
+/// Returns `true` if the IP packet of a raw Ethernet frame is a fragment: an IPv4
+/// packet with More Fragments set or a nonzero offset, or an IPv6 packet with a
+/// Fragment header.
+fn is_fragment(frame: &[u8]) -> bool {
+    encapsulation::ipv4_fragment(frame).is_some_and(|fragment| fragment.is_fragment())
+        || encapsulation::ipv6_extensions(frame).is_some_and(|extensions| extensions.is_fragment())
+}
+
+/// Recomputes the checksum of an IPv4 header.
+fn verify_ipv4_header(packet: &[u8]) -> Option<Checksum> {
+    let header_len = usize::from(packet.first()? & 0x0f) * 4;
+    let header = packet.get(..header_len)?;
+
+    Some(Checksum {
+        layer: "IPv4",
+        stored: read_u16(header, IPV4_CHECKSUM_OFFSET)?,
+        computed: fold(sum_words(header, IPV4_CHECKSUM_OFFSET)),
+    })
+}
+
+/// Sums the pseudo-header that TCP and UDP checksums cover, of either IP version.
+fn pseudo_header_sum(source: IpAddr, destination: IpAddr, protocol: u8, len: usize) -> u64 {
+    let mut pseudo_header = Vec::with_capacity(40);
+    for address in [source, destination] {
+        match address {
+            IpAddr::V4(address) => pseudo_header.extend_from_slice(&address.octets()),
+            IpAddr::V6(address) => pseudo_header.extend_from_slice(&address.octets()),
+        }
+    }
+    pseudo_header.extend_from_slice(&[0, protocol]);
+    pseudo_header.extend_from_slice(&(len as u32).to_be_bytes());
+
+    sum_words(&pseudo_header, usize::MAX)
+}
+
+/// Sums `data` as big-endian 16-bit words, treating the word at `skip` as zero
+/// and padding an odd trailing byte with zero.
+fn sum_words(data: &[u8], skip: usize) -> u64 {
+    data.chunks(2)
+        .enumerate()
+        .filter(|(index, _)| index * 2 != skip)
+        .map(|(_, word)| match word {
+            [high, low] => u64::from(u16::from_be_bytes([*high, *low])),
+            [high] => u64::from(*high) << 8,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Folds a sum of words into the ones' complement checksum.
+fn fold(mut sum: u64) -> u16 {
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A UDP datagram from 192.0.2.1:50000 to 198.51.100.1:53 carrying `wyre`, with
+    /// valid IPv4 and UDP checksums.
+    fn ipv4_udp_frame() -> Vec<u8> {
+        let mut frame = vec![
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0x08, 0x00,
+        ];
+        frame.extend_from_slice(&[
+            0x45, 0x00, 0x00, 0x20, 0x00, 0x01, 0x00, 0x00, 0x40, 0x11, 0x8e, 0x96, // header
+            192, 0, 2, 1, // source address
+            198, 51, 100, 1, // destination address
+        ]);
+        frame.extend_from_slice(&[
+            0xc3, 0x50, 0x00, 0x35, 0x00, 0x0c, 0x66, 0x3b, // ports, length and checksum
+            b'w', b'y', b'r', b'e',
+        ]);
+        frame
+    }
+
+    #[test]
+    fn verifies_known_good_checksums() {
+        assert_eq!(
+            verify(&ipv4_udp_frame()),
+            [
+                Checksum {
+                    layer: "IPv4",
+                    stored: 0x8e96,
+                    computed: 0x8e96,
+                },
+                Checksum {
+                    layer: "UDP",
+                    stored: 0x663b,
+                    computed: 0x663b,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_corrupted_checksums() {
+        let mut frame = ipv4_udp_frame();
+        // Flip a payload byte, which only the UDP checksum covers
+        frame[42] ^= 0x01;
+
+        let checksums = verify(&frame);
+
+        assert!(checksums[0].is_valid());
+        assert_eq!(checksums[1].layer, "UDP");
+        assert_eq!(checksums[1].stored, 0x663b);
+        assert!(!checksums[1].is_valid());
+    }
+
+    #[test]
+    fn skips_transport_checksum_of_ipv4_fragments() {
+        // More Fragments set on the first fragment, then a later fragment
+        for flags_and_offset in [[0x20, 0x00], [0x00, 0x01]] {
+            let mut frame = ipv4_udp_frame();
+            frame[20..22].copy_from_slice(&flags_and_offset);
+
+            let checksums = verify(&frame);
+
+            assert_eq!(checksums.len(), 1);
+            assert_eq!(checksums[0].layer, "IPv4");
+        }
+    }
+
+    #[test]
+    fn skips_transport_checksum_of_ipv6_fragments() {
+        let mut frame = vec![
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0x86, 0xdd,
+        ];
+        frame.extend_from_slice(&[
+            0x60, 0x00, 0x00, 0x00, 0x00, 0x14, 0x2c, 0x40, // header, next header Fragment
+            0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, // source address
+            0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, // destination address
+            0x11, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x07, // Fragment header, More Fragments
+            0xc3, 0x50, 0x00, 0x35, 0x00, 0x0c, 0x12, 0x34, // UDP header
+            b'w', b'y', b'r', b'e',
+        ]);
+
+        assert!(verify(&frame).is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+use super::{snapshot_server::SnapshotServer, state::CaptureState};
+use crate::logger::format_packets::format_flow;
+use log::info;
+use serde_json::{json, Value};
+use std::io;
+
+const DASHBOARD_HTML: &str = include_str!("dashboard.html");
+
+/// The number of flows shown in the top talkers table.
+const TOP_TALKERS_LEN: usize = 10;
+
+/// A minimal web dashboard showing live statistics of a capture.
+///
+/// `GET /` serves a single static HTML page which polls `GET /stats` for a JSON
+/// snapshot of the protocol breakdown, top talkers and byte counters. The capture
+/// loop publishes snapshots at most once per second, so serving requests never
+/// contends with per-packet processing.
+pub struct Dashboard {
+    server: SnapshotServer,
+}
+
+impl Dashboard {
+    /// Binds to `addr` and starts serving the dashboard on a background thread.
+    pub fn start(addr: &str) -> io::Result<Self> {
+        let server = SnapshotServer::start(addr, "dashboard", json!({}).to_string(), Self::route)?;
+
+        info!("Serving dashboard on http://{}", server.local_addr());
+
+        Ok(Self { server })
+    }
+
+    /// Publishes a snapshot of the capture state if the publish interval has elapsed.
+    pub fn publish(&mut self, state: &CaptureState) {
+        self.server
+            .publish(|| Self::snapshot_json(state).to_string());
+    }
+
+    fn snapshot_json(state: &CaptureState) -> Value {
+        let stats = &state.stats;
+        let top_talkers = state
+            .flows
+            .top(TOP_TALKERS_LEN)
+            .into_iter()
+            .map(|(key, flow)| {
+                json!({
+                    "flow": format_flow(key, flow),
+                    "bytes": flow.total_bytes(),
+                })
+            })
+            .collect::<Vec<Value>>();
+
+        json!({
+            "packets": stats.packets,
+            "bytes": stats.bytes,
+            "protocols": {
+                "IPv4": stats.ipv4,
+                "IPv6": stats.ipv6,
+                "TCP": stats.tcp,
+                "UDP": stats.udp,
+                "ICMP": stats.icmp,
+                "Other": stats.other,
+            },
+            "destinations": {
+                "unicast": stats.unicast,
+                "multicast": stats.multicast,
+                "broadcast": stats.broadcast,
+            },
+            "top_talkers": top_talkers,
+        })
+    }
+
+    fn route(path: &str, snapshot: &str) -> Option<(&'static str, String)> {
+        match path {
+            "/" | "/index.html" => Some(("text/html", DASHBOARD_HTML.to_string())),
+            "/stats" => Some(("application/json", snapshot.to_string())),
+            _ => None,
+        }
+    }
+}
@@ -1,6 +1,375 @@
-use pcap::PacketHeader;
+#[cfg(feature = "geoip")]
+use super::geoip::GeoIp;
+use super::{filter::PacketFilter, pcap_writer::DEFAULT_SNAPLEN, vlan::VlanTag};
+use clap::ValueEnum;
+use net_sift::parsers::ethernet_frame::EthernetFrame;
+use pcap::{Linktype, PacketHeader, Stat};
+#[cfg(feature = "geoip")]
+use std::sync::Arc;
+use std::{net::IpAddr, path::PathBuf, time::Duration};
 
 pub enum ReadPacketResult {
     Success((PacketHeader, Vec<u8>)),
     Error(String),
+    /// The packets received and dropped so far, as counted by libpcap.
+    Stats(Stat),
+}
+
+/// A captured packet along with the layers parsed from it, as handed to the
+/// closure of `Analyzer::capture_with`.
+pub struct ParsedFrame<'a> {
+    pub header: &'a PacketHeader,
+    /// The raw bytes of the packet, as captured.
+    pub data: &'a [u8],
+    /// The VLAN tags removed from the frame before it was parsed.
+    pub vlan_tags: Vec<VlanTag>,
+    /// The Ethernet frame and the layers net_sift parsed inside it, or `None` if
+    /// it could not be parsed. Loopback packets are parsed as the Ethernet frame of
+    /// their IP packet.
+    pub frame: Option<EthernetFrame>,
+}
+
+/// The format in which decoded packets are emitted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable log lines.
+    #[default]
+    Text,
+    /// One JSON object per packet and line (NDJSON), written to stdout.
+    Json,
+    /// One comma-separated row per packet, after a header row, written to stdout.
+    Csv,
+}
+
+/// How much detail is decoded and shown for each packet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum DecodeLevel {
+    /// Only the Ethernet and IP layers.
+    Summary,
+    /// The Ethernet, IP and transport layers.
+    #[default]
+    Standard,
+    /// Also IP and TCP options and a hexdump of the payload.
+    Verbose,
+}
+
+/// How a capture to a named pipe behaves until a reader opens the pipe.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum FifoMode {
+    /// Wait for a reader before starting the capture.
+    #[default]
+    Block,
+    /// Start capturing immediately and buffer packets until a reader attaches.
+    Buffer,
+}
+
+/// The application protocols that objects can be extracted from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum ExtractProtocol {
+    /// Response bodies of HTTP/1.x exchanges.
+    #[default]
+    Http,
+}
+
+/// The file format that captures are saved in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum FileFormat {
+    /// Classic pcap, written by libpcap.
+    #[default]
+    Pcap,
+    /// PCAPNG, which keeps the interface name and nanosecond timestamps.
+    #[cfg(feature = "pcapng")]
+    Pcapng,
+}
+
+impl FileFormat {
+    /// The extension of files of this format, without the dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FileFormat::Pcap => "pcap",
+            #[cfg(feature = "pcapng")]
+            FileFormat::Pcapng => "pcapng",
+        }
+    }
+}
+
+/// Where the packets of a capture are saved.
+#[derive(Debug, Clone)]
+pub enum SaveTarget {
+    /// A `<file_name>.pcap` or `<file_name>.pcapng` file in the `dir_name` directory.
+    File {
+        dir_name: String,
+        file_name: String,
+        format: FileFormat,
+    },
+    /// A named pipe (FIFO) read live by another process.
+    Fifo { path: String, mode: FifoMode },
+    /// A single file of at most `size` bytes that keeps the most recent packets.
+    Ring { path: String, size: u64 },
+    /// A pcap stream written to stdout, e.g. for piping into Wireshark.
+    Stdout,
+}
+
+impl SaveTarget {
+    /// The target of the `run`-th run of a repeated capture: files are numbered,
+    /// e.g. `capture-001.pcap`, while a FIFO is reused.
+    pub fn numbered(&self, run: usize) -> Self {
+        match self {
+            SaveTarget::File {
+                dir_name,
+                file_name,
+                format,
+            } => SaveTarget::File {
+                dir_name: dir_name.clone(),
+                file_name: format!("{}-{:03}", file_name, run),
+                format: *format,
+            },
+            SaveTarget::Fifo { .. } | SaveTarget::Ring { .. } | SaveTarget::Stdout => self.clone(),
+        }
+    }
+}
+
+/// The traffic measure a talker alert threshold applies to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TalkerMetric {
+    Bytes,
+    Packets,
+}
+
+/// Alerts when a single host sends more than `threshold` bytes or packets within
+/// one `window`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TalkerAlert {
+    pub metric: TalkerMetric,
+    pub threshold: u64,
+    pub window: Duration,
+}
+
+/// Warns about a source whose unanswered TCP SYNs within one `window` reach
+/// `ports` distinct destination ports (a port scan), or `syns` SYNs to a single
+/// port (a SYN flood).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanThresholds {
+    pub ports: u64,
+    pub syns: u64,
+    pub window: Duration,
+}
+
+/// When a rotated capture moves on to its next file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RotateLimit {
+    /// After this many packets.
+    Packets(u64),
+    /// Before the file would grow beyond this many bytes.
+    Bytes(u64),
+}
+
+/// A daily window of local time, in minutes since midnight. A window that ends
+/// before it starts spans midnight, e.g. 22:00-06:00.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveHours {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl ActiveHours {
+    /// Returns `true` if `minute` (minutes since midnight) falls within the window,
+    /// including its start and excluding its end.
+    pub fn contains(&self, minute: u32) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+}
+
+/// Options controlling how captured packets are decoded and formatted.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// How much detail is decoded and shown for each layer.
+    pub decode_level: DecodeLevel,
+
+    /// The format in which decoded packets are emitted.
+    pub format: OutputFormat,
+
+    /// A file that each captured packet is also appended to as a JSON record, next
+    /// to the saved capture. Only used by `basic_capture`.
+    pub json_out: Option<PathBuf>,
+
+    /// Follow the decoded text of each packet with a hexdump of its raw bytes.
+    pub hex: bool,
+
+    /// Follow the decoded text of each packet with its application payload as
+    /// text, truncated to this many bytes.
+    pub payload: Option<usize>,
+
+    /// Abbreviate the field labels of decoded packets, for narrow terminals.
+    pub compact: bool,
+
+    /// Cut each line of a decoded packet to this many characters, if set.
+    pub width: Option<usize>,
+
+    /// Color the transport layer of decoded packets, or ARP, by protocol.
+    pub color: bool,
+
+    /// Only show the packets that fail to decode, each with a hexdump of its bytes.
+    pub only_errors: bool,
+
+    /// A capture file that the packets failing to decode are saved to.
+    pub errors_file: Option<PathBuf>,
+
+    /// Recompute the IPv4, TCP and UDP checksums of each packet and show whether
+    /// they match the stored ones.
+    pub verify_checksums: bool,
+
+    /// Only show packets whose parsed addresses and ports match.
+    pub filter: PacketFilter,
+
+    /// Annotate public IP addresses with their country, looked up in this database.
+    #[cfg(feature = "geoip")]
+    pub geoip: Option<Arc<GeoIp>>,
+
+    /// Log an alert as soon as a host exceeds this traffic threshold.
+    pub talker_alert: Option<TalkerAlert>,
+
+    /// Log an alert when a source looks like a port scanner or SYN flooder.
+    pub scan_detection: Option<ScanThresholds>,
+
+    /// The number of host pairs that exchanged the most traffic to list in the
+    /// end-of-capture summary, or 0 to list none.
+    pub top_talkers: usize,
+
+    /// The number of threads packets are decoded on. With 0 or 1, packets are
+    /// decoded on the thread processing the capture.
+    pub parse_threads: usize,
+
+    /// The addresses of the capture interface, used to mark packets as outbound or
+    /// inbound. Empty when reading capture files, which leaves packets unmarked.
+    pub local_addresses: Vec<IpAddr>,
+}
+
+/// Options specific to live streaming of packets.
+#[derive(Debug, Clone, Default)]
+pub struct StreamOptions {
+    /// Address to serve the live capture on as a pcap stream over HTTP.
+    pub serve_http: Option<String>,
+
+    /// Resident memory ceiling in bytes, above which the stream is stopped.
+    pub max_memory: Option<u64>,
+
+    /// Address to serve the live statistics dashboard on.
+    pub dashboard: Option<String>,
+
+    /// Address to serve the capture counters on as Prometheus metrics.
+    pub metrics_addr: Option<String>,
+
+    /// Only process packets captured within this daily window of local time.
+    pub active_hours: Option<ActiveHours>,
+
+    /// How often to log the packet and byte rates of the stream.
+    pub stats_interval: Option<Duration>,
+
+    /// Only log the rates instead of decoding each packet.
+    pub stats_only: bool,
+}
+
+/// Conditions under which a capture to file stops.
+#[derive(Debug, Clone, Default)]
+pub struct CaptureLimits {
+    /// The maximum number of packets to capture, or `0` to capture until interrupted.
+    pub packets: usize,
+
+    /// How long to capture for, timed from the start of each run.
+    pub duration: Option<Duration>,
+
+    /// The number of captured bytes to save, after which the capture stops. The
+    /// packet crossing the budget is still saved.
+    pub bytes: Option<u64>,
+
+    /// Resident memory ceiling in bytes, above which the capture is stopped.
+    pub max_memory: Option<u64>,
+}
+
+/// How many times a bounded capture is run and how far apart the runs start.
+#[derive(Debug, Clone)]
+pub struct CaptureSchedule {
+    /// The number of captures to run, each saved to its own numbered file.
+    pub runs: usize,
+
+    /// The time from the start of one run to the start of the next. A run that
+    /// takes longer is followed by the next run immediately.
+    pub interval: Duration,
+
+    /// Only save packets captured within this daily window of local time.
+    pub active_hours: Option<ActiveHours>,
+
+    /// Split each run into numbered files of at most this many packets or bytes.
+    pub rotate: Option<RotateLimit>,
+}
+
+impl Default for CaptureSchedule {
+    fn default() -> Self {
+        Self {
+            runs: 1,
+            interval: Duration::ZERO,
+            active_hours: None,
+            rotate: None,
+        }
+    }
+}
+
+/// Options applied when opening a capture handle.
+#[derive(Debug, Clone)]
+pub struct CaptureOptions {
+    /// The read timeout, in milliseconds, after which `next_packet` returns
+    /// even if no packet has arrived, so that the reader thread notices Ctrl-C
+    /// and the end of a timed capture on an idle interface.
+    pub timeout_ms: i32,
+    /// Whether to capture frames not addressed to this host, e.g. on a mirrored port.
+    pub promisc: bool,
+    /// A BPF expression selecting the packets to capture, e.g. `tcp port 443`.
+    pub filter: Option<String>,
+    /// The maximum number of bytes captured per packet, or libpcap's default if unset.
+    pub snaplen: Option<i32>,
+    /// The size of the kernel buffer packets are held in until they are read, in
+    /// bytes, or libpcap's default if unset.
+    pub buffer_size: Option<i32>,
+    /// Whether packets are delivered as soon as they arrive instead of being
+    /// buffered, at the cost of more wake-ups.
+    pub immediate: bool,
+    /// Whether to capture timestamps in nanoseconds rather than microseconds, in
+    /// which case the `tv_usec` of packet headers holds nanoseconds.
+    pub nanosecond_timestamps: bool,
+    /// Whether a live capture reopens its interface after a capture error, such as
+    /// the interface going away, instead of ending.
+    pub reconnect: bool,
+    /// The datalink type to capture with, for interfaces that support several,
+    /// or the default of the interface if unset.
+    pub datalink: Option<Linktype>,
+}
+
+impl CaptureOptions {
+    /// The snaplen to advertise in the header of a capture saved or streamed by
+    /// hand, which is the one the capture handle is opened with.
+    pub fn file_snaplen(&self) -> u32 {
+        self.snaplen
+            .map_or(DEFAULT_SNAPLEN, |snaplen| snaplen.max(0) as u32)
+    }
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 200,
+            promisc: false,
+            filter: None,
+            snaplen: None,
+            buffer_size: None,
+            immediate: false,
+            nanosecond_timestamps: false,
+            reconnect: false,
+            datalink: None,
+        }
+    }
 }
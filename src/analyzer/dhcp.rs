@@ -0,0 +1,143 @@
+use super::encapsulation::{read_mac, read_u16, read_u32};
+use std::net::Ipv4Addr;
+
+pub const DHCP_SERVER_PORT: u16 = 67;
+pub const DHCP_CLIENT_PORT: u16 = 68;
+
+const UDP_HEADER_LEN: usize = 8;
+/// The offset of the client hardware address in the fixed BOOTP fields.
+const CHADDR_OFFSET: usize = 28;
+/// The offset of the magic cookie, which follows the server name and boot file.
+const MAGIC_COOKIE_OFFSET: usize = 236;
+const MAGIC_COOKIE: u32 = 0x6382_5363;
+/// The hardware type of Ethernet, whose client addresses are MAC addresses.
+const HTYPE_ETHERNET: u8 = 1;
+
+const OPTION_PAD: u8 = 0;
+const OPTION_REQUESTED_IP: u8 = 50;
+const OPTION_MESSAGE_TYPE: u8 = 53;
+const OPTION_SERVER_IDENTIFIER: u8 = 54;
+const OPTION_END: u8 = 255;
+
+/// The fixed BOOTP fields and the options of a DHCP message that matter when
+/// following a lease.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DhcpMessage {
+    /// 1 for a request from a client, 2 for a reply from a server.
+    pub op: u8,
+    pub transaction_id: u32,
+    /// The address the client already holds, if any.
+    pub client_ip: Ipv4Addr,
+    /// The address offered or assigned to the client.
+    pub your_ip: Ipv4Addr,
+    /// The client's MAC address, `None` for hardware other than Ethernet.
+    pub client_mac: Option<[u8; 6]>,
+    /// The DHCP message type (option 53), `None` for plain BOOTP.
+    pub message_type: Option<u8>,
+    /// The requested IP address (option 50).
+    pub requested_ip: Option<Ipv4Addr>,
+    /// The server identifier (option 54).
+    pub server_identifier: Option<Ipv4Addr>,
+}
+
+impl DhcpMessage {
+    /// Parses the DHCP message carried by a UDP datagram between ports 67 and 68.
+    ///
+    /// # Arguments
+    /// * `datagram` - The UDP header and payload.
+    ///
+    /// # Returns
+    /// `Some(DhcpMessage)` if either port is 67 or 68 and the payload is a BOOTP
+    /// message with the DHCP magic cookie, `None` otherwise.
+    pub fn from_udp(datagram: &[u8]) -> Option<Self> {
+        let source_port = read_u16(datagram, 0)?;
+        let destination_port = read_u16(datagram, 2)?;
+        let is_dhcp_port = |port| port == DHCP_SERVER_PORT || port == DHCP_CLIENT_PORT;
+        if !is_dhcp_port(source_port) && !is_dhcp_port(destination_port) {
+            return None;
+        }
+
+        Self::from_bytes(datagram.get(UDP_HEADER_LEN..)?)
+    }
+
+    /// Parses a DHCP message.
+    ///
+    /// # Returns
+    /// `Some(DhcpMessage)` if the fixed fields are complete and followed by the
+    /// magic cookie, `None` otherwise. Options cut short are ignored, along with
+    /// any that follow them.
+    pub fn from_bytes(message: &[u8]) -> Option<Self> {
+        if read_u32(message, MAGIC_COOKIE_OFFSET)? != MAGIC_COOKIE {
+            return None;
+        }
+
+        let htype = *message.get(1)?;
+        let hlen = *message.get(2)?;
+        let client_mac = if htype == HTYPE_ETHERNET && hlen == 6 {
+            read_mac(message, CHADDR_OFFSET)
+        } else {
+            None
+        };
+
+        let mut dhcp = Self {
+            op: *message.first()?,
+            transaction_id: read_u32(message, 4)?,
+            client_ip: Ipv4Addr::from(read_u32(message, 12)?),
+            your_ip: Ipv4Addr::from(read_u32(message, 16)?),
+            client_mac,
+            message_type: None,
+            requested_ip: None,
+            server_identifier: None,
+        };
+
+        let mut position = MAGIC_COOKIE_OFFSET + 4;
+        while let Some(&code) = message.get(position) {
+            match code {
+                OPTION_PAD => {
+                    position += 1;
+                    continue;
+                }
+                OPTION_END => break,
+                _ => {}
+            }
+
+            let len = match message.get(position + 1) {
+                Some(&len) => usize::from(len),
+                None => break,
+            };
+            let value = match message.get(position + 2..position + 2 + len) {
+                Some(value) => value,
+                None => break,
+            };
+
+            match (code, value) {
+                (OPTION_MESSAGE_TYPE, [message_type]) => dhcp.message_type = Some(*message_type),
+                (OPTION_REQUESTED_IP, [a, b, c, d]) => {
+                    dhcp.requested_ip = Some(Ipv4Addr::new(*a, *b, *c, *d))
+                }
+                (OPTION_SERVER_IDENTIFIER, [a, b, c, d]) => {
+                    dhcp.server_identifier = Some(Ipv4Addr::new(*a, *b, *c, *d))
+                }
+                _ => {}
+            }
+            position += 2 + len;
+        }
+
+        Some(dhcp)
+    }
+}
+
+/// Returns the name of a DHCP message type, e.g. `DISCOVER` for 1.
+pub fn message_type_name(message_type: u8) -> String {
+    match message_type {
+        1 => "DISCOVER".to_string(),
+        2 => "OFFER".to_string(),
+        3 => "REQUEST".to_string(),
+        4 => "DECLINE".to_string(),
+        5 => "ACK".to_string(),
+        6 => "NAK".to_string(),
+        7 => "RELEASE".to_string(),
+        8 => "INFORM".to_string(),
+        _ => format!("TYPE{}", message_type),
+    }
+}
@@ -0,0 +1,52 @@
+use super::{encapsulation, vlan};
+use std::net::IpAddr;
+
+/// Whether a packet was sent or received by the capturing host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketDirection {
+    Outbound,
+    Inbound,
+    /// Traffic between other hosts, as seen in promiscuous mode, or without an IP layer.
+    Other,
+}
+
+impl PacketDirection {
+    /// Classifies a raw Ethernet frame by its IP addresses.
+    ///
+    /// # Arguments
+    /// * `frame` - The raw bytes of an Ethernet frame.
+    /// * `local_addresses` - The addresses of the capture interface.
+    pub fn of_frame(frame: &[u8], local_addresses: &[IpAddr]) -> Self {
+        let (_, untagged) = vlan::strip_tags(frame);
+        let header = match encapsulation::parse_ip(&untagged) {
+            Some((header, _)) => header,
+            None => return PacketDirection::Other,
+        };
+
+        if local_addresses.contains(&header.source_address) {
+            PacketDirection::Outbound
+        } else if local_addresses.contains(&header.destination_address) {
+            PacketDirection::Inbound
+        } else {
+            PacketDirection::Other
+        }
+    }
+
+    /// The marker that starts the log line of a packet: `>` for outbound, `<` for
+    /// inbound and `-` for other traffic.
+    pub fn marker(&self) -> &'static str {
+        match self {
+            PacketDirection::Outbound => ">",
+            PacketDirection::Inbound => "<",
+            PacketDirection::Other => "-",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PacketDirection::Outbound => "outbound",
+            PacketDirection::Inbound => "inbound",
+            PacketDirection::Other => "other",
+        }
+    }
+}
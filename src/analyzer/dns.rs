@@ -0,0 +1,199 @@
+use super::encapsulation::{read_u16, read_u32};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+pub const DNS_PORT: u16 = 53;
+
+const DNS_HEADER_LEN: usize = 12;
+const UDP_HEADER_LEN: usize = 8;
+/// The number of compression pointers followed in one name before it is treated
+/// as malformed, which guards against pointer loops.
+const MAX_NAME_POINTERS: usize = 16;
+
+/// A question of a DNS message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DnsQuestion {
+    pub name: String,
+    pub record_type: u16,
+}
+
+/// A resource record in the answer section of a DNS message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DnsAnswer {
+    pub name: String,
+    pub record_type: u16,
+    pub ttl: u32,
+    /// The record data in presentation form, e.g. an address for A records.
+    pub data: String,
+}
+
+/// The header, questions and answers of a DNS message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DnsMessage {
+    pub id: u16,
+    pub response: bool,
+    pub rcode: u8,
+    pub questions: Vec<DnsQuestion>,
+    pub answers: Vec<DnsAnswer>,
+}
+
+impl DnsMessage {
+    /// Parses the DNS message carried by a UDP datagram to or from port 53.
+    ///
+    /// # Arguments
+    /// * `datagram` - The UDP header and payload.
+    ///
+    /// # Returns
+    /// `Some(DnsMessage)` if either port is 53 and the payload is a valid DNS
+    /// message, `None` otherwise.
+    pub fn from_udp(datagram: &[u8]) -> Option<Self> {
+        let source_port = read_u16(datagram, 0)?;
+        let destination_port = read_u16(datagram, 2)?;
+        if source_port != DNS_PORT && destination_port != DNS_PORT {
+            return None;
+        }
+
+        Self::from_bytes(datagram.get(UDP_HEADER_LEN..)?)
+    }
+
+    /// Parses a DNS message.
+    ///
+    /// # Returns
+    /// `Some(DnsMessage)` if the header, questions and answers are complete and
+    /// well-formed, `None` otherwise. The authority and additional sections are
+    /// not decoded.
+    pub fn from_bytes(message: &[u8]) -> Option<Self> {
+        let flags = read_u16(message, 2)?;
+        let question_count = read_u16(message, 4)?;
+        let answer_count = read_u16(message, 6)?;
+
+        let mut position = DNS_HEADER_LEN;
+        let mut questions = Vec::new();
+        for _ in 0..question_count {
+            let (name, end) = read_name(message, position)?;
+            questions.push(DnsQuestion {
+                name,
+                record_type: read_u16(message, end)?,
+            });
+            // Skip the type and class
+            position = end + 4;
+        }
+
+        let mut answers = Vec::new();
+        for _ in 0..answer_count {
+            let (name, end) = read_name(message, position)?;
+            let record_type = read_u16(message, end)?;
+            let ttl = read_u32(message, end + 4)?;
+            let data_len = usize::from(read_u16(message, end + 8)?);
+            let data_start = end + 10;
+            let data = message.get(data_start..data_start + data_len)?;
+
+            answers.push(DnsAnswer {
+                name,
+                record_type,
+                ttl,
+                data: format_record_data(message, record_type, data_start, data),
+            });
+            position = data_start + data_len;
+        }
+
+        Some(Self {
+            id: read_u16(message, 0)?,
+            response: flags & 0x8000 != 0,
+            rcode: (flags & 0x000f) as u8,
+            questions,
+            answers,
+        })
+    }
+}
+
+/// Reads a possibly compressed domain name starting at `position`.
+///
+/// # Returns
+/// The name in dotted form, `.` for the root, and the position following the name
+/// where it started, or `None` if the name is malformed.
+fn read_name(message: &[u8], mut position: usize) -> Option<(String, usize)> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut end = None;
+    let mut pointers = 0;
+
+    loop {
+        let len = *message.get(position)?;
+        match len & 0xc0 {
+            0x00 if len == 0 => {
+                let name = if labels.is_empty() {
+                    ".".to_string()
+                } else {
+                    labels.join(".")
+                };
+                return Some((name, end.unwrap_or(position + 1)));
+            }
+            0x00 => {
+                let label = message.get(position + 1..position + 1 + usize::from(len))?;
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                position += 1 + usize::from(len);
+            }
+            0xc0 => {
+                pointers += 1;
+                if pointers > MAX_NAME_POINTERS {
+                    return None;
+                }
+
+                let offset = usize::from(read_u16(message, position)? & 0x3fff);
+                end.get_or_insert(position + 2);
+                position = offset;
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Formats the data of a resource record for display.
+fn format_record_data(message: &[u8], record_type: u16, start: usize, data: &[u8]) -> String {
+    let formatted = match (record_type, data.len()) {
+        (1, 4) => Some(Ipv4Addr::new(data[0], data[1], data[2], data[3]).to_string()),
+        (28, 16) => {
+            let mut address = [0u8; 16];
+            address.copy_from_slice(data);
+            Some(Ipv6Addr::from(address).to_string())
+        }
+        // NS, CNAME and PTR hold a single name, which may point outside the record
+        (2 | 5 | 12, _) => read_name(message, start).map(|(name, _)| name),
+        (15, _) => read_u16(data, 0).and_then(|preference| {
+            read_name(message, start + 2).map(|(name, _)| format!("{} {}", preference, name))
+        }),
+        _ => None,
+    };
+
+    formatted.unwrap_or_else(|| format!("<{} bytes>", data.len()))
+}
+
+/// Returns the mnemonic of a DNS record type, e.g. `AAAA` for 28.
+pub fn record_type_name(record_type: u16) -> String {
+    match record_type {
+        1 => "A".to_string(),
+        2 => "NS".to_string(),
+        5 => "CNAME".to_string(),
+        6 => "SOA".to_string(),
+        12 => "PTR".to_string(),
+        15 => "MX".to_string(),
+        16 => "TXT".to_string(),
+        28 => "AAAA".to_string(),
+        33 => "SRV".to_string(),
+        65 => "HTTPS".to_string(),
+        255 => "ANY".to_string(),
+        _ => format!("TYPE{}", record_type),
+    }
+}
+
+/// Returns the mnemonic of a DNS response code, e.g. `NXDOMAIN` for 3.
+pub fn rcode_name(rcode: u8) -> String {
+    match rcode {
+        0 => "NOERROR".to_string(),
+        1 => "FORMERR".to_string(),
+        2 => "SERVFAIL".to_string(),
+        3 => "NXDOMAIN".to_string(),
+        4 => "NOTIMP".to_string(),
+        5 => "REFUSED".to_string(),
+        _ => format!("RCODE{}", rcode),
+    }
+}
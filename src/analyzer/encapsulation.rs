@@ -0,0 +1,354 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+pub const ETHERNET_HEADER_LEN: usize = 14;
+pub const ETHER_TYPE_IPV4: u16 = 0x0800;
+pub const ETHER_TYPE_IPV6: u16 = 0x86DD;
+
+pub const IP_PROTOCOL_IPV4: u8 = 4;
+pub const IP_PROTOCOL_IPV6: u8 = 41;
+
+pub const IPV6_HOP_BY_HOP: u8 = 0;
+pub const IPV6_ROUTING: u8 = 43;
+pub const IPV6_FRAGMENT: u8 = 44;
+pub const IPV6_NO_NEXT_HEADER: u8 = 59;
+pub const IPV6_DESTINATION_OPTIONS: u8 = 60;
+
+const IPV4_MIN_HEADER_LEN: usize = 20;
+const IPV6_HEADER_LEN: usize = 40;
+const IPV6_FRAGMENT_HEADER_LEN: usize = 8;
+
+/// The addresses and protocol of an IPv4 or IPv6 header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IpHeader {
+    pub source_address: IpAddr,
+    pub destination_address: IpAddr,
+    pub protocol: u8,
+}
+
+/// The extension headers of an IPv6 packet, in the order they are chained.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ipv6Extensions {
+    /// The protocol numbers of the extension headers.
+    pub headers: Vec<u8>,
+    /// The upper-layer protocol following the last extension header, or
+    /// `IPV6_FRAGMENT` for a fragment other than the first, whose data follows.
+    pub protocol: u8,
+    /// The combined length of the extension headers.
+    pub len: usize,
+}
+
+impl Ipv6Extensions {
+    /// Returns `true` if the packet carries a Fragment header.
+    pub fn is_fragment(&self) -> bool {
+        self.headers.contains(&IPV6_FRAGMENT) || self.protocol == IPV6_FRAGMENT
+    }
+}
+
+/// The fragmentation fields of an IPv4 header.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ipv4Fragment {
+    /// The Don't Fragment flag.
+    pub dont_fragment: bool,
+    /// The More Fragments flag, set on every fragment but the last.
+    pub more_fragments: bool,
+    /// The offset of the fragment's data in the original packet, in bytes.
+    pub offset: u16,
+}
+
+impl Ipv4Fragment {
+    /// Returns `true` if the packet is a fragment of a larger packet.
+    pub fn is_fragment(&self) -> bool {
+        self.more_fragments || self.offset != 0
+    }
+
+    /// Returns `true` if the packet is a fragment other than the first, whose
+    /// payload does not start with the transport header.
+    pub fn is_non_first(&self) -> bool {
+        self.offset != 0
+    }
+}
+
+/// An IP packet carried inside another IP packet, over IPv4 or IPv6.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IpTunnel<'a> {
+    pub outer: IpHeader,
+    pub inner_ether_type: u16,
+    pub payload: &'a [u8],
+}
+
+/// Reads the EtherType of a raw Ethernet frame.
+pub fn ether_type(frame: &[u8]) -> Option<u16> {
+    read_u16(frame, 12)
+}
+
+/// Reads a big-endian `u16` at `offset`, if the slice is long enough.
+pub fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+/// Reads a big-endian `u32` at `offset`, if the slice is long enough.
+pub fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Reads a MAC address at `offset`, if the slice is long enough.
+pub fn read_mac(data: &[u8], offset: usize) -> Option<[u8; 6]> {
+    let mut mac = [0u8; 6];
+    mac.copy_from_slice(data.get(offset..offset + 6)?);
+    Some(mac)
+}
+
+/// Formats a MAC address as colon separated hex octets.
+pub fn format_mac(mac: &[u8; 6]) -> String {
+    mac.iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<String>>()
+        .join(":")
+}
+
+/// Wraps an L3 payload in a synthetic Ethernet header.
+///
+/// net_sift only parses from the Ethernet layer down, so decapsulated packets are
+/// given a zeroed Ethernet header carrying `ether_type` before being handed back
+/// to the regular parsing pipeline.
+pub fn wrap_in_ethernet(ether_type: u16, payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0u8; ETHERNET_HEADER_LEN];
+    frame[12..14].copy_from_slice(&ether_type.to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Detects an IPv4 (protocol 4) or IPv6 (protocol 41) tunnel in a raw
+/// Ethernet frame and returns the outer header along with the inner packet.
+///
+/// # Arguments
+/// * `frame` - The raw bytes of an Ethernet frame.
+///
+/// # Returns
+/// `Some(IpTunnel)` if the frame carries an IPv4 or IPv6 packet whose payload is
+/// itself an IP packet, `None` otherwise.
+pub fn decapsulate_ip(frame: &[u8]) -> Option<IpTunnel<'_>> {
+    let (outer, payload) = parse_ip(frame)?;
+
+    let inner_ether_type = match outer.protocol {
+        IP_PROTOCOL_IPV4 => ETHER_TYPE_IPV4,
+        IP_PROTOCOL_IPV6 => ETHER_TYPE_IPV6,
+        _ => return None,
+    };
+
+    Some(IpTunnel {
+        outer,
+        inner_ether_type,
+        payload,
+    })
+}
+
+/// Parses the IP header of a raw Ethernet frame.
+///
+/// # Returns
+/// `Some((IpHeader, payload))` if the frame carries an IPv4 or IPv6 packet, where
+/// `payload` holds the bytes following the IP header up to the length given in the
+/// IP header, excluding any Ethernet padding, `None` otherwise.
+pub fn parse_ip(frame: &[u8]) -> Option<(IpHeader, &[u8])> {
+    let packet = frame.get(ETHERNET_HEADER_LEN..)?;
+
+    match ether_type(frame)? {
+        ETHER_TYPE_IPV4 => parse_ipv4_header(packet),
+        ETHER_TYPE_IPV6 => parse_ipv6_header(packet),
+        _ => None,
+    }
+}
+
+/// Returns the options of the IPv4 header of a raw Ethernet frame, which are
+/// empty unless the header is longer than 20 bytes.
+pub fn ipv4_options(frame: &[u8]) -> Option<&[u8]> {
+    if ether_type(frame)? != ETHER_TYPE_IPV4 {
+        return None;
+    }
+
+    let packet = frame.get(ETHERNET_HEADER_LEN..)?;
+    let header_len = usize::from(packet.first()? & 0x0f) * 4;
+    packet.get(IPV4_MIN_HEADER_LEN..header_len)
+}
+
+/// Reads the fragmentation flags and offset of the IPv4 header of a raw Ethernet
+/// frame, or `None` if the frame does not carry IPv4.
+pub fn ipv4_fragment(frame: &[u8]) -> Option<Ipv4Fragment> {
+    if ether_type(frame)? != ETHER_TYPE_IPV4 {
+        return None;
+    }
+
+    let fragment = read_u16(frame, ETHERNET_HEADER_LEN + 6)?;
+    Some(Ipv4Fragment {
+        dont_fragment: fragment & 0x4000 != 0,
+        more_fragments: fragment & 0x2000 != 0,
+        offset: (fragment & 0x1fff) * 8,
+    })
+}
+
+fn parse_ipv4_header(packet: &[u8]) -> Option<(IpHeader, &[u8])> {
+    let header_len = usize::from(packet.first()? & 0x0f) * 4;
+    if header_len < IPV4_MIN_HEADER_LEN || packet.len() < header_len {
+        return None;
+    }
+
+    // The total length may exceed the captured bytes if the packet was truncated,
+    // and is zero for large segments captured before TCP segmentation offload
+    let end = match usize::from(read_u16(packet, 2)?) {
+        0 => packet.len(),
+        total_len => total_len.clamp(header_len, packet.len()),
+    };
+
+    let header = IpHeader {
+        source_address: IpAddr::V4(Ipv4Addr::new(
+            packet[12], packet[13], packet[14], packet[15],
+        )),
+        destination_address: IpAddr::V4(Ipv4Addr::new(
+            packet[16], packet[17], packet[18], packet[19],
+        )),
+        protocol: packet[9],
+    };
+
+    Some((header, &packet[header_len..end]))
+}
+
+/// Returns the extension headers of the IPv6 packet of a raw Ethernet frame.
+pub fn ipv6_extensions(frame: &[u8]) -> Option<Ipv6Extensions> {
+    if ether_type(frame)? != ETHER_TYPE_IPV6 {
+        return None;
+    }
+
+    let packet = frame.get(ETHERNET_HEADER_LEN..)?;
+    Some(walk_ipv6_extensions(
+        *packet.get(6)?,
+        packet.get(IPV6_HEADER_LEN..)?,
+    ))
+}
+
+/// Follows the chain of IPv6 extension headers that starts with `next_header`.
+///
+/// AH is left to be decoded as an upper-layer protocol, as it is for IPv4. A chain
+/// cut short by the snapshot length ends at the first incomplete header, and the
+/// chain of a fragment other than the first ends at its Fragment header, as the
+/// data that follows is the middle of the fragmented datagram.
+///
+/// # Arguments
+/// * `next_header` - The Next Header field of the fixed IPv6 header.
+/// * `data` - The bytes following the fixed IPv6 header.
+fn walk_ipv6_extensions(next_header: u8, data: &[u8]) -> Ipv6Extensions {
+    let mut extensions = Ipv6Extensions {
+        headers: Vec::new(),
+        protocol: next_header,
+        len: 0,
+    };
+
+    while matches!(
+        extensions.protocol,
+        IPV6_HOP_BY_HOP | IPV6_ROUTING | IPV6_FRAGMENT | IPV6_DESTINATION_OPTIONS
+    ) {
+        let header = &data[extensions.len..];
+        let header_len = match (extensions.protocol, header.get(1)) {
+            (IPV6_FRAGMENT, _) => IPV6_FRAGMENT_HEADER_LEN,
+            (_, Some(&len)) => (usize::from(len) + 1) * 8,
+            (_, None) => break,
+        };
+        if header.len() < header_len {
+            break;
+        }
+        // The fragment offset is stored in the upper 13 bits, in units of 8 bytes
+        if extensions.protocol == IPV6_FRAGMENT
+            && read_u16(header, 2).is_some_and(|field| field & 0xfff8 != 0)
+        {
+            break;
+        }
+
+        extensions.headers.push(extensions.protocol);
+        extensions.protocol = header[0];
+        extensions.len += header_len;
+    }
+
+    extensions
+}
+
+fn parse_ipv6_header(packet: &[u8]) -> Option<(IpHeader, &[u8])> {
+    if packet.len() < IPV6_HEADER_LEN {
+        return None;
+    }
+
+    let end =
+        (IPV6_HEADER_LEN + usize::from(read_u16(packet, 4)?)).clamp(IPV6_HEADER_LEN, packet.len());
+
+    let mut source = [0u8; 16];
+    let mut destination = [0u8; 16];
+    source.copy_from_slice(&packet[8..24]);
+    destination.copy_from_slice(&packet[24..40]);
+
+    // The upper-layer protocol and its header follow any extension headers
+    let payload = &packet[IPV6_HEADER_LEN..end];
+    let extensions = walk_ipv6_extensions(packet[6], payload);
+
+    let header = IpHeader {
+        source_address: IpAddr::V6(Ipv6Addr::from(source)),
+        destination_address: IpAddr::V6(Ipv6Addr::from(destination)),
+        protocol: extensions.protocol,
+    };
+
+    Some((header, &payload[extensions.len..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walks_hop_by_hop_options_to_tcp() {
+        let mut data = vec![
+            0x06, 0x00, // next header TCP and length of 8 bytes
+            0x01, 0x04, 0x00, 0x00, 0x00, 0x00, // PadN option
+        ];
+        data.extend_from_slice(&[0u8; 20]);
+
+        assert_eq!(
+            walk_ipv6_extensions(IPV6_HOP_BY_HOP, &data),
+            Ipv6Extensions {
+                headers: vec![IPV6_HOP_BY_HOP],
+                protocol: 6,
+                len: 8,
+            }
+        );
+    }
+
+    #[test]
+    fn walks_first_fragment_to_upper_layer() {
+        let mut data = vec![
+            0x11, 0x00, 0x00, 0x01, // next header UDP, offset 0, More Fragments
+            0x00, 0x00, 0x00, 0x07, // identification
+        ];
+        data.extend_from_slice(&[0u8; 8]);
+
+        let extensions = walk_ipv6_extensions(IPV6_FRAGMENT, &data);
+
+        assert_eq!(extensions.headers, [IPV6_FRAGMENT]);
+        assert_eq!(extensions.protocol, 17);
+        assert_eq!(extensions.len, 8);
+        assert!(extensions.is_fragment());
+    }
+
+    #[test]
+    fn stops_at_non_first_fragment() {
+        let mut data = vec![
+            0x11, 0x00, 0x05, 0xc8, // next header UDP, offset 1480, last fragment
+            0x00, 0x00, 0x00, 0x07, // identification
+        ];
+        data.extend_from_slice(&[0xaa; 8]);
+
+        let extensions = walk_ipv6_extensions(IPV6_FRAGMENT, &data);
+
+        assert!(extensions.headers.is_empty());
+        assert_eq!(extensions.protocol, IPV6_FRAGMENT);
+        assert_eq!(extensions.len, 0);
+        assert!(extensions.is_fragment());
+    }
+}
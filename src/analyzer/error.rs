@@ -21,12 +21,63 @@ pub enum AnalyzerError {
 
     #[error("Failed to open capture handle : {0}")]
     FailedToOpenCaptureHandle(#[source] PcapError),
+
+    #[error("Permission denied opening capture handle : {1}. Run as root, or grant the capture capabilities with `sudo setcap cap_net_raw,cap_net_admin=eip {0}`")]
+    CapturePermissionDenied(String, #[source] PcapError),
+
+    #[error("Invalid snaplen {0}, it must be positive")]
+    InvalidSnaplen(i32),
+
+    #[error("Invalid buffer size {0}, it must be positive")]
+    InvalidBufferSize(i32),
+
+    #[error("Failed to compile filter {0:?} : {1}")]
+    InvalidFilter(String, #[source] PcapError),
+
+    #[error("Failed to open capture file : {0}")]
+    FailedToOpenCaptureFile(#[source] PcapError),
+
+    #[error("Failed to open ring file : {0}")]
+    FailedToOpenRingFile(#[source] std::io::Error),
+
+    #[error("Datalink type {1} of {0} does not match datalink type {2} of the first file")]
+    MismatchedDatalink(String, i32, i32),
+
+    #[error("Failed to read packet : {0}")]
+    CaptureFailed(#[source] PcapError),
+
+    #[error("Failed to list datalink types : {0}")]
+    FailedToListDatalinks(#[source] PcapError),
+
+    #[error("Failed to set datalink type {0} : {1}")]
+    FailedToSetDatalink(String, #[source] PcapError),
+
+    #[error("Failed to set capture handle to non-blocking mode : {0}")]
+    FailedToSetNonBlocking(#[source] PcapError),
+
+    #[cfg(feature = "geoip")]
+    #[error("Failed to open GeoIP database : {0}")]
+    FailedToOpenGeoIpDatabase(#[source] maxminddb::MaxMindDBError),
+
+    #[error("Failed to parse packet : {0}")]
+    ParseFailed(#[from] ParserError),
 }
 
-impl From<ParserError> for AnalyzerError {
-    fn from(err: ParserError) -> Self {
-        match err {
-            _ => panic!("Other errors"),
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use net_sift::parsers::ethernet_frame::EthernetFrame;
+
+    #[test]
+    fn parser_errors_convert_without_panicking() {
+        // A frame shorter than an Ethernet header fails to parse
+        let Err(err) = EthernetFrame::from_bytes(&[0u8; 4], false) else {
+            panic!("a 4-byte frame should fail to parse");
+        };
+
+        assert!(matches!(
+            AnalyzerError::from(err),
+            AnalyzerError::ParseFailed(_)
+        ));
     }
 }
@@ -0,0 +1,182 @@
+use super::{definitions::FifoMode, pcap_writer::PcapWriter};
+use log::{info, warn};
+use pcap::{Linktype, PacketHeader};
+use std::{
+    collections::VecDeque,
+    ffi::CString,
+    fs::{File, OpenOptions},
+    io,
+    os::unix::{ffi::OsStrExt, fs::FileTypeExt},
+    path::Path,
+    sync::mpsc::{channel, Receiver, TryRecvError},
+    thread,
+};
+
+/// The maximum number of packets held while waiting for a reader in buffer mode.
+/// The oldest packets are dropped beyond this.
+const MAX_BUFFERED_PACKETS: usize = 10_000;
+
+enum FifoState {
+    /// No reader has opened the FIFO yet; packets are buffered in memory.
+    Waiting {
+        opened: Receiver<io::Result<File>>,
+        buffered: VecDeque<(PacketHeader, Vec<u8>)>,
+    },
+    Open(PcapWriter<File>),
+}
+
+/// Writes captured packets as a pcap stream to a named pipe (FIFO).
+///
+/// Opening a FIFO for writing blocks until a reader attaches. In `FifoMode::Block`
+/// the capture waits for the reader before starting; in `FifoMode::Buffer` the
+/// FIFO is opened on a background thread and packets are buffered until the reader
+/// attaches, or reported as dropped if none did by the end of the capture. A reader
+/// that disconnects surfaces as a `BrokenPipe` error from `write_packet` rather
+/// than a `SIGPIPE`, which Rust ignores by default.
+pub struct FifoWriter {
+    state: FifoState,
+    linktype: Linktype,
+    snaplen: u32,
+}
+
+impl FifoWriter {
+    /// Opens the FIFO at `path`, creating it if it does not exist.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the named pipe.
+    /// * `mode` - How to behave until a reader opens the pipe.
+    /// * `linktype` - The datalink type of the captured packets.
+    /// * `snaplen` - The maximum number of bytes captured per packet.
+    pub fn open(path: &str, mode: FifoMode, linktype: Linktype, snaplen: u32) -> io::Result<Self> {
+        create_fifo(Path::new(path))?;
+
+        let state = match mode {
+            FifoMode::Block => {
+                info!("Waiting for a reader to open {}", path);
+                let file = OpenOptions::new().write(true).open(path)?;
+                FifoState::Open(PcapWriter::new(file, linktype, snaplen)?)
+            }
+            FifoMode::Buffer => {
+                let (send_file, opened) = channel();
+                let path = path.to_string();
+                thread::spawn(move || {
+                    let _ = send_file.send(OpenOptions::new().write(true).open(path));
+                });
+
+                FifoState::Waiting {
+                    opened,
+                    buffered: VecDeque::new(),
+                }
+            }
+        };
+
+        Ok(Self {
+            state,
+            linktype,
+            snaplen,
+        })
+    }
+
+    /// Writes a packet to the FIFO, or buffers it while no reader is attached.
+    pub fn write_packet(&mut self, header: &PacketHeader, data: &[u8]) -> io::Result<()> {
+        let attached = match &mut self.state {
+            FifoState::Waiting { opened, buffered } => match opened.try_recv() {
+                Ok(file) => Some(attach(file?, buffered, self.linktype, self.snaplen)?),
+                Err(TryRecvError::Empty) => {
+                    if buffered.len() >= MAX_BUFFERED_PACKETS {
+                        buffered.pop_front();
+                    }
+                    buffered.push_back((*header, data.to_vec()));
+                    return Ok(());
+                }
+                Err(TryRecvError::Disconnected) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        "failed to open FIFO for writing",
+                    ))
+                }
+            },
+            FifoState::Open(_) => None,
+        };
+
+        if let Some(writer) = attached {
+            self.state = FifoState::Open(writer);
+        }
+
+        match &mut self.state {
+            FifoState::Open(writer) => writer.write_packet(header, data),
+            FifoState::Waiting { .. } => Ok(()),
+        }
+    }
+}
+
+impl Drop for FifoWriter {
+    /// Flushes the packets buffered in buffer mode if a reader attached since the
+    /// last packet was written, or reports how many are lost if none did.
+    fn drop(&mut self) {
+        let FifoState::Waiting { opened, buffered } = &mut self.state else {
+            return;
+        };
+        if buffered.is_empty() {
+            return;
+        }
+
+        let count = buffered.len();
+        match opened.try_recv() {
+            Ok(file) => {
+                if let Err(err) =
+                    file.and_then(|file| attach(file, buffered, self.linktype, self.snaplen))
+                {
+                    warn!(
+                        "Failed to flush {} buffered packets to the FIFO: {}",
+                        count, err
+                    );
+                }
+            }
+            Err(_) => warn!(
+                "No reader opened the FIFO, dropped {} buffered packets",
+                count
+            ),
+        }
+    }
+}
+
+/// Writes the pcap header and the packets buffered until a reader opened the FIFO.
+fn attach(
+    file: File,
+    buffered: &mut VecDeque<(PacketHeader, Vec<u8>)>,
+    linktype: Linktype,
+    snaplen: u32,
+) -> io::Result<PcapWriter<File>> {
+    info!("FIFO reader attached");
+    let mut writer = PcapWriter::new(file, linktype, snaplen)?;
+    for (header, data) in buffered.drain(..) {
+        writer.write_packet(&header, &data)?;
+    }
+
+    Ok(writer)
+}
+
+/// Creates a named pipe at `path` unless one already exists.
+fn create_fifo(path: &Path) -> io::Result<()> {
+    match path.metadata() {
+        Ok(metadata) if metadata.file_type().is_fifo() => return Ok(()),
+        Ok(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a FIFO", path.display()),
+            ))
+        }
+        Err(_) => {}
+    }
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    // SAFETY: `c_path` is a valid NUL-terminated string that outlives the call.
+    if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use net_sift::parsers::definitions::LayeredData;
+use std::net::IpAddr;
+
+/// Selects packets by the addresses and ports that net_sift parsed from them, for
+/// filtering that BPF cannot express or that applies to a saved capture.
+///
+/// Every criterion that is set must match. A packet without an IP layer, or
+/// without a TCP or UDP layer when a port is set, never matches.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PacketFilter {
+    pub src_ip: Option<IpAddr>,
+    pub dst_ip: Option<IpAddr>,
+    /// Matches either the source or the destination port.
+    pub port: Option<u16>,
+}
+
+impl PacketFilter {
+    /// Returns `true` if no criterion is set, so that every packet matches.
+    pub fn is_empty(&self) -> bool {
+        self.src_ip.is_none() && self.dst_ip.is_none() && self.port.is_none()
+    }
+
+    /// Checks the IP and transport layers of a parsed packet against the filter.
+    ///
+    /// # Arguments
+    /// * `layered_data` - The data of an Ethernet frame, starting with its IP layer.
+    pub fn matches(&self, layered_data: &LayeredData) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+
+        match layered_data {
+            LayeredData::Ipv4Data(ipv4) => self.matches_layers(
+                IpAddr::from(ipv4.header.source_address),
+                IpAddr::from(ipv4.header.destination_address),
+                &ipv4.data,
+            ),
+            LayeredData::Ipv6Data(ipv6) => self.matches_layers(
+                IpAddr::from(ipv6.header.source_address),
+                IpAddr::from(ipv6.header.destination_address),
+                &ipv6.data,
+            ),
+            _ => false,
+        }
+    }
+
+    /// Checks the addresses of an IP packet and the ports of its transport layer.
+    fn matches_layers(&self, source: IpAddr, destination: IpAddr, transport: &LayeredData) -> bool {
+        if self.src_ip.is_some_and(|ip| ip != source)
+            || self.dst_ip.is_some_and(|ip| ip != destination)
+        {
+            return false;
+        }
+
+        let port = match self.port {
+            Some(port) => port,
+            None => return true,
+        };
+
+        match transport {
+            LayeredData::TcpData(tcp) => {
+                tcp.header.source_port == port || tcp.header.destination_port == port
+            }
+            LayeredData::UdpData(udp) => {
+                udp.header.source_port == port || udp.header.destination_port == port
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use super::encapsulation::{self, read_u16};
+use std::{collections::HashMap, net::IpAddr};
+
+pub const IP_PROTOCOL_TCP: u8 = 6;
+pub const IP_PROTOCOL_UDP: u8 = 17;
+
+/// The number of flows a `FlowTable` keeps by default.
+const MAX_FLOWS: usize = 65_536;
+
+/// One side of a flow: an IP address and, for TCP and UDP, a port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Endpoint {
+    pub address: IpAddr,
+    pub port: u16,
+}
+
+/// The direction a packet travels in, relative to a normalized `FlowKey`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From endpoint `a` to endpoint `b`.
+    AToB,
+    /// From endpoint `b` to endpoint `a`.
+    BToA,
+}
+
+/// A bidirectional flow identifier.
+///
+/// The key is normalized so that both directions of a conversation map to the same
+/// key: endpoint `a` is always the lower of the two endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FlowKey {
+    pub protocol: u8,
+    pub a: Endpoint,
+    pub b: Endpoint,
+}
+
+impl FlowKey {
+    /// Builds the normalized flow key of a raw Ethernet frame.
+    ///
+    /// # Returns
+    /// `Some((FlowKey, Direction))` for IPv4 and IPv6 packets, where the direction
+    /// indicates whether the packet's source is endpoint `a` or `b`, `None` otherwise.
+    /// IPv4 fragments other than the first are skipped, as they carry no ports.
+    pub fn from_frame(frame: &[u8]) -> Option<(Self, Direction)> {
+        if encapsulation::ipv4_fragment(frame).is_some_and(|fragment| fragment.is_non_first()) {
+            return None;
+        }
+
+        let (header, payload) = encapsulation::parse_ip(frame)?;
+
+        let (source_port, destination_port) = match header.protocol {
+            IP_PROTOCOL_TCP | IP_PROTOCOL_UDP => (read_u16(payload, 0)?, read_u16(payload, 2)?),
+            _ => (0, 0),
+        };
+
+        let source = Endpoint {
+            address: header.source_address,
+            port: source_port,
+        };
+        let destination = Endpoint {
+            address: header.destination_address,
+            port: destination_port,
+        };
+
+        Some(Self::new(header.protocol, source, destination))
+    }
+
+    /// Builds the normalized flow key of a packet between two endpoints.
+    ///
+    /// # Returns
+    /// The key and whether `source` is endpoint `a` or `b` of it.
+    pub fn new(protocol: u8, source: Endpoint, destination: Endpoint) -> (Self, Direction) {
+        let (a, b, direction) = if source <= destination {
+            (source, destination, Direction::AToB)
+        } else {
+            (destination, source, Direction::BToA)
+        };
+
+        (Self { protocol, a, b }, direction)
+    }
+}
+
+/// Packet and byte counts for one direction of a flow.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Counter {
+    pub packets: u64,
+    pub bytes: u64,
+}
+
+impl Counter {
+    pub fn add(&mut self, bytes: usize) {
+        self.packets += 1;
+        self.bytes += bytes as u64;
+    }
+}
+
+/// Traffic counters of a flow, kept separately for each direction.
+#[derive(Debug, Clone)]
+pub struct FlowStats {
+    /// The direction of the first packet seen, taken to be sent by the initiator.
+    pub initiator: Direction,
+    pub a_to_b: Counter,
+    pub b_to_a: Counter,
+}
+
+impl FlowStats {
+    fn new(initiator: Direction) -> Self {
+        Self {
+            initiator,
+            a_to_b: Counter::default(),
+            b_to_a: Counter::default(),
+        }
+    }
+
+    /// Traffic sent by the initiator of the flow.
+    pub fn upload(&self) -> Counter {
+        match self.initiator {
+            Direction::AToB => self.a_to_b,
+            Direction::BToA => self.b_to_a,
+        }
+    }
+
+    /// Traffic received by the initiator of the flow.
+    pub fn download(&self) -> Counter {
+        match self.initiator {
+            Direction::AToB => self.b_to_a,
+            Direction::BToA => self.a_to_b,
+        }
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.a_to_b.bytes + self.b_to_a.bytes
+    }
+
+    /// The endpoints of the flow, initiator first.
+    pub fn endpoints(&self, key: &FlowKey) -> (Endpoint, Endpoint) {
+        match self.initiator {
+            Direction::AToB => (key.a, key.b),
+            Direction::BToA => (key.b, key.a),
+        }
+    }
+}
+
+/// Per-flow traffic counters for a capture.
+///
+/// The table is bounded: once it holds `max_flows` flows, the half that carried
+/// the fewest bytes is evicted before a new flow is added. A scan or flood of
+/// short flows then cannot grow it without bound, while the largest flows are
+/// kept for the summary.
+#[derive(Debug)]
+pub struct FlowTable {
+    flows: HashMap<FlowKey, FlowStats>,
+    max_flows: usize,
+}
+
+impl Default for FlowTable {
+    fn default() -> Self {
+        Self::with_max_flows(MAX_FLOWS)
+    }
+}
+
+impl FlowTable {
+    /// Creates a table that keeps at most `max_flows` flows.
+    pub fn with_max_flows(max_flows: usize) -> Self {
+        Self {
+            flows: HashMap::new(),
+            max_flows,
+        }
+    }
+
+    /// Accounts a raw Ethernet frame to its flow. Non-IP frames are ignored.
+    pub fn record(&mut self, frame: &[u8]) {
+        let Some((key, direction)) = FlowKey::from_frame(frame) else {
+            return;
+        };
+
+        if self.flows.len() >= self.max_flows && !self.flows.contains_key(&key) {
+            self.evict();
+        }
+
+        let stats = self
+            .flows
+            .entry(key)
+            .or_insert_with(|| FlowStats::new(direction));
+
+        match direction {
+            Direction::AToB => stats.a_to_b.add(frame.len()),
+            Direction::BToA => stats.b_to_a.add(frame.len()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.flows.is_empty()
+    }
+
+    /// Evicts the flows that carried no more bytes than the median flow.
+    fn evict(&mut self) {
+        let mut sizes = self
+            .flows
+            .values()
+            .map(FlowStats::total_bytes)
+            .collect::<Vec<u64>>();
+        if sizes.is_empty() {
+            return;
+        }
+
+        let middle = (sizes.len() - 1) / 2;
+        let median = *sizes.select_nth_unstable(middle).1;
+        self.flows.retain(|_, stats| stats.total_bytes() > median);
+    }
+
+    /// Returns the `n` flows that carried the most bytes, largest first.
+    pub fn top(&self, n: usize) -> Vec<(&FlowKey, &FlowStats)> {
+        let mut flows = self.flows.iter().collect::<Vec<_>>();
+        flows.sort_by(|x, y| y.1.total_bytes().cmp(&x.1.total_bytes()));
+        flows.truncate(n);
+        flows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT: [u8; 4] = [192, 168, 1, 10];
+    const SERVER: [u8; 4] = [10, 0, 0, 1];
+
+    /// A UDP datagram with `payload_len` zero bytes, and the given flags and
+    /// fragment offset in the IPv4 header.
+    fn udp_frame(
+        source: ([u8; 4], u16),
+        destination: ([u8; 4], u16),
+        payload_len: usize,
+        fragment: u16,
+    ) -> Vec<u8> {
+        let mut frame = vec![0u8; 12];
+        frame.extend_from_slice(&encapsulation::ETHER_TYPE_IPV4.to_be_bytes());
+        frame.extend_from_slice(&[0x45, 0x00]);
+        frame.extend_from_slice(&(28 + payload_len as u16).to_be_bytes());
+        frame.extend_from_slice(&[0x00, 0x01]);
+        frame.extend_from_slice(&fragment.to_be_bytes());
+        frame.extend_from_slice(&[0x40, IP_PROTOCOL_UDP, 0x00, 0x00]);
+        frame.extend_from_slice(&source.0);
+        frame.extend_from_slice(&destination.0);
+        frame.extend_from_slice(&source.1.to_be_bytes());
+        frame.extend_from_slice(&destination.1.to_be_bytes());
+        frame.extend_from_slice(&(8 + payload_len as u16).to_be_bytes());
+        frame.extend_from_slice(&[0x00, 0x00]);
+        frame.resize(frame.len() + payload_len, 0);
+        frame
+    }
+
+    #[test]
+    fn counts_each_direction_of_a_flow() {
+        let mut table = FlowTable::default();
+        // The server has the lower address, so the client is endpoint `b`
+        table.record(&udp_frame((CLIENT, 50000), (SERVER, 53), 10, 0));
+        table.record(&udp_frame((CLIENT, 50000), (SERVER, 53), 20, 0));
+        table.record(&udp_frame((SERVER, 53), (CLIENT, 50000), 100, 0));
+
+        let flows = table.top(10);
+        assert_eq!(flows.len(), 1);
+
+        let (key, stats) = flows[0];
+        assert_eq!(stats.initiator, Direction::BToA);
+        assert_eq!(
+            stats.upload(),
+            Counter {
+                packets: 2,
+                bytes: 52 + 62,
+            }
+        );
+        assert_eq!(
+            stats.download(),
+            Counter {
+                packets: 1,
+                bytes: 142,
+            }
+        );
+        assert_eq!(stats.total_bytes(), 52 + 62 + 142);
+
+        let (initiator, responder) = stats.endpoints(key);
+        assert_eq!(initiator.address, IpAddr::from(CLIENT));
+        assert_eq!(initiator.port, 50000);
+        assert_eq!(responder.port, 53);
+    }
+
+    #[test]
+    fn skips_non_first_fragments() {
+        // The first fragment has More Fragments set and carries the UDP header
+        let first = udp_frame((CLIENT, 50000), (SERVER, 53), 16, 0x2000);
+        assert!(FlowKey::from_frame(&first).is_some());
+
+        // A later fragment starts with payload bytes, not ports
+        let later = udp_frame((CLIENT, 50000), (SERVER, 53), 16, 0x0003);
+        assert_eq!(FlowKey::from_frame(&later), None);
+
+        let mut table = FlowTable::default();
+        table.record(&later);
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn evicts_smallest_flows_when_full() {
+        let mut table = FlowTable::with_max_flows(4);
+        for port in 1..=4 {
+            let payload_len = usize::from(port) * 100;
+            table.record(&udp_frame((CLIENT, port), (SERVER, 53), payload_len, 0));
+        }
+        assert_eq!(table.flows.len(), 4);
+
+        // A packet of a known flow does not evict anything
+        table.record(&udp_frame((CLIENT, 1), (SERVER, 53), 0, 0));
+        assert_eq!(table.flows.len(), 4);
+
+        table.record(&udp_frame((CLIENT, 5), (SERVER, 53), 0, 0));
+
+        // The client has the higher address, so its ports are those of endpoint `b`
+        let mut ports = table
+            .flows
+            .keys()
+            .map(|key| key.b.port)
+            .collect::<Vec<u16>>();
+        ports.sort_unstable();
+        assert_eq!(ports, [3, 4, 5]);
+    }
+}
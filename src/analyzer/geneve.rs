@@ -0,0 +1,99 @@
+use super::{
+    encapsulation::{self, read_u16, read_u32, ETHER_TYPE_IPV4, ETHER_TYPE_IPV6},
+    flows::IP_PROTOCOL_UDP,
+};
+
+pub const GENEVE_UDP_PORT: u16 = 6081;
+
+/// The protocol type of a Geneve packet carrying a full Ethernet frame.
+pub const ETHER_TYPE_TRANSPARENT_BRIDGING: u16 = 0x6558;
+
+const UDP_HEADER_LEN: usize = 8;
+const GENEVE_HEADER_LEN: usize = 8;
+const GENEVE_OPTION_HEADER_LEN: usize = 4;
+
+/// A type-length-value option of a Geneve header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneveOption<'a> {
+    pub class: u16,
+    pub option_type: u8,
+    pub data: &'a [u8],
+}
+
+/// A Geneve (Generic Network Virtualization Encapsulation) packet, carried over
+/// UDP port 6081.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenevePacket<'a> {
+    pub version: u8,
+    /// Whether this is a control packet rather than a data packet.
+    pub oam: bool,
+    /// Whether the options include one that must be understood by the receiver.
+    pub critical: bool,
+    /// The EtherType of the payload; 0x6558 for an Ethernet frame.
+    pub protocol_type: u16,
+    /// The 24-bit Virtual Network Identifier.
+    pub vni: u32,
+    pub options: Vec<GeneveOption<'a>>,
+    pub payload: &'a [u8],
+}
+
+impl<'a> GenevePacket<'a> {
+    /// Parses the Geneve header of a raw Ethernet frame.
+    ///
+    /// # Returns
+    /// `Some(GenevePacket)` if the frame carries a UDP datagram to port 6081 with a
+    /// complete Geneve header and options, `None` otherwise.
+    pub fn from_frame(frame: &'a [u8]) -> Option<Self> {
+        let (header, datagram) = encapsulation::parse_ip(frame)?;
+        if header.protocol != IP_PROTOCOL_UDP || read_u16(datagram, 2)? != GENEVE_UDP_PORT {
+            return None;
+        }
+
+        Self::from_bytes(datagram.get(UDP_HEADER_LEN..)?)
+    }
+
+    /// Parses a Geneve header and its options from the payload of a UDP datagram.
+    pub fn from_bytes(data: &'a [u8]) -> Option<Self> {
+        let first = *data.first()?;
+        let flags = *data.get(1)?;
+        let options_len = usize::from(first & 0x3f) * 4;
+        let options_end = GENEVE_HEADER_LEN + options_len;
+
+        Some(Self {
+            version: first >> 6,
+            oam: flags & 0x80 != 0,
+            critical: flags & 0x40 != 0,
+            protocol_type: read_u16(data, 2)?,
+            vni: read_u32(data, 4)? >> 8,
+            options: Self::parse_options(data.get(GENEVE_HEADER_LEN..options_end)?)?,
+            payload: &data[options_end..],
+        })
+    }
+
+    fn parse_options(mut data: &'a [u8]) -> Option<Vec<GeneveOption<'a>>> {
+        let mut options = Vec::new();
+
+        while !data.is_empty() {
+            let length = usize::from(*data.get(3)? & 0x1f) * 4;
+            let end = GENEVE_OPTION_HEADER_LEN + length;
+
+            options.push(GeneveOption {
+                class: read_u16(data, 0)?,
+                option_type: *data.get(2)?,
+                data: data.get(GENEVE_OPTION_HEADER_LEN..end)?,
+            });
+            data = &data[end..];
+        }
+
+        Some(options)
+    }
+
+    /// Returns the EtherType of the inner packet if it is IP, for payloads that are
+    /// not a full Ethernet frame.
+    pub fn inner_ip_ether_type(&self) -> Option<u16> {
+        match self.protocol_type {
+            ETHER_TYPE_IPV4 | ETHER_TYPE_IPV6 => Some(self.protocol_type),
+            _ => None,
+        }
+    }
+}
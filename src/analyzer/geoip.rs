@@ -0,0 +1,77 @@
+use super::error::AnalyzerError;
+use maxminddb::{geoip2, Reader};
+use std::{fmt, net::IpAddr, path::Path};
+
+/// A MaxMind GeoLite2 or GeoIP2 Country database, used to annotate public IP
+/// addresses with the country they are located in.
+pub struct GeoIp {
+    reader: Reader<Vec<u8>>,
+}
+
+impl GeoIp {
+    /// Reads a database in the MaxMind DB (.mmdb) format.
+    pub fn open(path: &Path) -> Result<Self, AnalyzerError> {
+        let reader =
+            Reader::open_readfile(path).map_err(AnalyzerError::FailedToOpenGeoIpDatabase)?;
+        Ok(Self { reader })
+    }
+
+    /// Looks up the ISO country code of an address, e.g. `US`.
+    ///
+    /// # Returns
+    /// `Some(String)` with the country code of a public address found in the
+    /// database, `None` for private, loopback and other non-routable addresses, and
+    /// for addresses the database does not know.
+    pub fn country(&self, address: IpAddr) -> Option<String> {
+        if !is_public(address) {
+            return None;
+        }
+
+        let country: geoip2::Country = self.reader.lookup(address).ok()?;
+        country
+            .country
+            .and_then(|country| country.iso_code)
+            .map(str::to_string)
+    }
+}
+
+impl fmt::Debug for GeoIp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GeoIp")
+            .field("database_type", &self.reader.metadata.database_type)
+            .finish()
+    }
+}
+
+/// Returns `true` if an address is routable on the internet, and so may be found
+/// in a GeoIP database.
+fn is_public(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => {
+            let [first, second, ..] = address.octets();
+            // The shared address space of carrier-grade NAT, 100.64.0.0/10
+            let shared = first == 100 && (second & 0xc0) == 64;
+
+            !(address.is_private()
+                || address.is_loopback()
+                || address.is_link_local()
+                || address.is_unspecified()
+                || address.is_broadcast()
+                || address.is_multicast()
+                || address.is_documentation()
+                || shared)
+        }
+        IpAddr::V6(address) => {
+            let first = address.segments()[0];
+            // Unique local addresses, fc00::/7, and link-local addresses, fe80::/10
+            let unique_local = (first & 0xfe00) == 0xfc00;
+            let link_local = (first & 0xffc0) == 0xfe80;
+
+            !(address.is_loopback()
+                || address.is_unspecified()
+                || address.is_multicast()
+                || unique_local
+                || link_local)
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use super::encapsulation::{read_u16, read_u32};
+
+pub const IP_PROTOCOL_GRE: u8 = 47;
+
+const FLAG_CHECKSUM: u16 = 0x8000;
+const FLAG_KEY: u16 = 0x2000;
+const FLAG_SEQUENCE: u16 = 0x1000;
+/// Set in the enhanced GRE header of PPTP when an acknowledgment number follows.
+const FLAG_ACKNOWLEDGMENT: u16 = 0x0080;
+
+/// The flags, version and protocol type of a GRE header.
+const GRE_HEADER_LEN: usize = 4;
+
+/// A GRE header, which tunnels the packet of another protocol over IP.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GreHeader {
+    /// 0 for GRE, 1 for the enhanced GRE of PPTP.
+    pub version: u8,
+    /// The EtherType of the encapsulated packet.
+    pub protocol_type: u16,
+    pub key: Option<u32>,
+    pub sequence: Option<u32>,
+    pub acknowledgment: Option<u32>,
+}
+
+impl GreHeader {
+    /// Parses a GRE header from the payload of an IP packet, along with the
+    /// optional fields announced by its flags.
+    ///
+    /// # Returns
+    /// `Some(GreHeader)` if the header and its optional fields are complete, `None`
+    /// otherwise.
+    pub fn parse(payload: &[u8]) -> Option<Self> {
+        let flags = read_u16(payload, 0)?;
+        let protocol_type = read_u16(payload, 2)?;
+        let version = (flags & 0x07) as u8;
+
+        let mut offset = GRE_HEADER_LEN;
+        let mut optional = |present: bool| -> Option<Option<u32>> {
+            if !present {
+                return Some(None);
+            }
+            let value = read_u32(payload, offset)?;
+            offset += 4;
+            Some(Some(value))
+        };
+
+        // The checksum is followed by a reserved field, and is not shown
+        optional(flags & FLAG_CHECKSUM != 0)?;
+        let key = optional(flags & FLAG_KEY != 0)?;
+        let sequence = optional(flags & FLAG_SEQUENCE != 0)?;
+        let acknowledgment = optional(version == 1 && flags & FLAG_ACKNOWLEDGMENT != 0)?;
+
+        Some(Self {
+            version,
+            protocol_type,
+            key,
+            sequence,
+            acknowledgment,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_header_with_key_and_sequence() {
+        let payload = [
+            0x30, 0x00, 0x08, 0x00, // key and sequence flags, version 0, IPv4
+            0x00, 0x00, 0x04, 0xd2, // key
+            0x00, 0x00, 0x00, 0x2a, // sequence number
+        ];
+
+        assert_eq!(
+            GreHeader::parse(&payload),
+            Some(GreHeader {
+                version: 0,
+                protocol_type: 0x0800,
+                key: Some(1234),
+                sequence: Some(42),
+                acknowledgment: None,
+            })
+        );
+    }
+
+    #[test]
+    fn skips_checksum() {
+        let payload = [
+            0xa0, 0x00, 0x86, 0xdd, // checksum and key flags, version 0, IPv6
+            0x12, 0x34, 0x00, 0x00, // checksum and reserved field
+            0x00, 0x00, 0x00, 0x07, // key
+        ];
+        let header = GreHeader::parse(&payload).unwrap();
+
+        assert_eq!(header.protocol_type, 0x86dd);
+        assert_eq!(header.key, Some(7));
+        assert_eq!(header.sequence, None);
+    }
+
+    #[test]
+    fn parses_pptp_enhanced_header() {
+        let payload = [
+            0x30, 0x81, 0x88, 0x0b, // key, sequence and ack flags, version 1, PPP
+            0x00, 0x10, 0x80, 0x00, // payload length and call ID
+            0x00, 0x00, 0x00, 0x05, // sequence number
+            0x00, 0x00, 0x00, 0x04, // acknowledgment number
+        ];
+
+        assert_eq!(
+            GreHeader::parse(&payload),
+            Some(GreHeader {
+                version: 1,
+                protocol_type: 0x880b,
+                key: Some(0x0010_8000),
+                sequence: Some(5),
+                acknowledgment: Some(4),
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_cut_optional_field() {
+        // The sequence flag is set but the sequence number is missing
+        let payload = [0x10, 0x00, 0x08, 0x00];
+
+        assert_eq!(GreHeader::parse(&payload), None);
+    }
+}
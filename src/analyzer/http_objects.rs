@@ -0,0 +1,362 @@
+use super::reassembly::TcpConversation;
+
+const HEADER_END: &[u8] = b"\r\n\r\n";
+const LINE_END: &[u8] = b"\r\n";
+
+/// A response body transferred over HTTP/1.x.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpObject {
+    /// The URL of the request, or only its path if the request had no Host header.
+    pub url: String,
+    /// The path of the request, without the query.
+    pub path: String,
+    pub status: u16,
+    pub content_type: Option<String>,
+    /// The body as transferred, with chunked transfer-encoding removed. Content
+    /// encodings such as gzip are left as they are.
+    pub body: Vec<u8>,
+}
+
+/// The start line and headers of an HTTP message.
+struct MessageHead<'a> {
+    start_line: &'a str,
+    headers: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> MessageHead<'a> {
+    /// Parses the head of the message at the start of `data`.
+    ///
+    /// # Returns
+    /// The head and its length in bytes, including the blank line that ends it.
+    fn parse(data: &'a [u8]) -> Option<(Self, usize)> {
+        let end = find(data, HEADER_END)?;
+        let head = std::str::from_utf8(&data[..end]).ok()?;
+        let mut lines = head.split("\r\n");
+
+        let start_line = lines.next()?;
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim(), value.trim()))
+            .collect();
+
+        Some((
+            Self {
+                start_line,
+                headers,
+            },
+            end + HEADER_END.len(),
+        ))
+    }
+
+    fn header(&self, name: &str) -> Option<&'a str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| *value)
+    }
+
+    fn is_chunked(&self) -> bool {
+        self.header("Transfer-Encoding").map_or(false, |value| {
+            value.to_ascii_lowercase().contains("chunked")
+        })
+    }
+
+    fn content_length(&self) -> Option<usize> {
+        self.header("Content-Length")?.parse().ok()
+    }
+}
+
+/// A request, as far as needed to name and delimit its response.
+struct Request {
+    method: String,
+    target: String,
+    host: Option<String>,
+}
+
+/// Extracts the response bodies of the HTTP/1.x exchanges in a TCP connection.
+///
+/// The direction whose data starts with `HTTP/` is taken to be the server.
+/// Responses are paired with requests in order, as HTTP/1.1 pipelining requires.
+/// Parsing stops at the first message that is incomplete or not HTTP.
+pub fn extract(conversation: &TcpConversation) -> Vec<HttpObject> {
+    let (requests, responses) = if conversation.b_to_a.starts_with(b"HTTP/") {
+        (&conversation.a_to_b, &conversation.b_to_a)
+    } else if conversation.a_to_b.starts_with(b"HTTP/") {
+        (&conversation.b_to_a, &conversation.a_to_b)
+    } else {
+        return Vec::new();
+    };
+
+    let mut requests = parse_requests(requests).into_iter();
+    let mut request = requests.next();
+    let mut objects = Vec::new();
+    let mut data = responses.as_slice();
+
+    while let Some(current) = &request {
+        let (head, head_len) = match MessageHead::parse(data) {
+            Some(head) => head,
+            None => break,
+        };
+
+        let status = match head.start_line.split_whitespace().nth(1) {
+            Some(status) => status.parse::<u16>().unwrap_or_default(),
+            None => break,
+        };
+
+        // Responses to HEAD and 1xx, 204 and 304 responses never carry a body
+        let bodyless = current.method.eq_ignore_ascii_case("HEAD")
+            || (100..200).contains(&status)
+            || status == 204
+            || status == 304;
+
+        let rest = &data[head_len..];
+        let (body, consumed) = if bodyless {
+            (Vec::new(), 0)
+        } else if head.is_chunked() {
+            match decode_chunked(rest) {
+                Some(decoded) => decoded,
+                None => break,
+            }
+        } else if let Some(length) = head.content_length() {
+            let length = length.min(rest.len());
+            (rest[..length].to_vec(), length)
+        } else {
+            // Without a length the body is delimited by the connection closing
+            (rest.to_vec(), rest.len())
+        };
+
+        let path = current
+            .target
+            .split(['?', '#'])
+            .next()
+            .unwrap_or_default()
+            .to_string();
+
+        if !body.is_empty() {
+            objects.push(HttpObject {
+                url: match &current.host {
+                    Some(host) => format!("http://{}{}", host, current.target),
+                    None => current.target.clone(),
+                },
+                path,
+                status,
+                content_type: head.header("Content-Type").map(str::to_string),
+                body,
+            });
+        }
+
+        data = &rest[consumed..];
+
+        // 1xx responses precede the final response to the same request
+        if !(100..200).contains(&status) {
+            request = requests.next();
+        }
+    }
+
+    objects
+}
+
+/// Parses the requests sent by the client, skipping over request bodies.
+fn parse_requests(mut data: &[u8]) -> Vec<Request> {
+    let mut requests = Vec::new();
+
+    while let Some((head, head_len)) = MessageHead::parse(data) {
+        let mut start_line = head.start_line.split_whitespace();
+        let (method, target) = match (start_line.next(), start_line.next()) {
+            (Some(method), Some(target)) => (method, target),
+            _ => break,
+        };
+
+        requests.push(Request {
+            method: method.to_string(),
+            target: target.to_string(),
+            host: head.header("Host").map(str::to_string),
+        });
+
+        let rest = &data[head_len..];
+        let consumed = if head.is_chunked() {
+            match decode_chunked(rest) {
+                Some((_, consumed)) => consumed,
+                None => break,
+            }
+        } else {
+            head.content_length().unwrap_or(0).min(rest.len())
+        };
+
+        data = &rest[consumed..];
+    }
+
+    requests
+}
+
+/// Decodes a body sent with chunked transfer-encoding.
+///
+/// # Returns
+/// `Some((body, consumed))` with the decoded body and the number of bytes of
+/// `data` it took up, or `None` if the chunks are malformed or incomplete.
+fn decode_chunked(data: &[u8]) -> Option<(Vec<u8>, usize)> {
+    let mut body = Vec::new();
+    let mut position = 0;
+
+    loop {
+        let line_end = position + find(&data[position..], LINE_END)?;
+        let size_line = std::str::from_utf8(&data[position..line_end]).ok()?;
+
+        // Chunk extensions follow the size after a semicolon
+        let size_field = size_line.split(';').next()?.trim();
+        let size = usize::from_str_radix(size_field, 16).ok()?;
+        position = line_end + LINE_END.len();
+
+        if size == 0 {
+            // Skip the trailer section, which ends with a blank line
+            loop {
+                let line_end = position + find(&data[position..], LINE_END)?;
+                let blank = line_end == position;
+                position = line_end + LINE_END.len();
+
+                if blank {
+                    return Some((body, position));
+                }
+            }
+        }
+
+        body.extend_from_slice(data.get(position..position.checked_add(size)?)?);
+        position += size;
+
+        if data.get(position..position + LINE_END.len())? != LINE_END {
+            return None;
+        }
+        position += LINE_END.len();
+    }
+}
+
+/// Builds a file name for an extracted object from its path and content type.
+///
+/// # Arguments
+/// * `index` - The position of the object in the capture, to keep names unique.
+/// * `object` - The extracted object.
+pub fn file_name(index: usize, object: &HttpObject) -> String {
+    let name = object
+        .path
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect::<String>();
+
+    let name = match name.trim_matches('.') {
+        "" => "index".to_string(),
+        name => name.to_string(),
+    };
+
+    if name.contains('.') {
+        return format!("{:04}-{}", index, name);
+    }
+
+    let mime = object
+        .content_type
+        .as_deref()
+        .and_then(|content_type| content_type.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase());
+
+    let extension = match mime.as_deref() {
+        Some("text/html") => "html",
+        Some("text/plain") => "txt",
+        Some("text/css") => "css",
+        Some("text/javascript" | "application/javascript") => "js",
+        Some("application/json") => "json",
+        Some("application/xml" | "text/xml") => "xml",
+        Some("application/pdf") => "pdf",
+        Some("image/png") => "png",
+        Some("image/jpeg") => "jpg",
+        Some("image/gif") => "gif",
+        Some("image/svg+xml") => "svg",
+        _ => "bin",
+    };
+
+    format!("{:04}-{}.{}", index, name, extension)
+}
+
+/// Returns the position of the first occurrence of `needle` in `data`.
+fn find(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::flows::{Endpoint, FlowKey, IP_PROTOCOL_TCP};
+    use std::net::IpAddr;
+
+    /// A connection from 10.0.0.1:50000 to the web server at 10.0.0.2:80.
+    fn conversation(requests: &[u8], responses: &[u8]) -> TcpConversation {
+        let (key, _) = FlowKey::new(
+            IP_PROTOCOL_TCP,
+            Endpoint {
+                address: IpAddr::from([10, 0, 0, 1]),
+                port: 50000,
+            },
+            Endpoint {
+                address: IpAddr::from([10, 0, 0, 2]),
+                port: 80,
+            },
+        );
+
+        TcpConversation {
+            key,
+            a_to_b: requests.to_vec(),
+            b_to_a: responses.to_vec(),
+            complete: true,
+        }
+    }
+
+    #[test]
+    fn extracts_pipelined_content_length_bodies() {
+        let conversation = conversation(
+            b"GET /index.html?lang=en HTTP/1.1\r\nHost: example.com\r\n\r\n\
+              GET /logo HTTP/1.1\r\nHost: example.com\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 5\r\n\r\nhello\
+              HTTP/1.1 200 OK\r\nContent-Type: image/png\r\ncontent-length: 4\r\n\r\n\x89PNG",
+        );
+        let objects = extract(&conversation);
+
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0].url, "http://example.com/index.html?lang=en");
+        assert_eq!(objects[0].path, "/index.html");
+        assert_eq!(objects[0].status, 200);
+        assert_eq!(objects[0].body, b"hello");
+        assert_eq!(file_name(0, &objects[0]), "0000-index.html");
+
+        assert_eq!(objects[1].body, b"\x89PNG");
+        assert_eq!(file_name(1, &objects[1]), "0001-logo.png");
+    }
+
+    #[test]
+    fn extracts_chunked_body() {
+        let conversation = conversation(
+            b"GET /data HTTP/1.1\r\nHost: example.com\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+              5;name=value\r\nhello\r\n6\r\n world\r\n0\r\nExpires: never\r\n\r\n",
+        );
+        let objects = extract(&conversation);
+
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].body, b"hello world");
+        assert_eq!(objects[0].content_type, None);
+        assert_eq!(file_name(0, &objects[0]), "0000-data.bin");
+    }
+
+    #[test]
+    fn rejects_incomplete_chunk() {
+        assert_eq!(decode_chunked(b"5\r\nhel"), None);
+        assert_eq!(
+            decode_chunked(b"3\r\nabc\r\n0\r\n\r\nrest"),
+            Some((b"abc".to_vec(), 13))
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use super::pcap_writer::PcapWriter;
+use log::{info, warn};
+use pcap::{Linktype, PacketHeader};
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{sync_channel, Receiver, SyncSender, TrySendError},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+/// How long a slow client may block its writer thread or the listener before it is
+/// dropped.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The number of packets queued for a client before it counts as stalled and is
+/// dropped.
+const CLIENT_QUEUE_LEN: usize = 1024;
+
+/// Wraps a writer in HTTP/1.1 chunked transfer encoding, one chunk per write.
+struct ChunkedWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> Write for ChunkedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        self.inner
+            .write_all(format!("{:x}\r\n", buf.len()).as_bytes())?;
+        self.inner.write_all(buf)?;
+        self.inner.write_all(b"\r\n")?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+type ClientWriter = PcapWriter<ChunkedWriter<TcpStream>>;
+
+/// The queue of packets to be written to a client by its writer thread.
+type Client = SyncSender<(PacketHeader, Vec<u8>)>;
+
+/// Serves a live capture over HTTP as a chunked pcap byte stream.
+///
+/// Every client connecting to the listening address receives a pcap global header
+/// followed by each packet captured from then on, so the stream can be consumed by
+/// `wireshark -k -i <(curl -s http://host:port)`. Each client is written to on its
+/// own thread, so the capture only queues packets. Clients that disconnect or stall
+/// are dropped without interrupting the capture.
+pub struct PcapHttpServer {
+    clients: Arc<Mutex<Vec<Client>>>,
+}
+
+impl PcapHttpServer {
+    /// Binds to `addr` and starts accepting clients on a background thread.
+    ///
+    /// # Arguments
+    /// * `addr` - The address to listen on, e.g. `0.0.0.0:8080`.
+    /// * `linktype` - The datalink type of the capture being served.
+    /// * `snaplen` - The maximum number of bytes captured per packet.
+    pub fn start(addr: &str, linktype: Linktype, snaplen: u32) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let clients: Arc<Mutex<Vec<Client>>> = Arc::new(Mutex::new(Vec::new()));
+
+        info!("Serving live capture on http://{}", listener.local_addr()?);
+
+        let accepted = Arc::clone(&clients);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let client = stream.and_then(|stream| Self::accept(stream, linktype, snaplen));
+
+                match client {
+                    Ok(writer) => {
+                        let (client, packets) = sync_channel(CLIENT_QUEUE_LEN);
+                        thread::spawn(move || Self::serve(writer, packets));
+
+                        if let Ok(mut clients) = accepted.lock() {
+                            clients.push(client);
+                        }
+                    }
+                    Err(e) => warn!("Failed to accept HTTP client: {}", e),
+                }
+            }
+        });
+
+        Ok(Self { clients })
+    }
+
+    /// Queues a packet for every connected client, dropping the ones that
+    /// disconnected or whose queue is full. Never blocks on a client.
+    pub fn broadcast(&self, header: &PacketHeader, data: &[u8]) {
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.retain(|client| match client.try_send((*header, data.to_vec())) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    warn!("HTTP client stalled, dropping it");
+                    false
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            });
+        }
+    }
+
+    /// Writes the packets queued for a client until it fails or is dropped.
+    fn serve(mut writer: ClientWriter, packets: Receiver<(PacketHeader, Vec<u8>)>) {
+        for (header, data) in packets {
+            if writer.write_packet(&header, &data).is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Consumes the HTTP request and answers with the headers of the pcap stream.
+    fn accept(stream: TcpStream, linktype: Linktype, snaplen: u32) -> io::Result<ClientWriter> {
+        let peer = stream.peer_addr()?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+        // The request itself is not inspected: every path serves the same stream.
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 && line != "\r\n" {
+            line.clear();
+        }
+
+        let mut stream = stream;
+        stream.write_all(
+            b"HTTP/1.1 200 OK\r\n\
+              Content-Type: application/vnd.tcpdump.pcap\r\n\
+              Transfer-Encoding: chunked\r\n\
+              Cache-Control: no-cache\r\n\
+              Connection: close\r\n\r\n",
+        )?;
+
+        info!("HTTP client {} connected", peer);
+        PcapWriter::new(ChunkedWriter { inner: stream }, linktype, snaplen)
+    }
+}
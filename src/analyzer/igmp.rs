@@ -0,0 +1,182 @@
+use super::encapsulation::read_u16;
+use std::net::Ipv4Addr;
+
+pub const IP_PROTOCOL_IGMP: u8 = 2;
+
+pub const IGMP_MEMBERSHIP_QUERY: u8 = 0x11;
+pub const IGMP_V1_MEMBERSHIP_REPORT: u8 = 0x12;
+pub const IGMP_V2_MEMBERSHIP_REPORT: u8 = 0x16;
+pub const IGMP_LEAVE_GROUP: u8 = 0x17;
+pub const IGMP_V3_MEMBERSHIP_REPORT: u8 = 0x22;
+
+/// The type, maximum response time and checksum preceding the group address.
+const IGMP_HEADER_LEN: usize = 4;
+/// The record type, auxiliary data length and source count of a group record.
+const GROUP_RECORD_HEADER_LEN: usize = 4;
+
+/// A group record of an IGMPv3 membership report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupRecord {
+    /// Whether the host includes or excludes the sources, or changes to doing so.
+    pub record_type: u8,
+    pub group: Ipv4Addr,
+    /// The number of source addresses of the record.
+    pub sources: u16,
+}
+
+/// An IGMP message, used by IPv4 hosts to join and leave multicast groups.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IgmpMessage {
+    pub message_type: u8,
+    /// The group being queried, reported or left. IGMPv3 reports carry their
+    /// groups in `records` instead.
+    pub group: Option<Ipv4Addr>,
+    pub records: Vec<GroupRecord>,
+}
+
+impl IgmpMessage {
+    /// Parses an IGMP message from the payload of an IPv4 packet.
+    ///
+    /// # Returns
+    /// `Some(IgmpMessage)` if the message is complete, `None` otherwise. The group
+    /// records of an IGMPv3 report are parsed up to the first one cut short.
+    pub fn parse(payload: &[u8]) -> Option<Self> {
+        let message_type = *payload.first()?;
+
+        if message_type != IGMP_V3_MEMBERSHIP_REPORT {
+            let group = payload.get(IGMP_HEADER_LEN..IGMP_HEADER_LEN + 4)?;
+            return Some(Self {
+                message_type,
+                group: Some(Ipv4Addr::new(group[0], group[1], group[2], group[3])),
+                records: Vec::new(),
+            });
+        }
+
+        let count = read_u16(payload, 6)?;
+        let mut records = Vec::new();
+        let mut offset = 8;
+        for _ in 0..count {
+            let record = match payload.get(offset..offset + GROUP_RECORD_HEADER_LEN + 4) {
+                Some(record) => record,
+                None => break,
+            };
+            let sources = u16::from_be_bytes([record[2], record[3]]);
+
+            records.push(GroupRecord {
+                record_type: record[0],
+                group: Ipv4Addr::new(record[4], record[5], record[6], record[7]),
+                sources,
+            });
+
+            // The sources and auxiliary data, the latter counted in 32-bit words
+            offset +=
+                GROUP_RECORD_HEADER_LEN + 4 + usize::from(sources) * 4 + usize::from(record[1]) * 4;
+        }
+
+        Some(Self {
+            message_type,
+            group: None,
+            records,
+        })
+    }
+}
+
+/// Returns the name of an IGMP message type, e.g. `Leave Group`.
+pub fn message_type_name(message_type: u8) -> Option<&'static str> {
+    let name = match message_type {
+        IGMP_MEMBERSHIP_QUERY => "Membership Query",
+        IGMP_V1_MEMBERSHIP_REPORT => "v1 Membership Report",
+        IGMP_V2_MEMBERSHIP_REPORT => "v2 Membership Report",
+        IGMP_LEAVE_GROUP => "Leave Group",
+        IGMP_V3_MEMBERSHIP_REPORT => "v3 Membership Report",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+/// Returns the name of an IGMPv3 group record type, e.g. `To Exclude`, which a
+/// host sends to join a group.
+pub fn record_type_name(record_type: u8) -> Option<&'static str> {
+    let name = match record_type {
+        1 => "Is Include",
+        2 => "Is Exclude",
+        3 => "To Include",
+        4 => "To Exclude",
+        5 => "Allow",
+        6 => "Block",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_v2_membership_report() {
+        let payload = [
+            0x16, 0x00, 0x09, 0x04, // type, max response time and checksum
+            239, 255, 255, 250, // group address
+        ];
+        let message = IgmpMessage::parse(&payload).unwrap();
+
+        assert_eq!(message.message_type, IGMP_V2_MEMBERSHIP_REPORT);
+        assert_eq!(message.group, Some(Ipv4Addr::new(239, 255, 255, 250)));
+        assert!(message.records.is_empty());
+        assert_eq!(
+            message_type_name(message.message_type),
+            Some("v2 Membership Report")
+        );
+    }
+
+    #[test]
+    fn parses_v3_membership_report() {
+        let payload = [
+            0x22, 0x00, 0xf6, 0x8b, // type, reserved and checksum
+            0x00, 0x00, 0x00, 0x02, // reserved and number of group records
+            0x04, 0x00, 0x00, 0x00, // To Exclude, no auxiliary data or sources
+            224, 0, 0, 251, // group address
+            0x01, 0x01, 0x00, 0x01, // Is Include, one word of auxiliary data, one source
+            232, 1, 1, 1, // group address
+            192, 0, 2, 1, // source address
+            0xde, 0xad, 0xbe, 0xef, // auxiliary data
+        ];
+        let message = IgmpMessage::parse(&payload).unwrap();
+
+        assert_eq!(message.message_type, IGMP_V3_MEMBERSHIP_REPORT);
+        assert_eq!(message.group, None);
+        assert_eq!(
+            message.records,
+            vec![
+                GroupRecord {
+                    record_type: 4,
+                    group: Ipv4Addr::new(224, 0, 0, 251),
+                    sources: 0,
+                },
+                GroupRecord {
+                    record_type: 1,
+                    group: Ipv4Addr::new(232, 1, 1, 1),
+                    sources: 1,
+                },
+            ]
+        );
+        assert_eq!(record_type_name(4), Some("To Exclude"));
+    }
+
+    #[test]
+    fn stops_at_cut_group_record() {
+        let payload = [
+            0x22, 0x00, 0x00, 0x00, // type, reserved and checksum
+            0x00, 0x00, 0x00, 0x02, // reserved and number of group records
+            0x04, 0x00, 0x00, 0x00, // To Exclude, no auxiliary data or sources
+            224, 0, 0, 251, // group address
+            0x04, 0x00, 0x00, 0x00, // the second record is cut short
+        ];
+        let message = IgmpMessage::parse(&payload).unwrap();
+
+        assert_eq!(message.records.len(), 1);
+    }
+}
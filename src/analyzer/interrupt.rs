@@ -0,0 +1,60 @@
+use std::{
+    io,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How often a sleeping or idle capture checks whether it was interrupted.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn handle_sigint(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Replaces the default Ctrl-C behaviour of terminating the process with setting
+/// a flag, so that a capture can stop cleanly and flush its output.
+pub fn install() -> io::Result<()> {
+    let handler = handle_sigint as extern "C" fn(libc::c_int);
+
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe.
+    if unsafe { libc::signal(libc::SIGINT, handler as libc::sighandler_t) } == libc::SIG_ERR {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Returns `true` once Ctrl-C has been pressed.
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Sleeps until `deadline`, waking early on Ctrl-C.
+///
+/// # Returns
+/// `false` if the sleep was interrupted, `true` otherwise.
+pub fn sleep_until(deadline: Instant) -> bool {
+    sleep_until_or(deadline, &AtomicBool::new(false))
+}
+
+/// Sleeps until `deadline`, waking early on Ctrl-C or once `stop` is set.
+///
+/// # Returns
+/// `false` if the sleep was interrupted or stopped, `true` otherwise.
+pub fn sleep_until_or(deadline: Instant, stop: &AtomicBool) -> bool {
+    loop {
+        if interrupted() || stop.load(Ordering::SeqCst) {
+            return false;
+        }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return true;
+        }
+
+        thread::sleep(remaining.min(POLL_INTERVAL));
+    }
+}
@@ -0,0 +1,45 @@
+use super::encapsulation::read_u32;
+
+pub const IP_PROTOCOL_ESP: u8 = 50;
+pub const IP_PROTOCOL_AH: u8 = 51;
+
+/// The cleartext metadata of an IPsec packet. The ESP payload is encrypted, so
+/// only the Security Parameters Index and sequence number are decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IpsecHeader {
+    Esp {
+        spi: u32,
+        sequence: u32,
+    },
+    Ah {
+        next_header: u8,
+        spi: u32,
+        sequence: u32,
+    },
+}
+
+impl IpsecHeader {
+    /// Parses an ESP or AH header from the payload of an IP packet.
+    ///
+    /// # Arguments
+    /// * `protocol` - The IP protocol number of the packet.
+    /// * `payload` - The bytes following the IP header.
+    ///
+    /// # Returns
+    /// `Some(IpsecHeader)` if `protocol` is ESP (50) or AH (51) and the header is
+    /// complete, `None` otherwise.
+    pub fn parse(protocol: u8, payload: &[u8]) -> Option<Self> {
+        match protocol {
+            IP_PROTOCOL_ESP => Some(Self::Esp {
+                spi: read_u32(payload, 0)?,
+                sequence: read_u32(payload, 4)?,
+            }),
+            IP_PROTOCOL_AH => Some(Self::Ah {
+                next_header: *payload.first()?,
+                spi: read_u32(payload, 4)?,
+                sequence: read_u32(payload, 8)?,
+            }),
+            _ => None,
+        }
+    }
+}
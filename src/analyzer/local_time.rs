@@ -0,0 +1,24 @@
+use std::mem::MaybeUninit;
+
+/// Converts a Unix timestamp to the number of minutes since local midnight.
+///
+/// # Arguments
+/// * `unix_secs` - Seconds since the Unix epoch, e.g. the timestamp of a packet.
+///
+/// # Returns
+/// `Some(minute)` in `0..1440`, or `None` if the timestamp cannot be represented
+/// in local time.
+pub fn minute_of_day(unix_secs: libc::time_t) -> Option<u32> {
+    let mut tm = MaybeUninit::<libc::tm>::uninit();
+
+    // SAFETY: both pointers are valid for the duration of the call, and unlike
+    // `localtime`, `localtime_r` does not share a static buffer across threads.
+    let result = unsafe { libc::localtime_r(&unix_secs, tm.as_mut_ptr()) };
+    if result.is_null() {
+        return None;
+    }
+
+    // SAFETY: `localtime_r` initialized `tm` as it returned non-null.
+    let tm = unsafe { tm.assume_init() };
+    u32::try_from(tm.tm_hour * 60 + tm.tm_min).ok()
+}
@@ -0,0 +1,71 @@
+use super::encapsulation::{wrap_in_ethernet, ETHER_TYPE_IPV4, ETHER_TYPE_IPV6};
+use pcap::Linktype;
+
+/// The length of the address family header of DLT_NULL and DLT_LOOP frames.
+const LOOPBACK_HEADER_LEN: usize = 4;
+
+const AF_INET: u32 = 2;
+/// AF_INET6 differs between platforms: Linux, NetBSD/OpenBSD, FreeBSD and macOS.
+const AF_INET6: [u32; 4] = [10, 24, 28, 30];
+
+/// Returns `true` for the datalink types of BSD loopback and tun interfaces,
+/// DLT_NULL and DLT_LOOP, whose frames start with an address family.
+pub fn is_loopback(linktype: Linktype) -> bool {
+    linktype == Linktype::NULL || linktype == Linktype::LOOP
+}
+
+/// A DLT_NULL or DLT_LOOP frame: a 4-byte address family followed by the packet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoopbackFrame<'a> {
+    pub family: u32,
+    pub payload: &'a [u8],
+}
+
+impl<'a> LoopbackFrame<'a> {
+    /// Parses the address family header of a loopback frame.
+    ///
+    /// DLT_LOOP stores the family in network byte order, while DLT_NULL uses the
+    /// byte order of the host that wrote the capture. As families are small, a
+    /// DLT_NULL value with its low 16 bits clear was written by a host of the other
+    /// byte order.
+    ///
+    /// # Arguments
+    /// * `frame` - The raw bytes of the frame.
+    /// * `linktype` - The datalink type of the capture, DLT_NULL or DLT_LOOP.
+    ///
+    /// # Returns
+    /// `Some(LoopbackFrame)` if the header is complete, `None` otherwise.
+    pub fn from_bytes(frame: &'a [u8], linktype: Linktype) -> Option<Self> {
+        let header: [u8; LOOPBACK_HEADER_LEN] =
+            frame.get(..LOOPBACK_HEADER_LEN)?.try_into().ok()?;
+
+        let family = if linktype == Linktype::LOOP {
+            u32::from_be_bytes(header)
+        } else {
+            match u32::from_ne_bytes(header) {
+                family if family & 0xffff == 0 => family.swap_bytes(),
+                family => family,
+            }
+        };
+
+        Some(Self {
+            family,
+            payload: &frame[LOOPBACK_HEADER_LEN..],
+        })
+    }
+
+    /// The EtherType of the payload, or `None` for families other than IPv4 and IPv6.
+    pub fn ether_type(&self) -> Option<u16> {
+        match self.family {
+            AF_INET => Some(ETHER_TYPE_IPV4),
+            family if AF_INET6.contains(&family) => Some(ETHER_TYPE_IPV6),
+            _ => None,
+        }
+    }
+
+    /// Wraps the IP payload in a synthetic Ethernet header, so that it can be
+    /// tracked and decoded like a captured Ethernet frame.
+    pub fn to_ethernet(&self) -> Option<Vec<u8>> {
+        Some(wrap_in_ethernet(self.ether_type()?, self.payload))
+    }
+}
@@ -0,0 +1,50 @@
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+
+/// How often the resident set size is sampled by a `MemoryGuard`.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Returns the resident set size of the current process in bytes.
+///
+/// The value is read from `/proc/self/status`, so `None` is returned on platforms
+/// without procfs.
+pub fn resident_set_size() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+
+    // e.g. "VmRSS:      5120 kB"
+    let kilobytes = line.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// Periodically compares the resident set size against a configured ceiling.
+#[derive(Debug)]
+pub struct MemoryGuard {
+    limit: u64,
+    last_check: Instant,
+}
+
+impl MemoryGuard {
+    pub fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            last_check: Instant::now(),
+        }
+    }
+
+    /// Samples the resident set size if the check interval has elapsed.
+    ///
+    /// # Returns
+    /// `Some(rss)` if the process is using more memory than the limit, `None` if it
+    /// is within the limit or was not sampled on this call.
+    pub fn exceeded(&mut self) -> Option<u64> {
+        if self.last_check.elapsed() < CHECK_INTERVAL {
+            return None;
+        }
+
+        self.last_check = Instant::now();
+        resident_set_size().filter(|rss| *rss > self.limit)
+    }
+}
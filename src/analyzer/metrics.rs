@@ -0,0 +1,171 @@
+use super::{snapshot_server::SnapshotServer, state::CaptureState};
+use log::info;
+use std::{fmt::Write as _, io};
+
+/// The content type of the Prometheus text exposition format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// A minimal HTTP endpoint exporting the counters of a capture as Prometheus
+/// metrics.
+///
+/// `GET /metrics` serves the packet and byte totals, the packets of each protocol
+/// and the packet counts of libpcap, including its drops. Like the dashboard, the
+/// capture loop publishes the metrics at most once per second, so scrapes never
+/// contend with per-packet processing.
+pub struct MetricsServer {
+    server: SnapshotServer,
+}
+
+impl MetricsServer {
+    /// Binds to `addr` and starts serving the metrics on a background thread.
+    pub fn start(addr: &str) -> io::Result<Self> {
+        let metrics = Self::metrics_text(&CaptureState::default());
+        let server = SnapshotServer::start(addr, "metrics", metrics, Self::route)?;
+
+        info!("Serving metrics on http://{}/metrics", server.local_addr());
+
+        Ok(Self { server })
+    }
+
+    /// Publishes the metrics of the capture state if the publish interval has elapsed.
+    pub fn publish(&mut self, state: &CaptureState) {
+        self.server.publish(|| Self::metrics_text(state));
+    }
+
+    fn metrics_text(state: &CaptureState) -> String {
+        let stats = &state.stats;
+        let mut text = String::new();
+
+        counter(
+            &mut text,
+            "wyre_packets_total",
+            "Packets processed.",
+            &[("", stats.packets)],
+        );
+        counter(
+            &mut text,
+            "wyre_bytes_total",
+            "Bytes of the packets processed.",
+            &[("", stats.bytes)],
+        );
+        counter(
+            &mut text,
+            "wyre_network_packets_total",
+            "Packets processed by network protocol.",
+            &[
+                ("protocol=\"ipv4\"", stats.ipv4),
+                ("protocol=\"ipv6\"", stats.ipv6),
+            ],
+        );
+        counter(
+            &mut text,
+            "wyre_transport_packets_total",
+            "Packets processed by transport protocol.",
+            &[
+                ("protocol=\"tcp\"", stats.tcp),
+                ("protocol=\"udp\"", stats.udp),
+                ("protocol=\"icmp\"", stats.icmp),
+                ("protocol=\"other\"", stats.other),
+            ],
+        );
+
+        // libpcap does not count packets for every capture source
+        if let Some(capture_stats) = &state.capture_stats {
+            counter(
+                &mut text,
+                "wyre_pcap_received_total",
+                "Packets received by libpcap.",
+                &[("", u64::from(capture_stats.received))],
+            );
+            counter(
+                &mut text,
+                "wyre_pcap_dropped_total",
+                "Packets dropped by libpcap because its buffer was full.",
+                &[("", u64::from(capture_stats.dropped))],
+            );
+            counter(
+                &mut text,
+                "wyre_pcap_if_dropped_total",
+                "Packets dropped by the network interface or its driver.",
+                &[("", u64::from(capture_stats.if_dropped))],
+            );
+        }
+
+        text
+    }
+
+    fn route(path: &str, metrics: &str) -> Option<(&'static str, String)> {
+        match path {
+            "/metrics" => Some((CONTENT_TYPE, metrics.to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// Appends a counter with its help text and one sample per set of labels.
+fn counter(text: &mut String, name: &str, help: &str, samples: &[(&str, u64)]) {
+    let _ = writeln!(text, "# HELP {} {}", name, help);
+    let _ = writeln!(text, "# TYPE {} counter", name);
+
+    for (labels, value) in samples {
+        if labels.is_empty() {
+            let _ = writeln!(text, "{} {}", name, value);
+        } else {
+            let _ = writeln!(text, "{}{{{}}} {}", name, labels, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::stats::Stats;
+    use pcap::Stat;
+
+    #[test]
+    fn formats_counters_in_exposition_format() {
+        let mut state = CaptureState {
+            stats: Stats {
+                packets: 10,
+                bytes: 1500,
+                ipv4: 8,
+                ipv6: 2,
+                tcp: 6,
+                udp: 3,
+                icmp: 1,
+                ..Stats::default()
+            },
+            ..CaptureState::default()
+        };
+
+        let text = MetricsServer::metrics_text(&state);
+        assert!(text.starts_with(
+            "# HELP wyre_packets_total Packets processed.\n\
+             # TYPE wyre_packets_total counter\n\
+             wyre_packets_total 10\n\
+             # HELP wyre_bytes_total Bytes of the packets processed.\n\
+             # TYPE wyre_bytes_total counter\n\
+             wyre_bytes_total 1500\n"
+        ));
+        assert!(text.contains(
+            "# TYPE wyre_network_packets_total counter\n\
+             wyre_network_packets_total{protocol=\"ipv4\"} 8\n\
+             wyre_network_packets_total{protocol=\"ipv6\"} 2\n"
+        ));
+        assert!(text.contains("wyre_transport_packets_total{protocol=\"other\"} 0\n"));
+        assert!(!text.contains("wyre_pcap_"));
+
+        state.capture_stats = Some(Stat {
+            received: 12,
+            dropped: 2,
+            if_dropped: 0,
+        });
+        let text = MetricsServer::metrics_text(&state);
+        assert!(text.ends_with(
+            "# TYPE wyre_pcap_if_dropped_total counter\n\
+             wyre_pcap_if_dropped_total 0\n"
+        ));
+        assert!(text.contains("\nwyre_pcap_received_total 12\n"));
+        assert!(text.contains("\nwyre_pcap_dropped_total 2\n"));
+    }
+}
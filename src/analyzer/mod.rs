@@ -1,4 +1,47 @@
 pub mod analyzer;
+pub mod arp;
+pub mod capture_files;
+pub mod checksum;
+pub mod dashboard;
 pub mod definitions;
+pub mod dhcp;
+pub mod direction;
+pub mod dns;
+pub mod encapsulation;
 pub mod error;
+pub mod fifo;
+pub mod filter;
+pub mod flows;
+pub mod geneve;
+#[cfg(feature = "geoip")]
+pub mod geoip;
+pub mod gre;
+pub mod http_objects;
+pub mod http_stream;
+pub mod igmp;
+pub mod interrupt;
+pub mod ipsec;
+pub mod local_time;
+pub mod loopback;
+pub mod memory;
+pub mod metrics;
+pub mod oui;
+pub mod parse_pool;
 pub mod pcap_interface;
+pub mod pcap_writer;
+#[cfg(feature = "pcapng")]
+pub mod pcapng;
+pub mod pppoe;
+pub mod rate;
+pub mod reassembly;
+pub mod ring_file;
+pub mod rotation;
+pub mod scans;
+pub mod sink;
+pub mod snapshot_server;
+pub mod state;
+pub mod stats;
+pub mod talkers;
+pub mod tls;
+pub mod vlan;
+pub mod wifi;
@@ -0,0 +1,117 @@
+/// Vendors of common network equipment, virtual machines and devices, keyed by the
+/// Organizationally Unique Identifier in the first three bytes of a MAC address.
+///
+/// This is a curated subset of the IEEE registry, plus the locally administered
+/// prefix QEMU assigns by default, sorted by OUI for binary search.
+const OUI_VENDORS: &[([u8; 3], &str)] = &[
+    ([0x00, 0x00, 0x0c], "Cisco"),
+    ([0x00, 0x01, 0x42], "Cisco"),
+    ([0x00, 0x01, 0x43], "Cisco"),
+    ([0x00, 0x02, 0xb3], "Intel"),
+    ([0x00, 0x03, 0x93], "Apple"),
+    ([0x00, 0x03, 0xff], "Microsoft"),
+    ([0x00, 0x04, 0x96], "Extreme Networks"),
+    ([0x00, 0x04, 0xf2], "Polycom"),
+    ([0x00, 0x05, 0x02], "Apple"),
+    ([0x00, 0x05, 0x69], "VMware"),
+    ([0x00, 0x05, 0x85], "Juniper Networks"),
+    ([0x00, 0x06, 0x5b], "Dell"),
+    ([0x00, 0x08, 0x9b], "QNAP"),
+    ([0x00, 0x09, 0x0f], "Fortinet"),
+    ([0x00, 0x09, 0x5b], "Netgear"),
+    ([0x00, 0x0a, 0x95], "Apple"),
+    ([0x00, 0x0b, 0x82], "Grandstream"),
+    ([0x00, 0x0c, 0x29], "VMware"),
+    ([0x00, 0x0c, 0x42], "MikroTik"),
+    ([0x00, 0x0d, 0x3a], "Microsoft"),
+    ([0x00, 0x0d, 0x93], "Apple"),
+    ([0x00, 0x0d, 0xb9], "PC Engines"),
+    ([0x00, 0x10, 0x18], "Broadcom"),
+    ([0x00, 0x11, 0x24], "Apple"),
+    ([0x00, 0x11, 0x32], "Synology"),
+    ([0x00, 0x14, 0x22], "Dell"),
+    ([0x00, 0x14, 0x51], "Apple"),
+    ([0x00, 0x14, 0x6c], "Netgear"),
+    ([0x00, 0x14, 0xbf], "Cisco-Linksys"),
+    ([0x00, 0x15, 0x17], "Intel"),
+    ([0x00, 0x15, 0x5d], "Microsoft"),
+    ([0x00, 0x15, 0x6d], "Ubiquiti"),
+    ([0x00, 0x15, 0xf2], "ASUSTek"),
+    ([0x00, 0x16, 0x3e], "Xensource"),
+    ([0x00, 0x16, 0xcb], "Apple"),
+    ([0x00, 0x17, 0x88], "Philips Lighting"),
+    ([0x00, 0x17, 0xa4], "Hewlett Packard"),
+    ([0x00, 0x17, 0xf2], "Apple"),
+    ([0x00, 0x18, 0x0a], "Cisco Meraki"),
+    ([0x00, 0x19, 0xe3], "Apple"),
+    ([0x00, 0x1a, 0x11], "Google"),
+    ([0x00, 0x1a, 0x4b], "Hewlett Packard"),
+    ([0x00, 0x1a, 0xa0], "Dell"),
+    ([0x00, 0x1b, 0x11], "D-Link"),
+    ([0x00, 0x1b, 0x17], "Palo Alto Networks"),
+    ([0x00, 0x1b, 0x21], "Intel"),
+    ([0x00, 0x1b, 0x63], "Apple"),
+    ([0x00, 0x1c, 0x14], "VMware"),
+    ([0x00, 0x1c, 0x42], "Parallels"),
+    ([0x00, 0x1c, 0x73], "Arista Networks"),
+    ([0x00, 0x1d, 0x0f], "TP-Link"),
+    ([0x00, 0x1d, 0x60], "ASUSTek"),
+    ([0x00, 0x1d, 0x7e], "Cisco-Linksys"),
+    ([0x00, 0x1e, 0x0b], "Hewlett Packard"),
+    ([0x00, 0x1e, 0x67], "Intel"),
+    ([0x00, 0x1e, 0xc2], "Apple"),
+    ([0x00, 0x1f, 0x29], "Hewlett Packard"),
+    ([0x00, 0x1f, 0x33], "Netgear"),
+    ([0x00, 0x1f, 0xf3], "Apple"),
+    ([0x00, 0x21, 0x9b], "Dell"),
+    ([0x00, 0x23, 0xdf], "Apple"),
+    ([0x00, 0x24, 0x01], "D-Link"),
+    ([0x00, 0x25, 0x00], "Apple"),
+    ([0x00, 0x25, 0x90], "Super Micro"),
+    ([0x00, 0x26, 0x18], "ASUSTek"),
+    ([0x00, 0x26, 0xb9], "Dell"),
+    ([0x00, 0x26, 0xbb], "Apple"),
+    ([0x00, 0x27, 0x22], "Ubiquiti"),
+    ([0x00, 0x30, 0x48], "Super Micro"),
+    ([0x00, 0x50, 0x56], "VMware"),
+    ([0x00, 0x50, 0xf2], "Microsoft"),
+    ([0x00, 0xa0, 0xc9], "Intel"),
+    ([0x00, 0xe0, 0x18], "ASUSTek"),
+    ([0x00, 0xe0, 0x4c], "Realtek"),
+    ([0x08, 0x00, 0x27], "PCS Systemtechnik (VirtualBox)"),
+    ([0x0c, 0xc4, 0x7a], "Super Micro"),
+    ([0x18, 0xb4, 0x30], "Nest Labs"),
+    ([0x24, 0x5e, 0xbe], "QNAP"),
+    ([0x24, 0xa4, 0x3c], "Ubiquiti"),
+    ([0x28, 0xcd, 0xc1], "Raspberry Pi Trading"),
+    ([0x28, 0xcf, 0xe9], "Apple"),
+    ([0x3c, 0x5a, 0xb4], "Google"),
+    ([0x3c, 0xd9, 0x2b], "Hewlett Packard"),
+    ([0x3c, 0xfd, 0xfe], "Intel"),
+    ([0x44, 0x65, 0x0d], "Amazon"),
+    ([0x4c, 0x5e, 0x0c], "MikroTik"),
+    ([0x50, 0xc7, 0xbf], "TP-Link"),
+    ([0x52, 0x54, 0x00], "QEMU/KVM"),
+    ([0xac, 0x1f, 0x6b], "Super Micro"),
+    ([0xb8, 0x27, 0xeb], "Raspberry Pi Foundation"),
+    ([0xdc, 0xa6, 0x32], "Raspberry Pi Trading"),
+    ([0xe4, 0x5f, 0x01], "Raspberry Pi Trading"),
+    ([0xf0, 0x18, 0x98], "Apple"),
+    ([0xf4, 0xf5, 0xd8], "Google"),
+    ([0xf8, 0xbc, 0x12], "Dell"),
+    ([0xfc, 0xa6, 0x67], "Amazon"),
+    ([0xfc, 0xec, 0xda], "Ubiquiti"),
+];
+
+/// Looks up the vendor of a MAC address from its OUI.
+///
+/// # Returns
+/// `Some(vendor)` if the OUI is in the embedded table, `None` otherwise.
+pub fn oui_vendor(mac: &[u8; 6]) -> Option<&'static str> {
+    let oui = [mac[0], mac[1], mac[2]];
+
+    OUI_VENDORS
+        .binary_search_by_key(&oui, |(prefix, _)| *prefix)
+        .ok()
+        .map(|index| OUI_VENDORS[index].1)
+}
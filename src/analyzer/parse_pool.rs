@@ -0,0 +1,149 @@
+use pcap::PacketHeader;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        mpsc::{channel, sync_channel, Receiver, SyncSender},
+        Arc,
+    },
+    thread,
+};
+
+/// The number of packets queued per worker before `submit` blocks, so that a
+/// capture faster than the workers cannot grow the queues without bound.
+const QUEUE_LEN_PER_WORKER: usize = 1024;
+
+type Job = (u64, PacketHeader, Vec<u8>);
+
+/// A pool of threads that parse packets in parallel.
+///
+/// Packets are distributed round-robin over the workers, and the results are
+/// returned in the order the packets were submitted, so the output of a parallel
+/// capture matches that of a serial one.
+pub struct ParsePool<T> {
+    workers: Vec<SyncSender<Job>>,
+    results: Receiver<(u64, T)>,
+    /// The sequence number of the next packet submitted.
+    submitted: u64,
+    /// The sequence number of the next result returned.
+    returned: u64,
+    /// Results that arrived ahead of an earlier packet still being parsed.
+    pending: BTreeMap<u64, T>,
+}
+
+impl<T: Send + 'static> ParsePool<T> {
+    /// Starts `threads` workers, each running `parse` on the packets it receives.
+    pub fn new<F>(threads: usize, parse: F) -> Self
+    where
+        F: Fn(&PacketHeader, &[u8]) -> T + Send + Sync + 'static,
+    {
+        let parse = Arc::new(parse);
+        let (send_result, results) = channel();
+
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                let (send_job, jobs) = sync_channel::<Job>(QUEUE_LEN_PER_WORKER);
+                let parse = Arc::clone(&parse);
+                let send_result = send_result.clone();
+
+                thread::spawn(move || {
+                    for (sequence, header, data) in jobs {
+                        if send_result.send((sequence, parse(&header, &data))).is_err() {
+                            break;
+                        }
+                    }
+                });
+
+                send_job
+            })
+            .collect();
+
+        Self {
+            workers,
+            results,
+            submitted: 0,
+            returned: 0,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Queues a packet for parsing, blocking while the worker's queue is full.
+    pub fn submit(&mut self, header: PacketHeader, data: Vec<u8>) {
+        let worker = (self.submitted % self.workers.len() as u64) as usize;
+
+        // A worker only stops if its results can no longer be received
+        let _ = self.workers[worker].send((self.submitted, header, data));
+        self.submitted += 1;
+    }
+
+    /// Returns the results that are ready, in submission order, without blocking.
+    pub fn ready(&mut self) -> Vec<T> {
+        while let Ok((sequence, result)) = self.results.try_recv() {
+            self.pending.insert(sequence, result);
+        }
+
+        self.take_in_order()
+    }
+
+    /// Waits for every submitted packet and returns the remaining results in order.
+    pub fn finish(&mut self) -> Vec<T> {
+        while self.returned + (self.pending.len() as u64) < self.submitted {
+            match self.results.recv() {
+                Ok((sequence, result)) => {
+                    self.pending.insert(sequence, result);
+                }
+                Err(_) => break,
+            }
+        }
+
+        self.take_in_order()
+    }
+
+    fn take_in_order(&mut self) -> Vec<T> {
+        let mut ready = Vec::new();
+
+        while let Some(result) = self.pending.remove(&self.returned) {
+            ready.push(result);
+            self.returned += 1;
+        }
+
+        ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const PACKETS: u32 = 32;
+
+    /// A header whose captured length carries the sequence number of a packet.
+    fn packet_header(sequence: u32) -> PacketHeader {
+        PacketHeader {
+            ts: libc::timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            },
+            caplen: sequence,
+            len: sequence,
+        }
+    }
+
+    #[test]
+    fn returns_results_in_submission_order() {
+        // Earlier packets take longer, so later ones finish first on other workers
+        let mut pool = ParsePool::new(4, |header: &PacketHeader, _: &[u8]| {
+            thread::sleep(Duration::from_millis(u64::from(PACKETS - header.caplen)));
+            header.caplen
+        });
+
+        let mut results = Vec::new();
+        for sequence in 0..PACKETS {
+            pool.submit(packet_header(sequence), Vec::new());
+            results.extend(pool.ready());
+        }
+        results.extend(pool.finish());
+
+        assert_eq!(results, (0..PACKETS).collect::<Vec<u32>>());
+    }
+}
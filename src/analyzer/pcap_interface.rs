@@ -1,7 +1,39 @@
-use super::{definitions::ReadPacketResult, error::AnalyzerError};
-use pcap::{Activated, Active, Capture, Device, Error as PcapError};
+use super::{
+    definitions::{CaptureOptions, ReadPacketResult},
+    error::AnalyzerError,
+    interrupt,
+};
+use log::{info, warn};
+use pcap::{
+    Activated, Active, Capture, Device, Error as PcapError, Inactive, PacketHeader, Precision, Stat,
+};
 
-use std::sync::mpsc::Sender;
+use std::{
+    env,
+    ffi::CStr,
+    io,
+    os::raw::c_char,
+    os::unix::io::{AsRawFd, RawFd},
+    sync::{atomic::AtomicBool, mpsc::Sender},
+    time::{Duration, Instant},
+};
+
+extern "C" {
+    // Provided by the libpcap the `pcap` crate links against
+    fn pcap_lib_version() -> *const c_char;
+}
+
+/// How often the reader thread sends the packet counts of libpcap.
+const STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a failed capture waits before it first tries to reopen its interface.
+/// The delay doubles after each failed attempt, up to `RECONNECT_MAX_DELAY`.
+const RECONNECT_MIN_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// The number of packets buffered by `packet_stream` until they are polled.
+#[cfg(feature = "async")]
+const PACKET_STREAM_BUFFER: usize = 1024;
 
 pub struct PcapInterface;
 
@@ -16,6 +48,42 @@ impl PcapInterface {
         Device::list()
     }
 
+    /// Picks the interface best suited to capture on when none is given.
+    ///
+    /// `Device::lookup` may return an interface that is down or virtual, so the
+    /// first interface that is up, running, not a loopback and has an IPv4 address
+    /// is preferred. If there is none, the lookup of libpcap is used instead.
+    ///
+    /// # Returns
+    /// * `Ok(Some(Device))` - The interface to capture on.
+    /// * `Ok(None)` - No interface was found.
+    /// * `Err(PcapError)` - The interfaces could not be listed.
+    pub fn best_interface() -> Result<Option<Device>, PcapError> {
+        let best = Self::devices()?.into_iter().find(|device| {
+            device.flags.is_up()
+                && device.flags.is_running()
+                && !device.flags.is_loopback()
+                && device
+                    .addresses
+                    .iter()
+                    .any(|address| address.addr.is_ipv4())
+        });
+
+        match best {
+            Some(device) => Ok(Some(device)),
+            None => Self::default_interface(),
+        }
+    }
+
+    /// Returns the version string of the libpcap the binary is linked against,
+    /// e.g. `libpcap version 1.10.4 (with TPACKET_V3)`.
+    pub fn lib_version() -> String {
+        // SAFETY: libpcap returns a pointer to a static, NUL-terminated string.
+        unsafe { CStr::from_ptr(pcap_lib_version()) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
     /// Finds a network device by its name.
     ///
     /// This function searches through the list of available network interfaces
@@ -43,11 +111,14 @@ impl PcapInterface {
     /// Creates a packet capture handle for the given network device.
     ///
     /// This function attempts to create and initialize a packet capture handle
-    /// for the provided network device. It configures the device in non-promiscuous mode
-    /// and sets up the handle for capturing packets.
+    /// for the provided network device. It configures the device in promiscuous mode
+    /// if requested and sets up the handle for capturing packets. If a BPF filter is
+    /// given, it is compiled and applied so that only matching packets are captured.
     ///
     /// # Arguments
     /// * `device` - A `Device` object representing the network interface for packet capture.
+    /// * `options` - The read timeout, promiscuous mode, snaplen, buffer size,
+    ///   immediate mode and filter of the handle.
     ///
     /// # Returns
     /// * `Ok(Capture<Active>)` if the capture handle is successfully created and opened.
@@ -56,21 +127,195 @@ impl PcapInterface {
     /// # Errors
     /// * `InterfaceError::FailedToCreateCaptureHandle` if the capture handle cannot be created.
     /// * `InterfaceError::FailedToOpenCaptureHandle` if the capture handle cannot be opened.
-    pub fn capture_handle(device: Device) -> Result<Capture<Active>, AnalyzerError> {
-        let capture_handle = Capture::from_device(device)
-            .map_err(AnalyzerError::FailedToCreateCaptureHandle)?
-            .promisc(false)
+    /// * `InterfaceError::CapturePermissionDenied` if capturing requires privileges the
+    ///   process lacks.
+    /// * `InterfaceError::InvalidSnaplen` if the snaplen is not positive.
+    /// * `InterfaceError::InvalidBufferSize` if the buffer size is not positive.
+    /// * `InterfaceError::FailedToSetDatalink` if the datalink type is not supported.
+    /// * `InterfaceError::InvalidFilter` if the filter expression cannot be compiled.
+    pub fn capture_handle(
+        device: Device,
+        options: &CaptureOptions,
+    ) -> Result<Capture<Active>, AnalyzerError> {
+        let mut capture_handle = Self::inactive_handle(device, options)?
             .open()
-            .map_err(AnalyzerError::FailedToOpenCaptureHandle)?;
+            .map_err(Self::open_error)?;
+
+        Self::apply_datalink(&mut capture_handle, options)?;
+        Self::apply_filter(&mut capture_handle, options)?;
 
         Ok(capture_handle)
     }
 
+    /// Creates a non-blocking packet capture handle for the given network device.
+    ///
+    /// The handle is opened with the read timeout from `options` and then switched
+    /// to non-blocking mode, so `next_packet` returns immediately instead of waiting
+    /// for traffic. This makes the handle suitable for embedding into an event loop,
+    /// together with [`PcapInterface::raw_fd`] for registering it with an external
+    /// poller such as epoll or kqueue.
+    ///
+    /// # Arguments
+    /// * `device` - A `Device` object representing the network interface for packet capture.
+    /// * `options` - Options applied to the handle before it is opened.
+    ///
+    /// # Returns
+    /// * `Ok(Capture<Active>)` if the capture handle is successfully created, opened
+    ///   and set to non-blocking mode.
+    /// * `Err(AnalyzerError)` if any of these steps fail.
+    ///
+    /// # Errors
+    /// * `AnalyzerError::FailedToCreateCaptureHandle` if the capture handle cannot be created.
+    /// * `AnalyzerError::FailedToOpenCaptureHandle` if the capture handle cannot be opened.
+    /// * `AnalyzerError::CapturePermissionDenied` if capturing requires privileges the
+    ///   process lacks.
+    /// * `AnalyzerError::FailedToSetNonBlocking` if non-blocking mode cannot be enabled.
+    /// * `AnalyzerError::InvalidSnaplen` if the snaplen is not positive.
+    /// * `AnalyzerError::InvalidBufferSize` if the buffer size is not positive.
+    /// * `AnalyzerError::FailedToSetDatalink` if the datalink type is not supported.
+    /// * `AnalyzerError::InvalidFilter` if the filter expression cannot be compiled.
+    pub fn capture_handle_nonblocking(
+        device: Device,
+        options: &CaptureOptions,
+    ) -> Result<Capture<Active>, AnalyzerError> {
+        let mut capture_handle = Self::inactive_handle(device, options)?
+            .open()
+            .map_err(Self::open_error)?
+            .setnonblock()
+            .map_err(AnalyzerError::FailedToSetNonBlocking)?;
+
+        Self::apply_datalink(&mut capture_handle, options)?;
+        Self::apply_filter(&mut capture_handle, options)?;
+
+        Ok(capture_handle)
+    }
+
+    /// Creates a capture handle with the read timeout, promiscuous mode, snaplen,
+    /// buffer size, immediate mode and timestamp precision of `options`, ready to
+    /// be opened.
+    fn inactive_handle(
+        device: Device,
+        options: &CaptureOptions,
+    ) -> Result<Capture<Inactive>, AnalyzerError> {
+        let mut capture_handle = Capture::from_device(device)
+            .map_err(AnalyzerError::FailedToCreateCaptureHandle)?
+            .timeout(options.timeout_ms)
+            .promisc(options.promisc)
+            .immediate_mode(options.immediate);
+
+        if options.nanosecond_timestamps {
+            capture_handle = capture_handle.precision(Precision::Nano);
+        }
+
+        if let Some(snaplen) = options.snaplen {
+            if snaplen <= 0 {
+                return Err(AnalyzerError::InvalidSnaplen(snaplen));
+            }
+            capture_handle = capture_handle.snaplen(snaplen);
+        }
+
+        if let Some(buffer_size) = options.buffer_size {
+            if buffer_size <= 0 {
+                return Err(AnalyzerError::InvalidBufferSize(buffer_size));
+            }
+            capture_handle = capture_handle.buffer_size(buffer_size);
+        }
+
+        Ok(capture_handle)
+    }
+
+    /// Switches an open handle to the datalink type of `options`, if any. This is
+    /// done before the filter is compiled, as filters depend on the datalink type.
+    fn apply_datalink(
+        capture_handle: &mut Capture<Active>,
+        options: &CaptureOptions,
+    ) -> Result<(), AnalyzerError> {
+        if let Some(datalink) = options.datalink {
+            capture_handle.set_datalink(datalink).map_err(|err| {
+                let name = datalink
+                    .get_name()
+                    .unwrap_or_else(|_| datalink.0.to_string());
+                AnalyzerError::FailedToSetDatalink(name, err)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Compiles and applies the BPF filter of `options`, if any, to an open handle.
+    fn apply_filter(
+        capture_handle: &mut Capture<Active>,
+        options: &CaptureOptions,
+    ) -> Result<(), AnalyzerError> {
+        if let Some(filter) = &options.filter {
+            capture_handle
+                .filter(filter, true)
+                .map_err(|err| AnalyzerError::InvalidFilter(filter.clone(), err))?;
+        }
+
+        Ok(())
+    }
+
+    /// Converts the error of opening a capture handle into an `AnalyzerError`. A
+    /// lack of privileges, the most common reason a first capture fails, gets an
+    /// error explaining how to grant them to this executable.
+    fn open_error(err: PcapError) -> AnalyzerError {
+        if !Self::is_permission_denied(&err) {
+            return AnalyzerError::FailedToOpenCaptureHandle(err);
+        }
+
+        let executable = env::current_exe()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|_| "<path to wyre>".to_string());
+        AnalyzerError::CapturePermissionDenied(executable, err)
+    }
+
+    /// Returns `true` if a capture handle failed to open because the process may not
+    /// capture, i.e. it is neither root nor has CAP_NET_RAW. libpcap only reports
+    /// this in the text of its error.
+    fn is_permission_denied(err: &PcapError) -> bool {
+        match err {
+            PcapError::IoError(kind) => *kind == io::ErrorKind::PermissionDenied,
+            PcapError::PcapError(message) => {
+                let message = message.to_ascii_lowercase();
+                message.contains("permission") || message.contains("operation not permitted")
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the file descriptor of an activated capture handle, for use with
+    /// external pollers. The descriptor becomes readable when packets are available.
+    pub fn raw_fd<T: Activated>(capture_handle: &Capture<T>) -> RawFd {
+        capture_handle.as_raw_fd()
+    }
+
+    /// Returns `true` if a `next_packet` error on a non-blocking handle only means
+    /// that no packet is currently available, rather than a capture failure.
+    pub fn is_would_block(err: &PcapError) -> bool {
+        matches!(err, PcapError::TimeoutExpired)
+    }
+
+    /// Reads the packets of a capture handle as an iterator, for embedding the
+    /// capture in another program.
+    ///
+    /// # Arguments
+    /// * `capture_handle` - A blocking capture handle, either live or of a capture file.
+    ///
+    /// # Returns
+    /// An iterator that yields each packet's header and data, skips read timeouts on
+    /// a quiet interface, and ends at the end of a capture file. Unlike
+    /// `read_packets`, it does not stop on Ctrl-C.
+    pub fn packets<T: Activated>(capture_handle: Capture<T>) -> Packets<T> {
+        Packets { capture_handle }
+    }
+
     /// Continuously reads packets from the given capture handle and sends the results.
     ///
     /// This function takes a mutable capture handle and a sender channel. It enters
-    /// a loop where it reads packets using the capture handle. Each packet, or an error
-    /// if one occurs, is sent to the receiver associated with the provided sender channel.
+    /// a loop where it reads packets using the capture handle. Each packet, or the
+    /// error that ends the capture, is sent to the receiver associated with the
+    /// provided sender channel.
     ///
     /// # Arguments
     /// * `capture_handle`: A mutable capture handle of type `T` where `T` is Activated.
@@ -78,24 +323,214 @@ impl PcapInterface {
     /// * `sender`: A channel sender for sending the results of packet reading.
     ///
     /// # Behavior
-    /// The function keeps reading packets in a loop until an error occurs.
-    /// For each packet read:
+    /// The function keeps reading packets in a loop until a capture error occurs,
+    /// the receiver is dropped, or Ctrl-C is pressed after the interrupt handler was
+    /// installed. For each read:
     /// - If successful, sends `ReadPacketResult::Success` containing the packet's header
     ///   and data.
-    /// - If an error occurs during sending, sends `ReadPacketResult::Error` and exits the loop.
+    /// - If the read timed out on a quiet interface, keeps reading.
+    /// - If the capture failed, sends `ReadPacketResult::Error` and exits the loop.
+    ///
+    /// The packet counts of libpcap, including dropped packets, are sent as
+    /// `ReadPacketResult::Stats` every few seconds and once the loop exits.
     pub fn read_packets<T: Activated>(
-        mut capture_handle: Capture<T>,
+        capture_handle: Capture<T>,
         sender: Sender<ReadPacketResult>,
     ) {
-        while let Ok(packet) = capture_handle.next_packet() {
-            let send_result = sender.send(ReadPacketResult::Success((
-                *packet.header,
-                packet.data.to_vec(),
-            )));
-
-            if let Err(e) = send_result {
-                let _ = sender.send(ReadPacketResult::Error(e.to_string()));
-                break;
+        Self::read_packets_with(capture_handle, |result| sender.send(result).is_ok());
+    }
+
+    /// Reads the packets of a capture handle on a blocking thread of the tokio
+    /// runtime, for use in async applications. Must be called within the runtime.
+    ///
+    /// # Arguments
+    /// * `capture_handle` - A blocking capture handle, either live or of a capture file.
+    ///
+    /// # Returns
+    /// A stream of the results that `read_packets` sends over its channel, which
+    /// ends when the capture does. Dropping the stream stops the capture at the next
+    /// packet.
+    #[cfg(feature = "async")]
+    pub fn packet_stream<T: Activated + Send + 'static>(
+        capture_handle: Capture<T>,
+    ) -> impl futures::Stream<Item = ReadPacketResult> {
+        let (sender, receiver) = tokio::sync::mpsc::channel(PACKET_STREAM_BUFFER);
+        tokio::task::spawn_blocking(move || {
+            Self::read_packets_with(capture_handle, |result| {
+                sender.blocking_send(result).is_ok()
+            })
+        });
+
+        futures::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|result| (result, receiver))
+        })
+    }
+
+    /// The read loop of `read_packets`, handing each result to `send`, which
+    /// returns `false` once the results are no longer received.
+    pub fn read_packets_with<T: Activated>(
+        mut capture_handle: Capture<T>,
+        mut send: impl FnMut(ReadPacketResult) -> bool,
+    ) {
+        let mut last_stats = Instant::now();
+        while !interrupt::interrupted() {
+            // Also checked on an idle interface, where no packets are sent
+            if last_stats.elapsed() >= STATS_INTERVAL {
+                last_stats = Instant::now();
+                if !Self::send_stats(&mut capture_handle, &mut send) {
+                    break;
+                }
+            }
+
+            match capture_handle.next_packet() {
+                Ok(packet) => {
+                    let sent = send(ReadPacketResult::Success((
+                        *packet.header,
+                        packet.data.to_vec(),
+                    )));
+
+                    // The receiver is only dropped once it stopped processing packets
+                    if !sent {
+                        break;
+                    }
+                }
+                Err(err) if Self::is_would_block(&err) => continue,
+                // The end of a capture file is not a failure
+                Err(PcapError::NoMorePackets) => break,
+                Err(err) => {
+                    send(ReadPacketResult::Error(err.to_string()));
+                    break;
+                }
+            }
+        }
+
+        Self::send_stats(&mut capture_handle, &mut send);
+    }
+
+    /// Reads the packets of a live capture on an interface like `read_packets_with`.
+    ///
+    /// If `options.reconnect` is set, a capture error, such as a USB adapter or VPN
+    /// tun interface going away, does not end the capture. Instead the interface is
+    /// looked up and reopened with `options`, waiting longer after each failed
+    /// attempt, and reading resumes once it is back. The packet counts of libpcap
+    /// start again from zero with the new handle.
+    ///
+    /// # Arguments
+    /// * `capture_handle` - The capture handle opened on `interface`.
+    /// * `interface` - The name of the interface to reopen.
+    /// * `options` - The options the handle was opened with.
+    /// * `stop` - Set once the capture stopped, which also ends the wait for the
+    ///   interface to come back.
+    /// * `send` - Called with each result, returning `false` once the results are no
+    ///   longer received.
+    pub fn read_interface_packets(
+        mut capture_handle: Capture<Active>,
+        interface: &str,
+        options: &CaptureOptions,
+        stop: &AtomicBool,
+        mut send: impl FnMut(ReadPacketResult) -> bool,
+    ) {
+        loop {
+            let mut failure = None;
+            Self::read_packets_with(capture_handle, |result| match result {
+                ReadPacketResult::Error(err) if options.reconnect => {
+                    failure = Some(err);
+                    false
+                }
+                result => send(result),
+            });
+
+            let err = match failure {
+                Some(err) => err,
+                None => return,
+            };
+            warn!("{}: Capture failed: {:?}, reconnecting", interface, err);
+
+            capture_handle = match Self::reconnect(interface, options, stop) {
+                Some(capture_handle) => capture_handle,
+                None => return,
+            };
+            info!("{}: Reconnected, resuming capture", interface);
+        }
+    }
+
+    /// Reopens a capture on an interface until it succeeds, backing off between
+    /// attempts.
+    ///
+    /// # Returns
+    /// The new capture handle, or `None` if Ctrl-C was pressed or the capture
+    /// stopped while waiting.
+    fn reconnect(
+        interface: &str,
+        options: &CaptureOptions,
+        stop: &AtomicBool,
+    ) -> Option<Capture<Active>> {
+        let mut delay = RECONNECT_MIN_DELAY;
+        for attempt in 1.. {
+            if !interrupt::sleep_until_or(Instant::now() + delay, stop) {
+                return None;
+            }
+
+            info!("{}: Reconnect attempt {}", interface, attempt);
+            match Self::find_device(interface)
+                .and_then(|device| Self::capture_handle(device, options))
+            {
+                Ok(capture_handle) => return Some(capture_handle),
+                Err(err) => {
+                    delay = (delay * 2).min(RECONNECT_MAX_DELAY);
+                    warn!(
+                        "{}: Reconnect attempt {} failed: {:?}, retrying in {}s",
+                        interface,
+                        attempt,
+                        err.to_string(),
+                        delay.as_secs()
+                    );
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Sends the packet counts of a capture handle, if libpcap provides them. They
+    /// are not available for capture files.
+    ///
+    /// # Returns
+    /// `false` if `send` did, i.e. the results are no longer received.
+    fn send_stats<T: Activated>(
+        capture_handle: &mut Capture<T>,
+        send: &mut impl FnMut(ReadPacketResult) -> bool,
+    ) -> bool {
+        match capture_handle.stats() {
+            Ok(stats) => send(ReadPacketResult::Stats(stats)),
+            Err(_) => true,
+        }
+    }
+}
+
+/// The packets of a capture handle, see `PcapInterface::packets`.
+pub struct Packets<T: Activated> {
+    capture_handle: Capture<T>,
+}
+
+impl<T: Activated> Packets<T> {
+    /// The packets received and dropped so far, as counted by libpcap. They are
+    /// not available for capture files.
+    pub fn stats(&mut self) -> Result<Stat, PcapError> {
+        self.capture_handle.stats()
+    }
+}
+
+impl<T: Activated> Iterator for Packets<T> {
+    type Item = Result<(PacketHeader, Vec<u8>), PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.capture_handle.next_packet() {
+                Ok(packet) => return Some(Ok((*packet.header, packet.data.to_vec()))),
+                Err(PcapError::NoMorePackets) => return None,
+                Err(err) if PcapInterface::is_would_block(&err) => continue,
+                Err(err) => return Some(Err(err)),
             }
         }
     }
@@ -104,4 +539,45 @@ impl PcapInterface {
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn permission_errors_explain_how_to_grant_privileges() {
+        let err = PcapError::PcapError(
+            "eth0: You don't have permission to capture on that device (socket: Operation not permitted)"
+                .to_string(),
+        );
+
+        let err = PcapInterface::open_error(err);
+        assert!(matches!(err, AnalyzerError::CapturePermissionDenied(..)));
+        assert!(err
+            .to_string()
+            .contains("sudo setcap cap_net_raw,cap_net_admin=eip"));
+    }
+
+    #[test]
+    fn other_open_errors_are_kept() {
+        let err = PcapError::PcapError("eth9: No such device exists".to_string());
+
+        assert!(matches!(
+            PcapInterface::open_error(err),
+            AnalyzerError::FailedToOpenCaptureHandle(_)
+        ));
+    }
+
+    #[test]
+    fn capture_files_end_without_an_error() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/ipv4_tcp.pcap");
+        let capture_handle = Capture::from_file(path).unwrap();
+
+        let mut results = Vec::new();
+        PcapInterface::read_packets_with(capture_handle, |result| {
+            results.push(result);
+            true
+        });
+
+        assert_eq!(results.len(), 3);
+        assert!(results
+            .iter()
+            .all(|result| matches!(result, ReadPacketResult::Success(_))));
+    }
 }
@@ -0,0 +1,80 @@
+use pcap::{Linktype, PacketHeader};
+use std::io::{self, Write};
+
+const PCAP_MAGIC: u32 = 0xa1b2_c3d4;
+const PCAP_VERSION_MAJOR: u16 = 2;
+const PCAP_VERSION_MINOR: u16 = 4;
+const PCAP_RECORD_HEADER_LEN: usize = 16;
+
+/// The snapshot length libpcap captures with unless one is set, advertised in the
+/// global header of captures without a snaplen.
+pub const DEFAULT_SNAPLEN: u32 = 262_144;
+
+/// Writes packets in the classic pcap file format to any `Write` destination.
+///
+/// `pcap::Savefile` can only target a filesystem path, so streaming a capture to
+/// sockets, pipes or stdout requires emitting the format by hand: a global header
+/// followed by one record header and the packet bytes for each packet.
+#[derive(Debug)]
+pub struct PcapWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> PcapWriter<W> {
+    /// Creates a writer and immediately emits the pcap global header.
+    ///
+    /// # Arguments
+    /// * `writer` - The destination of the pcap byte stream.
+    /// * `linktype` - The datalink type of the captured packets.
+    /// * `snaplen` - The maximum number of bytes captured per packet.
+    pub fn new(mut writer: W, linktype: Linktype, snaplen: u32) -> io::Result<Self> {
+        let mut header = Vec::with_capacity(24);
+        header.extend_from_slice(&PCAP_MAGIC.to_le_bytes());
+        header.extend_from_slice(&PCAP_VERSION_MAJOR.to_le_bytes());
+        header.extend_from_slice(&PCAP_VERSION_MINOR.to_le_bytes());
+        header.extend_from_slice(&0i32.to_le_bytes()); // thiszone
+        header.extend_from_slice(&0u32.to_le_bytes()); // sigfigs
+        header.extend_from_slice(&snaplen.to_le_bytes());
+        header.extend_from_slice(&(linktype.0 as u32).to_le_bytes());
+
+        writer.write_all(&header)?;
+        writer.flush()?;
+
+        Ok(Self { writer })
+    }
+
+    /// Writes a single packet record.
+    pub fn write_packet(&mut self, header: &PacketHeader, data: &[u8]) -> io::Result<()> {
+        let mut record = Vec::with_capacity(PCAP_RECORD_HEADER_LEN + data.len());
+        record.extend_from_slice(&(header.ts.tv_sec as u32).to_le_bytes());
+        record.extend_from_slice(&(header.ts.tv_usec as u32).to_le_bytes());
+        record.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        record.extend_from_slice(&header.len.to_le_bytes());
+        record.extend_from_slice(data);
+
+        self.writer.write_all(&record)?;
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_global_header_with_snaplen() {
+        let writer = PcapWriter::new(Vec::new(), Linktype::ETHERNET, 96).unwrap();
+
+        assert_eq!(
+            writer.writer,
+            [
+                0xd4, 0xc3, 0xb2, 0xa1, // magic number
+                0x02, 0x00, 0x04, 0x00, // version 2.4
+                0x00, 0x00, 0x00, 0x00, // thiszone
+                0x00, 0x00, 0x00, 0x00, // sigfigs
+                0x60, 0x00, 0x00, 0x00, // snaplen
+                0x01, 0x00, 0x00, 0x00, // Ethernet
+            ]
+        );
+    }
+}
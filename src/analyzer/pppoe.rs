@@ -0,0 +1,74 @@
+use super::encapsulation::{read_u16, ETHERNET_HEADER_LEN, ETHER_TYPE_IPV4, ETHER_TYPE_IPV6};
+
+pub const ETHER_TYPE_PPPOE_SESSION: u16 = 0x8864;
+
+pub const PPP_PROTOCOL_IPV4: u16 = 0x0021;
+pub const PPP_PROTOCOL_IPV6: u16 = 0x0057;
+
+/// PPPoE header (6 bytes) followed by the PPP protocol field (2 bytes).
+const PPPOE_SESSION_HEADER_LEN: usize = 8;
+
+/// A PPPoE session stage frame carrying a PPP payload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PppoeSession<'a> {
+    pub version: u8,
+    pub session_id: u16,
+    pub length: u16,
+    pub ppp_protocol: u16,
+    pub payload: &'a [u8],
+}
+
+impl<'a> PppoeSession<'a> {
+    /// Parses the PPPoE session header of a raw Ethernet frame.
+    ///
+    /// # Returns
+    /// `Some(PppoeSession)` if the frame's EtherType is 0x8864 and the header is
+    /// complete, `None` otherwise.
+    pub fn from_frame(frame: &'a [u8]) -> Option<Self> {
+        if read_u16(frame, 12)? != ETHER_TYPE_PPPOE_SESSION {
+            return None;
+        }
+
+        let data = frame.get(ETHERNET_HEADER_LEN..)?;
+        if data.len() < PPPOE_SESSION_HEADER_LEN {
+            return None;
+        }
+
+        let length = read_u16(data, 4)?;
+
+        // The PPPoE length covers the PPP protocol field and payload. Ethernet padding
+        // past it is excluded, unless the frame was truncated by the snapshot length.
+        let end = (6 + usize::from(length)).clamp(PPPOE_SESSION_HEADER_LEN, data.len());
+
+        Some(Self {
+            version: data[0] >> 4,
+            session_id: read_u16(data, 2)?,
+            length,
+            ppp_protocol: read_u16(data, 6)?,
+            payload: &data[PPPOE_SESSION_HEADER_LEN..end],
+        })
+    }
+
+    /// Returns the EtherType equivalent of the PPP protocol, if it carries IP.
+    pub fn inner_ether_type(&self) -> Option<u16> {
+        match self.ppp_protocol {
+            PPP_PROTOCOL_IPV4 => Some(ETHER_TYPE_IPV4),
+            PPP_PROTOCOL_IPV6 => Some(ETHER_TYPE_IPV6),
+            _ => None,
+        }
+    }
+}
+
+/// Returns a name for common PPP protocol numbers.
+pub fn ppp_protocol_name(protocol: u16) -> Option<&'static str> {
+    match protocol {
+        PPP_PROTOCOL_IPV4 => Some("IPv4"),
+        PPP_PROTOCOL_IPV6 => Some("IPv6"),
+        0xc021 => Some("LCP"),
+        0xc023 => Some("PAP"),
+        0xc223 => Some("CHAP"),
+        0x8021 => Some("IPCP"),
+        0x8057 => Some("IPv6CP"),
+        _ => None,
+    }
+}
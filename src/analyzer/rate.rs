@@ -0,0 +1,49 @@
+use std::time::{Duration, Instant};
+
+/// Accumulates packet and byte counts and periodically reports them as rates.
+#[derive(Debug)]
+pub struct RateMeter {
+    interval: Duration,
+    last_report: Instant,
+    packets: u64,
+    bytes: u64,
+}
+
+impl RateMeter {
+    /// Creates a meter that reports every `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_report: Instant::now(),
+            packets: 0,
+            bytes: 0,
+        }
+    }
+
+    /// Accounts a packet of `len` bytes on the wire.
+    pub fn record(&mut self, len: u32) {
+        self.packets += 1;
+        self.bytes += u64::from(len);
+    }
+
+    /// Measures the rates since the last report if the interval has elapsed, and
+    /// starts a new interval.
+    ///
+    /// # Returns
+    /// `Some((packets, bytes))` per second once the interval has elapsed, `None`
+    /// otherwise.
+    pub fn report(&mut self) -> Option<(f64, f64)> {
+        let elapsed = self.last_report.elapsed();
+        if elapsed < self.interval {
+            return None;
+        }
+
+        let seconds = elapsed.as_secs_f64();
+        let rates = (self.packets as f64 / seconds, self.bytes as f64 / seconds);
+
+        self.last_report = Instant::now();
+        self.packets = 0;
+        self.bytes = 0;
+        Some(rates)
+    }
+}
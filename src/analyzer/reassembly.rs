@@ -0,0 +1,256 @@
+use super::{
+    encapsulation::{self, read_u16, read_u32},
+    flows::{Direction, FlowKey, IP_PROTOCOL_TCP},
+};
+use std::collections::{BTreeMap, HashMap};
+
+const TCP_FLAG_SYN: u8 = 0x02;
+const TCP_MIN_HEADER_LEN: usize = 20;
+
+/// The sequence number, flags, window, options and payload of a TCP segment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TcpSegment<'a> {
+    pub sequence: u32,
+    pub flags: u8,
+    pub window: u16,
+    pub options: &'a [u8],
+    pub payload: &'a [u8],
+}
+
+impl<'a> TcpSegment<'a> {
+    /// Parses the TCP segment of a raw Ethernet frame.
+    ///
+    /// # Returns
+    /// `Some(TcpSegment)` if the frame carries a TCP segment with a complete
+    /// header, `None` otherwise.
+    pub fn from_frame(frame: &'a [u8]) -> Option<Self> {
+        let (header, segment) = encapsulation::parse_ip(frame)?;
+        if header.protocol != IP_PROTOCOL_TCP {
+            return None;
+        }
+
+        let data_offset = usize::from(segment.get(12)? >> 4) * 4;
+        if data_offset < TCP_MIN_HEADER_LEN {
+            return None;
+        }
+
+        Some(Self {
+            sequence: read_u32(segment, 4)?,
+            flags: *segment.get(13)?,
+            window: read_u16(segment, 14)?,
+            options: segment.get(TCP_MIN_HEADER_LEN..data_offset)?,
+            payload: segment.get(data_offset..)?,
+        })
+    }
+}
+
+/// The bytes sent in one direction of a TCP connection, keyed by their offset
+/// from the first sequence number.
+#[derive(Debug, Default)]
+struct HalfStream {
+    base: Option<u32>,
+    segments: BTreeMap<u32, Vec<u8>>,
+}
+
+impl HalfStream {
+    fn add(&mut self, segment: &TcpSegment) {
+        // The SYN consumes one sequence number ahead of the first data byte
+        let start = if segment.flags & TCP_FLAG_SYN != 0 {
+            let start = segment.sequence.wrapping_add(1);
+            self.base = Some(start);
+            start
+        } else {
+            segment.sequence
+        };
+
+        if segment.payload.is_empty() {
+            return;
+        }
+
+        // Without a SYN, the stream is taken to start at the first segment seen
+        let base = *self.base.get_or_insert(start);
+        let offset = start.wrapping_sub(base);
+
+        // Retransmissions of data sent before the base are out of the window
+        if offset > i32::MAX as u32 {
+            return;
+        }
+
+        let stored = self.segments.entry(offset).or_default();
+        if segment.payload.len() > stored.len() {
+            *stored = segment.payload.to_vec();
+        }
+    }
+
+    /// Concatenates the received bytes in sequence order, resolving retransmitted
+    /// and overlapping segments.
+    ///
+    /// # Returns
+    /// The contiguous bytes from the start of the stream and whether the stream
+    /// is complete, i.e. no bytes were missing before the last segment.
+    fn assemble(&self) -> (Vec<u8>, bool) {
+        let mut data: Vec<u8> = Vec::new();
+
+        for (offset, bytes) in &self.segments {
+            let offset = *offset as usize;
+            if offset > data.len() {
+                return (data, false);
+            }
+
+            let end = offset + bytes.len();
+            if end > data.len() {
+                data.extend_from_slice(&bytes[data.len() - offset..]);
+            }
+        }
+
+        (data, true)
+    }
+}
+
+/// A reassembled TCP connection.
+#[derive(Debug, Clone)]
+pub struct TcpConversation {
+    pub key: FlowKey,
+    /// The bytes sent from endpoint `a` to endpoint `b` of the key.
+    pub a_to_b: Vec<u8>,
+    /// The bytes sent from endpoint `b` to endpoint `a` of the key.
+    pub b_to_a: Vec<u8>,
+    /// Whether both directions were reassembled without missing segments.
+    pub complete: bool,
+}
+
+/// Reassembles the byte streams of the TCP connections in a capture.
+///
+/// Segments are buffered until `finish`, so that out-of-order segments and
+/// retransmissions are placed by their sequence numbers. The reassembler is meant
+/// for reading capture files; connections that reuse the same addresses and ports
+/// are merged.
+#[derive(Debug, Default)]
+pub struct TcpReassembler {
+    connections: HashMap<FlowKey, (HalfStream, HalfStream)>,
+    /// Connections in the order their first packet was seen.
+    order: Vec<FlowKey>,
+}
+
+impl TcpReassembler {
+    /// Adds the TCP segment of a raw Ethernet frame, ignoring other frames.
+    pub fn record(&mut self, frame: &[u8]) {
+        let (key, direction) = match FlowKey::from_frame(frame) {
+            Some(flow) => flow,
+            None => return,
+        };
+        let segment = match TcpSegment::from_frame(frame) {
+            Some(segment) => segment,
+            None => return,
+        };
+
+        let (a_to_b, b_to_a) = self.connections.entry(key).or_insert_with(|| {
+            self.order.push(key);
+            Default::default()
+        });
+
+        match direction {
+            Direction::AToB => a_to_b.add(&segment),
+            Direction::BToA => b_to_a.add(&segment),
+        }
+    }
+
+    /// Reassembles every connection, in the order they were first seen.
+    pub fn finish(mut self) -> Vec<TcpConversation> {
+        self.order
+            .iter()
+            .filter_map(|key| {
+                let (a_to_b, b_to_a) = self.connections.remove(key)?;
+                let (a_to_b, a_complete) = a_to_b.assemble();
+                let (b_to_a, b_complete) = b_to_a.assemble();
+
+                Some(TcpConversation {
+                    key: *key,
+                    a_to_b,
+                    b_to_a,
+                    complete: a_complete && b_complete,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT_PORT: u16 = 50000;
+    const SERVER_PORT: u16 = 80;
+
+    /// A TCP segment between 10.0.0.1:50000 and 10.0.0.2:80, sent by the client
+    /// unless `from_server` is set.
+    fn tcp_frame(from_server: bool, sequence: u32, flags: u8, payload: &[u8]) -> Vec<u8> {
+        let (source, destination) = match from_server {
+            false => ([10, 0, 0, 1], [10, 0, 0, 2]),
+            true => ([10, 0, 0, 2], [10, 0, 0, 1]),
+        };
+        let (source_port, destination_port) = match from_server {
+            false => (CLIENT_PORT, SERVER_PORT),
+            true => (SERVER_PORT, CLIENT_PORT),
+        };
+
+        let mut frame = vec![0u8; 12];
+        frame.extend_from_slice(&encapsulation::ETHER_TYPE_IPV4.to_be_bytes());
+        frame.extend_from_slice(&[0x45, 0x00]);
+        frame.extend_from_slice(&(40 + payload.len() as u16).to_be_bytes());
+        frame.extend_from_slice(&[0x00, 0x01, 0x40, 0x00, 0x40, IP_PROTOCOL_TCP, 0x00, 0x00]);
+        frame.extend_from_slice(&source);
+        frame.extend_from_slice(&destination);
+        frame.extend_from_slice(&source_port.to_be_bytes());
+        frame.extend_from_slice(&destination_port.to_be_bytes());
+        frame.extend_from_slice(&sequence.to_be_bytes());
+        frame.extend_from_slice(&[0x00, 0x00, 0x00, 0x00, 0x50, flags, 0xff, 0xff]);
+        frame.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    #[test]
+    fn parses_tcp_segment() {
+        let frame = tcp_frame(false, 1000, TCP_FLAG_SYN, b"");
+        let segment = TcpSegment::from_frame(&frame).unwrap();
+
+        assert_eq!(segment.sequence, 1000);
+        assert_eq!(segment.flags, TCP_FLAG_SYN);
+        assert_eq!(segment.window, 0xffff);
+        assert!(segment.options.is_empty());
+        assert!(segment.payload.is_empty());
+    }
+
+    #[test]
+    fn reassembles_out_of_order_and_retransmitted_segments() {
+        let mut reassembler = TcpReassembler::default();
+        reassembler.record(&tcp_frame(false, 100, TCP_FLAG_SYN, b""));
+        reassembler.record(&tcp_frame(true, 500, TCP_FLAG_SYN, b""));
+        reassembler.record(&tcp_frame(false, 107, 0x18, b"world"));
+        reassembler.record(&tcp_frame(false, 101, 0x18, b"hello "));
+        reassembler.record(&tcp_frame(false, 101, 0x18, b"hello "));
+        reassembler.record(&tcp_frame(true, 501, 0x18, b"ok"));
+
+        let conversations = reassembler.finish();
+        assert_eq!(conversations.len(), 1);
+
+        let conversation = &conversations[0];
+        assert_eq!(conversation.key.a.port, CLIENT_PORT);
+        assert_eq!(conversation.a_to_b, b"hello world");
+        assert_eq!(conversation.b_to_a, b"ok");
+        assert!(conversation.complete);
+    }
+
+    #[test]
+    fn stops_at_missing_segment() {
+        let mut reassembler = TcpReassembler::default();
+        reassembler.record(&tcp_frame(false, 100, TCP_FLAG_SYN, b""));
+        reassembler.record(&tcp_frame(false, 101, 0x18, b"hello "));
+        reassembler.record(&tcp_frame(false, 112, 0x18, b"again"));
+
+        let conversation = &reassembler.finish()[0];
+        assert_eq!(conversation.a_to_b, b"hello ");
+        assert!(!conversation.complete);
+    }
+}
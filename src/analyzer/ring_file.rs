@@ -0,0 +1,299 @@
+use super::pcap_writer::PcapWriter;
+use pcap::{Linktype, PacketHeader};
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+};
+
+/// Identifies a ring file, as opposed to a plain .pcap file.
+const RING_MAGIC: &[u8; 8] = b"WYRERING";
+/// The magic, capacity, start and used fields, each 8 bytes.
+const RING_HEADER_LEN: u64 = 32;
+const PCAP_HEADER_LEN: u64 = 24;
+const PCAP_RECORD_HEADER_LEN: usize = 16;
+/// The offset of the ring region, which follows the ring and pcap global headers.
+const RING_DATA_OFFSET: u64 = RING_HEADER_LEN + PCAP_HEADER_LEN;
+
+/// Writes packets to a single fixed-size file that always holds the most recent
+/// packets, overwriting the oldest ones (a "flight recorder").
+///
+/// The file starts with a ring header recording the capacity of the ring region
+/// and the valid window within it (the offset of the oldest record and the number
+/// of bytes in use), followed by a pcap global header and the ring region. Records
+/// use the pcap record format but wrap around the end of the region, so the file
+/// is read back with `RingReader` rather than libpcap.
+///
+/// The window is shrunk on disk before the oldest records are overwritten and
+/// grown after the new record is written, so the header never covers a partly
+/// written record.
+pub struct RingWriter {
+    file: File,
+    capacity: u64,
+    start: u64,
+    used: u64,
+    /// The lengths of the records in the window, oldest first.
+    records: VecDeque<u64>,
+}
+
+impl RingWriter {
+    /// Creates the ring file at `path`, replacing any existing file.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the ring file.
+    /// * `capacity` - The size of the ring region in bytes.
+    /// * `linktype` - The datalink type of the captured packets.
+    /// * `snaplen` - The maximum number of bytes captured per packet.
+    pub fn create(path: &str, capacity: u64, linktype: Linktype, snaplen: u32) -> io::Result<Self> {
+        if capacity <= PCAP_RECORD_HEADER_LEN as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "ring size is too small to hold a packet",
+            ));
+        }
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+
+        file.write_all(RING_MAGIC)?;
+        file.write_all(&capacity.to_le_bytes())?;
+        file.write_all(&0u64.to_le_bytes())?;
+        file.write_all(&0u64.to_le_bytes())?;
+        PcapWriter::new(&mut file, linktype, snaplen)?;
+        file.set_len(RING_DATA_OFFSET + capacity)?;
+
+        Ok(Self {
+            file,
+            capacity,
+            start: 0,
+            used: 0,
+            records: VecDeque::new(),
+        })
+    }
+
+    /// Writes a packet record, overwriting the oldest records to make room.
+    ///
+    /// Packets are truncated to fit the ring if they are larger than it.
+    pub fn write_packet(&mut self, header: &PacketHeader, data: &[u8]) -> io::Result<()> {
+        let max_data = (self.capacity as usize).saturating_sub(PCAP_RECORD_HEADER_LEN);
+        let data = &data[..data.len().min(max_data)];
+
+        let mut record = Vec::with_capacity(PCAP_RECORD_HEADER_LEN + data.len());
+        record.extend_from_slice(&(header.ts.tv_sec as u32).to_le_bytes());
+        record.extend_from_slice(&(header.ts.tv_usec as u32).to_le_bytes());
+        record.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        record.extend_from_slice(&header.len.to_le_bytes());
+        record.extend_from_slice(data);
+        let len = record.len() as u64;
+
+        let mut evicted = false;
+        while self.used + len > self.capacity {
+            let oldest = match self.records.pop_front() {
+                Some(oldest) => oldest,
+                None => break,
+            };
+            self.start = (self.start + oldest) % self.capacity;
+            self.used -= oldest;
+            evicted = true;
+        }
+
+        if evicted {
+            self.write_window()?;
+        }
+
+        let end = (self.start + self.used) % self.capacity;
+        let first = record.len().min((self.capacity - end) as usize);
+        self.file.seek(SeekFrom::Start(RING_DATA_OFFSET + end))?;
+        self.file.write_all(&record[..first])?;
+        if first < record.len() {
+            self.file.seek(SeekFrom::Start(RING_DATA_OFFSET))?;
+            self.file.write_all(&record[first..])?;
+        }
+
+        self.used += len;
+        self.records.push_back(len);
+        self.write_window()
+    }
+
+    /// Updates the valid window in the ring header.
+    fn write_window(&mut self) -> io::Result<()> {
+        let mut window = [0u8; 16];
+        window[..8].copy_from_slice(&self.start.to_le_bytes());
+        window[8..].copy_from_slice(&self.used.to_le_bytes());
+
+        self.file.seek(SeekFrom::Start(16))?;
+        self.file.write_all(&window)?;
+        self.file.flush()
+    }
+}
+
+/// Reads the packets of a ring file written by `RingWriter`, oldest first.
+pub struct RingReader {
+    linktype: Linktype,
+    /// The valid window of the ring, unwrapped into capture order.
+    window: Vec<u8>,
+    position: usize,
+}
+
+impl RingReader {
+    /// Opens `path` if it is a ring file.
+    ///
+    /// # Returns
+    /// `Ok(Some(RingReader))` for a ring file, `Ok(None)` for any other file, or an
+    /// `io::Error` if the file cannot be read or its header is inconsistent.
+    pub fn open(path: &str) -> io::Result<Option<Self>> {
+        let mut file = File::open(path)?;
+
+        let mut header = [0u8; (RING_HEADER_LEN + PCAP_HEADER_LEN) as usize];
+        match file.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err),
+        }
+
+        if &header[..8] != RING_MAGIC {
+            return Ok(None);
+        }
+
+        let field = |offset: usize| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&header[offset..offset + 8]);
+            u64::from_le_bytes(bytes)
+        };
+        let (capacity, start, used) = (field(8), field(16), field(24));
+
+        // The ring region must fit in the file, and the window in the region. An
+        // empty region has no offset to start from but 0.
+        let region_len = file.metadata()?.len().saturating_sub(RING_DATA_OFFSET);
+        if capacity > region_len || used > capacity || start >= capacity.max(1) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "ring header is inconsistent",
+            ));
+        }
+
+        let mut linktype = [0u8; 4];
+        linktype.copy_from_slice(&header[header.len() - 4..]);
+
+        let mut window = vec![0u8; used as usize];
+        let first = used.min(capacity - start) as usize;
+        file.seek(SeekFrom::Start(RING_DATA_OFFSET + start))?;
+        file.read_exact(&mut window[..first])?;
+        file.seek(SeekFrom::Start(RING_DATA_OFFSET))?;
+        file.read_exact(&mut window[first..])?;
+
+        Ok(Some(Self {
+            linktype: Linktype(u32::from_le_bytes(linktype) as i32),
+            window,
+            position: 0,
+        }))
+    }
+
+    /// The datalink type of the packets in the ring.
+    pub fn linktype(&self) -> Linktype {
+        self.linktype
+    }
+
+    /// Returns the next packet, or `None` once the window has been read.
+    pub fn next_packet(&mut self) -> Option<(PacketHeader, Vec<u8>)> {
+        let record = self.window.get(self.position..)?;
+        let field = |offset: usize| {
+            let mut bytes = [0u8; 4];
+            bytes.copy_from_slice(record.get(offset..offset + 4)?);
+            Some(u32::from_le_bytes(bytes))
+        };
+
+        let caplen = field(8)?;
+        let data = record.get(PCAP_RECORD_HEADER_LEN..PCAP_RECORD_HEADER_LEN + caplen as usize)?;
+        let header = PacketHeader {
+            ts: libc::timeval {
+                tv_sec: field(0)? as libc::time_t,
+                tv_usec: field(4)? as libc::suseconds_t,
+            },
+            caplen,
+            len: field(12)?,
+        };
+
+        self.position += PCAP_RECORD_HEADER_LEN + data.len();
+        Some((header, data.to_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs, process};
+
+    /// A path in the temporary directory for the ring file of one test.
+    fn ring_path(name: &str) -> String {
+        env::temp_dir()
+            .join(format!("wyre-{}-{}.ring", name, process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn packet_header(tv_sec: libc::time_t, len: usize) -> PacketHeader {
+        PacketHeader {
+            ts: libc::timeval { tv_sec, tv_usec: 0 },
+            caplen: len as u32,
+            len: len as u32,
+        }
+    }
+
+    #[test]
+    fn reads_back_records_wrapped_around_the_ring() {
+        let path = ring_path("wrap");
+        // Room for two 46-byte records, so the third evicts the first and wraps
+        let mut writer = RingWriter::create(&path, 100, Linktype::ETHERNET, 96).unwrap();
+        for index in 0..3u8 {
+            writer
+                .write_packet(&packet_header(libc::time_t::from(index), 30), &[index; 30])
+                .unwrap();
+        }
+        drop(writer);
+
+        let mut reader = RingReader::open(&path).unwrap().unwrap();
+        let packets = std::iter::from_fn(|| reader.next_packet()).collect::<Vec<_>>();
+        let file = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // The snaplen of the embedded pcap header
+        let snaplen = RING_HEADER_LEN as usize + 16;
+        assert_eq!(file[snaplen..snaplen + 4], 96u32.to_le_bytes());
+
+        assert_eq!(reader.linktype(), Linktype::ETHERNET);
+        assert_eq!(packets.len(), 2);
+        for ((header, data), index) in packets.iter().zip(1..) {
+            assert_eq!(header.ts.tv_sec, libc::time_t::from(index));
+            assert_eq!(header.caplen, 30);
+            assert_eq!(*data, [index; 30]);
+        }
+    }
+
+    #[test]
+    fn rejects_corrupt_ring_header() {
+        let path = ring_path("corrupt");
+        drop(RingWriter::create(&path, 100, Linktype::ETHERNET, 96).unwrap());
+
+        // A capacity beyond the end of the file, then an empty ring with a start
+        let corruptions: [(u64, u64, u64); 2] = [(1000, 0, 0), (0, 5, 0)];
+        for (capacity, start, used) in corruptions {
+            let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+            file.seek(SeekFrom::Start(8)).unwrap();
+            for field in [capacity, start, used] {
+                file.write_all(&field.to_le_bytes()).unwrap();
+            }
+            drop(file);
+
+            match RingReader::open(&path) {
+                Err(err) => assert_eq!(err.kind(), io::ErrorKind::InvalidData),
+                Ok(_) => panic!("a corrupt ring header was accepted"),
+            }
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,88 @@
+use super::definitions::RotateLimit;
+use pcap::{Capture, Error as PcapError, Linktype, Savefile};
+use std::path::{Path, PathBuf};
+
+const PCAP_HEADER_LEN: u64 = 24;
+const PCAP_RECORD_HEADER_LEN: u64 = 16;
+
+/// Splits a capture into numbered .pcap files, e.g. `capture-001.pcap`,
+/// `capture-002.pcap`, each holding at most a given number of packets or bytes.
+///
+/// Files are opened on a dead capture handle with the datalink type of the live
+/// one, since the live handle is owned by the thread reading packets. The next
+/// file is only opened once a packet no longer fits the current one, so a capture
+/// never ends with an empty file.
+pub struct FileRotation {
+    dir_name: String,
+    file_name: String,
+    limit: RotateLimit,
+    linktype: Linktype,
+    /// The number of the current file, starting at 1.
+    index: usize,
+    packets: u64,
+    bytes: u64,
+}
+
+impl FileRotation {
+    /// Creates a rotation whose files are named `<file_name>-NNN.pcap` in `dir_name`.
+    ///
+    /// # Arguments
+    /// * `dir_name` - The directory the files are saved in.
+    /// * `file_name` - The name the file numbers are appended to.
+    /// * `limit` - The number of packets or bytes after which a new file is started.
+    /// * `linktype` - The datalink type of the captured packets.
+    pub fn new(dir_name: &str, file_name: &str, limit: RotateLimit, linktype: Linktype) -> Self {
+        Self {
+            dir_name: dir_name.to_string(),
+            file_name: file_name.to_string(),
+            limit,
+            linktype,
+            index: 1,
+            packets: 0,
+            bytes: PCAP_HEADER_LEN,
+        }
+    }
+
+    /// The path of the current file.
+    pub fn path(&self) -> PathBuf {
+        Path::new(&self.dir_name).join(format!("{}-{:03}.pcap", self.file_name, self.index))
+    }
+
+    /// A pattern matching the paths of all files of the rotation, for logging.
+    pub fn pattern(&self) -> String {
+        Path::new(&self.dir_name)
+            .join(format!("{}-*.pcap", self.file_name))
+            .display()
+            .to_string()
+    }
+
+    /// Opens the current file for writing.
+    pub fn open(&self) -> Result<Savefile, PcapError> {
+        Capture::dead(self.linktype)?.savefile(self.path())
+    }
+
+    /// Returns `true` if a packet of `caplen` bytes does not fit the current file.
+    /// A packet larger than the size limit still fits an empty file.
+    pub fn is_full(&self, caplen: u32) -> bool {
+        match self.limit {
+            RotateLimit::Packets(packets) => self.packets >= packets,
+            RotateLimit::Bytes(bytes) => {
+                self.packets > 0 && self.bytes + PCAP_RECORD_HEADER_LEN + u64::from(caplen) > bytes
+            }
+        }
+    }
+
+    /// Accounts a packet of `caplen` bytes written to the current file.
+    pub fn record(&mut self, caplen: u32) {
+        self.packets += 1;
+        self.bytes += PCAP_RECORD_HEADER_LEN + u64::from(caplen);
+    }
+
+    /// Moves on to the next file and opens it.
+    pub fn next(&mut self) -> Result<Savefile, PcapError> {
+        self.index += 1;
+        self.packets = 0;
+        self.bytes = PCAP_HEADER_LEN;
+        self.open()
+    }
+}
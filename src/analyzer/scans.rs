@@ -0,0 +1,200 @@
+use super::{
+    definitions::ScanThresholds,
+    encapsulation::{self, read_u16},
+    flows::IP_PROTOCOL_TCP,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    net::IpAddr,
+    time::Instant,
+};
+
+const TCP_FLAG_SYN: u8 = 0x02;
+const TCP_FLAG_ACK: u8 = 0x10;
+
+/// The addresses and ports of a connection attempt, from the side sending the SYN.
+type SynKey = (IpAddr, u16, IpAddr, u16);
+
+/// A source that sent suspicious unanswered SYNs within the detection window.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanAlert {
+    /// The source sent unanswered SYNs to many distinct destination ports.
+    PortScan { source: IpAddr, ports: usize },
+    /// The source sent many unanswered SYNs to a single port.
+    SynFlood {
+        source: IpAddr,
+        destination: IpAddr,
+        port: u16,
+        syns: u64,
+    },
+}
+
+/// Tracks TCP SYNs that were not answered by a SYN-ACK over a sliding window, to
+/// flag sources that look like port scanners or SYN flooders.
+///
+/// A SYN is forgotten once it is answered or falls out of the window. Each source,
+/// or each port it floods, is reported at most once per window.
+#[derive(Debug, Default)]
+pub struct SynTracker {
+    /// Every SYN of the window, oldest first.
+    syns: VecDeque<(Instant, SynKey)>,
+    /// The unanswered SYNs of each connection attempt.
+    pending: HashMap<SynKey, u64>,
+    /// The unanswered SYNs of each source, by destination port.
+    ports: HashMap<IpAddr, HashMap<u16, u64>>,
+    /// The unanswered SYNs of each source to each destination port.
+    targets: HashMap<(IpAddr, IpAddr, u16), u64>,
+    scan_alerts: HashMap<IpAddr, Instant>,
+    flood_alerts: HashMap<(IpAddr, IpAddr, u16), Instant>,
+}
+
+impl SynTracker {
+    /// Accounts the TCP SYN or SYN-ACK of a raw Ethernet frame. Other frames only
+    /// move the window forward.
+    ///
+    /// # Arguments
+    /// * `frame` - The raw bytes of the captured Ethernet frame.
+    /// * `thresholds` - The thresholds and window to check the sender against.
+    /// * `now` - The time the frame was seen.
+    ///
+    /// # Returns
+    /// `Some(ScanAlert)` the first time within the window that the sender of a SYN
+    /// exceeds a threshold, `None` otherwise.
+    pub fn observe(
+        &mut self,
+        frame: &[u8],
+        thresholds: &ScanThresholds,
+        now: Instant,
+    ) -> Option<ScanAlert> {
+        self.expire(thresholds, now);
+
+        let (header, segment) = encapsulation::parse_ip(frame)?;
+        if header.protocol != IP_PROTOCOL_TCP {
+            return None;
+        }
+
+        let source_port = read_u16(segment, 0)?;
+        let destination_port = read_u16(segment, 2)?;
+        let flags = *segment.get(13)?;
+
+        match (flags & TCP_FLAG_SYN != 0, flags & TCP_FLAG_ACK != 0) {
+            (true, true) => {
+                // A SYN-ACK answers the SYN sent the other way
+                self.answer((
+                    header.destination_address,
+                    destination_port,
+                    header.source_address,
+                    source_port,
+                ));
+                None
+            }
+            (true, false) => {
+                let key = (
+                    header.source_address,
+                    source_port,
+                    header.destination_address,
+                    destination_port,
+                );
+                self.syns.push_back((now, key));
+                self.count(key, 1);
+                self.check(key, thresholds, now)
+            }
+            _ => None,
+        }
+    }
+
+    /// Forgets the SYNs that fell out of the window.
+    fn expire(&mut self, thresholds: &ScanThresholds, now: Instant) {
+        while let Some(&(seen, key)) = self.syns.front() {
+            if now.duration_since(seen) < thresholds.window {
+                break;
+            }
+
+            self.syns.pop_front();
+            if self.pending.contains_key(&key) {
+                self.uncount(key, 1);
+            }
+        }
+    }
+
+    /// Forgets the SYNs of a connection attempt that was answered.
+    fn answer(&mut self, key: SynKey) {
+        if let Some(&syns) = self.pending.get(&key) {
+            self.uncount(key, syns);
+        }
+    }
+
+    /// Adds `syns` unanswered SYNs of a connection attempt to every counter.
+    fn count(&mut self, key: SynKey, syns: u64) {
+        let (source, _, destination, port) = key;
+
+        *self.pending.entry(key).or_default() += syns;
+        *self
+            .ports
+            .entry(source)
+            .or_default()
+            .entry(port)
+            .or_default() += syns;
+        *self.targets.entry((source, destination, port)).or_default() += syns;
+    }
+
+    /// Removes `syns` unanswered SYNs of a connection attempt from every counter,
+    /// dropping the counters that reach zero.
+    fn uncount(&mut self, key: SynKey, syns: u64) {
+        let (source, _, destination, port) = key;
+
+        decrement(&mut self.pending, key, syns);
+        decrement(&mut self.targets, (source, destination, port), syns);
+        if let Some(ports) = self.ports.get_mut(&source) {
+            decrement(ports, port, syns);
+            if ports.is_empty() {
+                self.ports.remove(&source);
+            }
+        }
+    }
+
+    /// Checks the sender of a SYN against the thresholds.
+    fn check(
+        &mut self,
+        key: SynKey,
+        thresholds: &ScanThresholds,
+        now: Instant,
+    ) -> Option<ScanAlert> {
+        let (source, _, destination, port) = key;
+        let is_due = |alerted: Option<&Instant>| match alerted {
+            Some(&at) => now.duration_since(at) >= thresholds.window,
+            None => true,
+        };
+
+        let ports = self.ports.get(&source).map_or(0, HashMap::len);
+        if ports as u64 >= thresholds.ports && is_due(self.scan_alerts.get(&source)) {
+            self.scan_alerts.insert(source, now);
+            return Some(ScanAlert::PortScan { source, ports });
+        }
+
+        let target = (source, destination, port);
+        let syns = self.targets.get(&target).copied().unwrap_or_default();
+        if syns >= thresholds.syns && is_due(self.flood_alerts.get(&target)) {
+            self.flood_alerts.insert(target, now);
+            return Some(ScanAlert::SynFlood {
+                source,
+                destination,
+                port,
+                syns,
+            });
+        }
+
+        None
+    }
+}
+
+/// Subtracts `amount` from the counter of `key`, removing it once it reaches zero.
+fn decrement<K: Hash + Eq>(counters: &mut HashMap<K, u64>, key: K, amount: u64) {
+    if let Some(count) = counters.get_mut(&key) {
+        *count = count.saturating_sub(amount);
+        if *count == 0 {
+            counters.remove(&key);
+        }
+    }
+}
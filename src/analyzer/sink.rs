@@ -0,0 +1,49 @@
+#[cfg(feature = "pcapng")]
+use super::pcapng::PcapngWriter;
+use super::{fifo::FifoWriter, pcap_writer::PcapWriter, ring_file::RingWriter};
+use pcap::{Packet, PacketHeader, Savefile};
+use std::io::{self, Stdout};
+#[cfg(feature = "pcapng")]
+use std::{fs::File, io::BufWriter};
+
+/// A destination that captured packets are saved to.
+pub enum PacketSink {
+    /// A .pcap file written by libpcap.
+    Savefile(Savefile),
+    /// A .pcapng file, written with nanosecond timestamps.
+    #[cfg(feature = "pcapng")]
+    Pcapng(PcapngWriter<BufWriter<File>>),
+    /// A pcap stream written to a named pipe.
+    Fifo(FifoWriter),
+    /// A fixed-size ring file holding the most recent packets.
+    Ring(RingWriter),
+    /// A pcap stream written to stdout.
+    Stdout(PcapWriter<Stdout>),
+}
+
+impl PacketSink {
+    /// Writes a single packet to the sink.
+    pub fn write(&mut self, header: &PacketHeader, data: &[u8]) -> io::Result<()> {
+        match self {
+            PacketSink::Savefile(savefile) => {
+                savefile.write(&Packet::new(header, data));
+                Ok(())
+            }
+            #[cfg(feature = "pcapng")]
+            PacketSink::Pcapng(pcapng) => pcapng.write_packet(header, data),
+            PacketSink::Fifo(fifo) => fifo.write_packet(header, data),
+            PacketSink::Ring(ring) => ring.write_packet(header, data),
+            PacketSink::Stdout(stdout) => stdout.write_packet(header, data),
+        }
+    }
+
+    /// Whether the sink is written with the nanosecond timestamps that its capture
+    /// handle is opened with, rather than microseconds.
+    pub fn nanosecond_timestamps(&self) -> bool {
+        match self {
+            #[cfg(feature = "pcapng")]
+            PacketSink::Pcapng(_) => true,
+            _ => false,
+        }
+    }
+}
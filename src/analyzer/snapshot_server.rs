@@ -0,0 +1,144 @@
+use log::warn;
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How often the capture loop publishes a new snapshot.
+const PUBLISH_INTERVAL: Duration = Duration::from_secs(1);
+
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Maps the path of a request to the content type and body of the response, given
+/// the latest snapshot, or to `None` if nothing is served at the path.
+pub type Route = fn(path: &str, snapshot: &str) -> Option<(&'static str, String)>;
+
+/// A minimal HTTP server for a snapshot of the capture state, shared by the
+/// dashboard and the metrics endpoint.
+///
+/// The capture loop publishes snapshots at most once per second, so serving
+/// requests never contends with per-packet processing.
+pub struct SnapshotServer {
+    snapshot: Arc<Mutex<String>>,
+    local_addr: SocketAddr,
+    last_publish: Instant,
+}
+
+impl SnapshotServer {
+    /// Binds to `addr` and starts serving requests on a background thread.
+    ///
+    /// # Arguments
+    /// * `addr` - The address to listen on.
+    /// * `name` - What is served, for the log messages.
+    /// * `snapshot` - The snapshot served until the first one is published.
+    /// * `route` - Builds the response to each request.
+    pub fn start(
+        addr: &str,
+        name: &'static str,
+        snapshot: String,
+        route: Route,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let snapshot = Arc::new(Mutex::new(snapshot));
+
+        let served = Arc::clone(&snapshot);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let result = stream.and_then(|stream| Self::respond(stream, &served, route));
+
+                if let Err(e) = result {
+                    warn!("Failed to serve {} request: {}", name, e);
+                }
+            }
+        });
+
+        Ok(Self {
+            snapshot,
+            local_addr,
+            last_publish: Instant::now(),
+        })
+    }
+
+    /// The address the server listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Publishes the snapshot built by `snapshot` if the publish interval has elapsed.
+    pub fn publish(&mut self, snapshot: impl FnOnce() -> String) {
+        if self.last_publish.elapsed() < PUBLISH_INTERVAL {
+            return;
+        }
+
+        self.last_publish = Instant::now();
+        if let Ok(mut published) = self.snapshot.lock() {
+            *published = snapshot();
+        }
+    }
+
+    fn respond(stream: TcpStream, snapshot: &Mutex<String>, route: Route) -> io::Result<()> {
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+        let mut request_line = String::new();
+        BufReader::new(stream.try_clone()?).read_line(&mut request_line)?;
+        let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+
+        // The snapshot is copied so that the lock is not held while writing
+        let snapshot = snapshot.lock().map(|s| s.clone()).unwrap_or_default();
+        let (status, content_type, body) = match route(path, &snapshot) {
+            Some((content_type, body)) => ("200 OK", content_type, body),
+            None => ("404 Not Found", "text/plain", "Not found".to_string()),
+        };
+
+        let mut stream = stream;
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn route(path: &str, snapshot: &str) -> Option<(&'static str, String)> {
+        match path {
+            "/snapshot" => Some(("text/plain", snapshot.to_string())),
+            _ => None,
+        }
+    }
+
+    fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn serves_snapshot_and_unknown_paths() {
+        let server =
+            SnapshotServer::start("127.0.0.1:0", "test", "initial".to_string(), route).unwrap();
+
+        let response = get(server.local_addr(), "/snapshot");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: text/plain\r\nContent-Length: 7\r\n"));
+        assert!(response.ends_with("\r\n\r\ninitial"));
+
+        let response = get(server.local_addr(), "/missing");
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
@@ -0,0 +1,63 @@
+use super::pcap_writer::PcapWriter;
+use super::{
+    arp::ArpBindings,
+    flows::FlowTable,
+    scans::SynTracker,
+    stats::Stats,
+    talkers::{Conversations, TalkerCounters},
+};
+use pcap::Stat;
+use std::{collections::VecDeque, fs::File};
+
+/// State carried across packets for the lifetime of a capture.
+#[derive(Debug, Default)]
+pub struct CaptureState {
+    /// IP-to-MAC bindings learnt from ARP replies, used to detect IP conflicts.
+    pub arp_bindings: ArpBindings,
+
+    /// Per-direction traffic counters of each flow seen in the capture.
+    pub flows: FlowTable,
+
+    /// Packet counts by protocol.
+    pub stats: Stats,
+
+    /// Per-host counters of the current talker alert window.
+    pub talkers: TalkerCounters,
+
+    /// The unanswered TCP SYNs of the scan detection window.
+    pub syns: SynTracker,
+
+    /// Traffic counters of each pair of hosts seen in the capture.
+    pub conversations: Conversations,
+
+    /// The index of the latest packet, counted from 1. For `capture` it is the
+    /// frame number of the packet in the current capture file.
+    pub packet_index: u64,
+
+    /// The capture timestamp of the latest packet, in microseconds since the epoch.
+    pub last_timestamp: Option<i64>,
+
+    /// The time between the latest packet and the one before it, in microseconds.
+    /// Zero for the first packet.
+    pub packet_delta: i64,
+
+    /// The indices and deltas of the packets still being decoded on the parse
+    /// pool, oldest first, as their decoded forms are emitted in the same order.
+    pub pool_packets: VecDeque<(u64, i64)>,
+
+    /// The capture file that packets failing to decode are saved to, if requested.
+    pub errors_file: Option<PcapWriter<File>>,
+
+    /// Whether the user has been advised to increase the snaplen after a
+    /// truncated packet failed to decode.
+    pub truncation_reported: bool,
+
+    /// Packets skipped because they were captured outside the active hours.
+    pub inactive_packets: u64,
+
+    /// Whether the last packet was captured outside the active hours.
+    pub idle: bool,
+
+    /// The latest packet counts of libpcap, including the packets it dropped.
+    pub capture_stats: Option<Stat>,
+}
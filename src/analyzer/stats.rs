@@ -0,0 +1,77 @@
+use super::{
+    encapsulation::{self, ETHER_TYPE_IPV4, ETHER_TYPE_IPV6},
+    flows::{IP_PROTOCOL_TCP, IP_PROTOCOL_UDP},
+};
+
+pub const IP_PROTOCOL_ICMP: u8 = 1;
+pub const IP_PROTOCOL_ICMPV6: u8 = 58;
+
+/// The kind of destination a frame is addressed to, from its destination MAC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CastType {
+    Unicast,
+    Multicast,
+    Broadcast,
+}
+
+impl CastType {
+    /// Classifies a raw Ethernet frame by its destination MAC address: the
+    /// all-ones address is broadcast, otherwise the I/G bit of the first octet
+    /// distinguishes multicast from unicast.
+    pub fn of_frame(frame: &[u8]) -> Option<Self> {
+        let destination = encapsulation::read_mac(frame, 0)?;
+
+        if destination == [0xff; 6] {
+            Some(Self::Broadcast)
+        } else if destination[0] & 0x01 != 0 {
+            Some(Self::Multicast)
+        } else {
+            Some(Self::Unicast)
+        }
+    }
+}
+
+/// Packet counts by protocol and the total volume of a capture.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    pub packets: u64,
+    pub bytes: u64,
+    pub ipv4: u64,
+    pub ipv6: u64,
+    pub tcp: u64,
+    pub udp: u64,
+    pub icmp: u64,
+    /// Packets that are neither TCP, UDP nor ICMP.
+    pub other: u64,
+    pub unicast: u64,
+    pub multicast: u64,
+    pub broadcast: u64,
+}
+
+impl Stats {
+    /// Accounts a raw Ethernet frame to its protocol counters.
+    pub fn record(&mut self, frame: &[u8]) {
+        self.packets += 1;
+        self.bytes += frame.len() as u64;
+
+        match CastType::of_frame(frame) {
+            Some(CastType::Unicast) => self.unicast += 1,
+            Some(CastType::Multicast) => self.multicast += 1,
+            Some(CastType::Broadcast) => self.broadcast += 1,
+            None => {}
+        }
+
+        match encapsulation::ether_type(frame) {
+            Some(ETHER_TYPE_IPV4) => self.ipv4 += 1,
+            Some(ETHER_TYPE_IPV6) => self.ipv6 += 1,
+            _ => {}
+        }
+
+        match encapsulation::parse_ip(frame).map(|(header, _)| header.protocol) {
+            Some(IP_PROTOCOL_TCP) => self.tcp += 1,
+            Some(IP_PROTOCOL_UDP) => self.udp += 1,
+            Some(IP_PROTOCOL_ICMP | IP_PROTOCOL_ICMPV6) => self.icmp += 1,
+            _ => self.other += 1,
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use super::{
+    definitions::{TalkerAlert, TalkerMetric},
+    encapsulation,
+    flows::Counter,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
+    time::Instant,
+};
+
+/// A host that exceeded the talker alert threshold within the current window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TalkerExceeded {
+    pub host: IpAddr,
+    pub counter: Counter,
+}
+
+/// Per-host traffic counters over fixed windows, used to alert on top talkers
+/// while a capture is running.
+///
+/// Counters are reset at the start of each window, so the table only holds the
+/// hosts seen within the current window. A host is reported at most once per window.
+#[derive(Debug, Default)]
+pub struct TalkerCounters {
+    window_start: Option<Instant>,
+    hosts: HashMap<IpAddr, Counter>,
+    alerted: HashSet<IpAddr>,
+}
+
+impl TalkerCounters {
+    /// Accounts a raw Ethernet frame to the host that sent it.
+    ///
+    /// # Arguments
+    /// * `frame` - The raw bytes of the captured Ethernet frame.
+    /// * `alert` - The threshold and window to check the sender against.
+    /// * `now` - The time the frame was seen.
+    ///
+    /// # Returns
+    /// `Some(TalkerExceeded)` the first time the sender exceeds the threshold within
+    /// the current window, `None` otherwise.
+    pub fn observe(
+        &mut self,
+        frame: &[u8],
+        alert: &TalkerAlert,
+        now: Instant,
+    ) -> Option<TalkerExceeded> {
+        let (header, _) = encapsulation::parse_ip(frame)?;
+
+        match self.window_start {
+            Some(start) if now.duration_since(start) < alert.window => {}
+            _ => {
+                self.window_start = Some(now);
+                self.hosts.clear();
+                self.alerted.clear();
+            }
+        }
+
+        let host = header.source_address;
+        let counter = self.hosts.entry(host).or_default();
+        counter.add(frame.len());
+
+        let value = match alert.metric {
+            TalkerMetric::Bytes => counter.bytes,
+            TalkerMetric::Packets => counter.packets,
+        };
+
+        if value <= alert.threshold || !self.alerted.insert(host) {
+            return None;
+        }
+
+        Some(TalkerExceeded {
+            host,
+            counter: *counter,
+        })
+    }
+}
+
+/// Traffic counters of each pair of hosts over a whole capture, used to list the
+/// conversations that carried the most traffic once it ends.
+///
+/// Both directions between two hosts are counted together, under the pair with
+/// the lower address first.
+#[derive(Debug, Default)]
+pub struct Conversations {
+    pairs: HashMap<(IpAddr, IpAddr), Counter>,
+}
+
+impl Conversations {
+    /// Accounts a raw Ethernet frame to the pair of hosts it was sent between.
+    /// Non-IP frames are ignored.
+    pub fn record(&mut self, frame: &[u8]) {
+        let Some((header, _)) = encapsulation::parse_ip(frame) else {
+            return;
+        };
+
+        let (source, destination) = (header.source_address, header.destination_address);
+        let pair = if source <= destination {
+            (source, destination)
+        } else {
+            (destination, source)
+        };
+
+        self.pairs.entry(pair).or_default().add(frame.len());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Returns the `n` pairs of hosts that exchanged the most bytes, largest first.
+    pub fn top(&self, n: usize) -> Vec<(&(IpAddr, IpAddr), &Counter)> {
+        let mut pairs = self.pairs.iter().collect::<Vec<_>>();
+        pairs.sort_by(|x, y| y.1.bytes.cmp(&x.1.bytes));
+        pairs.truncate(n);
+        pairs
+    }
+}
@@ -0,0 +1,79 @@
+use super::encapsulation::read_u16;
+
+const CONTENT_TYPE_HANDSHAKE: u8 = 0x16;
+const HANDSHAKE_CLIENT_HELLO: u8 = 0x01;
+const EXTENSION_SERVER_NAME: u16 = 0x0000;
+const SERVER_NAME_HOST_NAME: u8 = 0x00;
+
+/// The content type, version and length of a TLS record.
+const RECORD_HEADER_LEN: usize = 5;
+/// The handshake type and 24-bit length of a handshake message.
+const HANDSHAKE_HEADER_LEN: usize = 4;
+/// The client version and random that start a ClientHello.
+const CLIENT_HELLO_FIXED_LEN: usize = 34;
+
+/// Extracts the Server Name Indication of a TLS ClientHello, the hostname a
+/// client is connecting to, which is sent before encryption starts.
+///
+/// Only a ClientHello that starts the payload and fits in it is parsed; one split
+/// across several segments is not reassembled.
+///
+/// # Arguments
+/// * `payload` - The payload of a TCP segment.
+///
+/// # Returns
+/// `Some(String)` with the first host name of the SNI extension, or `None` if the
+/// payload is not a ClientHello or has no SNI.
+pub fn client_hello_sni(payload: &[u8]) -> Option<String> {
+    if *payload.first()? != CONTENT_TYPE_HANDSHAKE {
+        return None;
+    }
+
+    let handshake = payload.get(RECORD_HEADER_LEN..)?;
+    if *handshake.first()? != HANDSHAKE_CLIENT_HELLO {
+        return None;
+    }
+
+    // Skip the fixed fields, then the session ID, cipher suites and compression methods
+    let mut position = HANDSHAKE_HEADER_LEN + CLIENT_HELLO_FIXED_LEN;
+    position += 1 + usize::from(*handshake.get(position)?);
+    position += 2 + usize::from(read_u16(handshake, position)?);
+    position += 1 + usize::from(*handshake.get(position)?);
+
+    let extensions_len = usize::from(read_u16(handshake, position)?);
+    let extensions = handshake.get(position + 2..position + 2 + extensions_len)?;
+
+    let mut position = 0;
+    while position < extensions.len() {
+        let extension_type = read_u16(extensions, position)?;
+        let len = usize::from(read_u16(extensions, position + 2)?);
+        let data = extensions.get(position + 4..position + 4 + len)?;
+
+        if extension_type == EXTENSION_SERVER_NAME {
+            return server_name(data);
+        }
+        position += 4 + len;
+    }
+
+    None
+}
+
+/// Reads the first host name of the server name list of an SNI extension.
+fn server_name(data: &[u8]) -> Option<String> {
+    let list_len = usize::from(read_u16(data, 0)?);
+    let list = data.get(2..2 + list_len)?;
+
+    let mut position = 0;
+    while position < list.len() {
+        let name_type = *list.get(position)?;
+        let len = usize::from(read_u16(list, position + 1)?);
+        let name = list.get(position + 3..position + 3 + len)?;
+
+        if name_type == SERVER_NAME_HOST_NAME {
+            return Some(String::from_utf8_lossy(name).into_owned());
+        }
+        position += 3 + len;
+    }
+
+    None
+}
@@ -0,0 +1,67 @@
+use super::encapsulation::read_u16;
+use std::borrow::Cow;
+
+pub const ETHER_TYPE_VLAN: u16 = 0x8100;
+/// The EtherType of the outer tag of a QinQ (802.1ad) frame.
+pub const ETHER_TYPE_QINQ: u16 = 0x88a8;
+
+/// The offset of the EtherType, or of the first VLAN tag, in an Ethernet frame.
+const ETHER_TYPE_OFFSET: usize = 12;
+const VLAN_TAG_LEN: usize = 4;
+
+/// An 802.1Q VLAN tag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VlanTag {
+    /// The Priority Code Point, the frame's class of service.
+    pub pcp: u8,
+    /// The Drop Eligible Indicator.
+    pub dei: bool,
+    /// The 12-bit VLAN identifier.
+    pub vid: u16,
+}
+
+impl VlanTag {
+    /// Parses the Tag Control Information that follows a tag's EtherType.
+    fn from_tci(tci: u16) -> Self {
+        Self {
+            pcp: (tci >> 13) as u8,
+            dei: tci & 0x1000 != 0,
+            vid: tci & 0x0fff,
+        }
+    }
+}
+
+/// Removes the VLAN tags of a raw Ethernet frame, including the stacked tags of
+/// QinQ frames, so that the frame can be parsed like an untagged one.
+///
+/// # Arguments
+/// * `frame` - The raw bytes of an Ethernet frame.
+///
+/// # Returns
+/// The tags, outermost first, along with the frame rebuilt from its addresses and
+/// the inner EtherType. Untagged frames, and frames whose tags are cut short, are
+/// returned unchanged without tags.
+pub fn strip_tags(frame: &[u8]) -> (Vec<VlanTag>, Cow<'_, [u8]>) {
+    let mut tags = Vec::new();
+    let mut offset = ETHER_TYPE_OFFSET;
+
+    while let Some(ETHER_TYPE_VLAN | ETHER_TYPE_QINQ) = read_u16(frame, offset) {
+        // The inner EtherType must follow the tag
+        let tci = match (read_u16(frame, offset + 2), read_u16(frame, offset + 4)) {
+            (Some(tci), Some(_)) => tci,
+            _ => return (Vec::new(), Cow::Borrowed(frame)),
+        };
+
+        tags.push(VlanTag::from_tci(tci));
+        offset += VLAN_TAG_LEN;
+    }
+
+    if tags.is_empty() {
+        return (tags, Cow::Borrowed(frame));
+    }
+
+    let mut untagged = Vec::with_capacity(frame.len() - (offset - ETHER_TYPE_OFFSET));
+    untagged.extend_from_slice(&frame[..ETHER_TYPE_OFFSET]);
+    untagged.extend_from_slice(&frame[offset..]);
+    (tags, Cow::Owned(untagged))
+}
@@ -0,0 +1,360 @@
+use super::encapsulation::{read_mac, read_u16, ETHERNET_HEADER_LEN};
+use pcap::Linktype;
+
+/// The version, padding, length and first present word of a radiotap header.
+const RADIOTAP_MIN_HEADER_LEN: usize = 8;
+/// Set in a present word when another present word follows it.
+const RADIOTAP_PRESENT_EXTENDED: u32 = 1 << 31;
+
+/// The radiotap fields up to the antenna signal, as (present bit, size, alignment).
+const RADIOTAP_FIELD_TSFT: (u32, usize, usize) = (0, 8, 8);
+const RADIOTAP_FIELD_FLAGS: (u32, usize, usize) = (1, 1, 1);
+const RADIOTAP_FIELD_RATE: (u32, usize, usize) = (2, 1, 1);
+const RADIOTAP_FIELD_CHANNEL: (u32, usize, usize) = (3, 4, 2);
+const RADIOTAP_FIELD_FHSS: (u32, usize, usize) = (4, 2, 1);
+const RADIOTAP_FIELD_SIGNAL: (u32, usize, usize) = (5, 1, 1);
+
+/// The frame control, duration and first three addresses of an 802.11 header.
+const MAC_HEADER_LEN: usize = 24;
+const QOS_CONTROL_LEN: usize = 2;
+
+pub const FRAME_TYPE_MANAGEMENT: u8 = 0;
+pub const FRAME_TYPE_CONTROL: u8 = 1;
+pub const FRAME_TYPE_DATA: u8 = 2;
+
+const FLAG_TO_DS: u8 = 0x01;
+const FLAG_FROM_DS: u8 = 0x02;
+const FLAG_PROTECTED: u8 = 0x40;
+
+/// The LLC/SNAP header that precedes the EtherType of unencrypted data frames.
+const LLC_SNAP_HEADER: [u8; 6] = [0xaa, 0xaa, 0x03, 0x00, 0x00, 0x00];
+
+/// Returns `true` for the datalink types of 802.11 captures, with or without the
+/// radiotap header added by monitor-mode interfaces.
+pub fn is_wifi(linktype: Linktype) -> bool {
+    linktype == Linktype::IEEE802_11 || linktype == Linktype::IEEE802_11_RADIOTAP
+}
+
+/// The radio information of a radiotap header.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Radiotap {
+    /// The channel frequency in MHz.
+    pub frequency: Option<u16>,
+    /// The antenna signal in dBm.
+    pub signal: Option<i8>,
+}
+
+impl Radiotap {
+    /// Parses a radiotap header, whose fields follow the present words, each
+    /// aligned to its natural size from the start of the header.
+    ///
+    /// # Returns
+    /// The radio information along with the length of the header, or `None` if the
+    /// header is cut short.
+    fn from_bytes(frame: &[u8]) -> Option<(Self, usize)> {
+        let len = usize::from(u16::from_le_bytes([*frame.get(2)?, *frame.get(3)?]));
+        let header = frame
+            .get(..len)
+            .filter(|_| len >= RADIOTAP_MIN_HEADER_LEN)?;
+
+        let present = read_u32_le(header, 4)?;
+        let mut offset = RADIOTAP_MIN_HEADER_LEN;
+        let mut word = present;
+        while word & RADIOTAP_PRESENT_EXTENDED != 0 {
+            word = read_u32_le(header, offset)?;
+            offset += 4;
+        }
+
+        let mut radiotap = Self::default();
+        for (bit, size, align) in [
+            RADIOTAP_FIELD_TSFT,
+            RADIOTAP_FIELD_FLAGS,
+            RADIOTAP_FIELD_RATE,
+            RADIOTAP_FIELD_CHANNEL,
+            RADIOTAP_FIELD_FHSS,
+            RADIOTAP_FIELD_SIGNAL,
+        ] {
+            if present & (1 << bit) == 0 {
+                continue;
+            }
+
+            offset = offset.next_multiple_of(align);
+            let field = match header.get(offset..offset + size) {
+                Some(field) => field,
+                None => break,
+            };
+
+            if bit == RADIOTAP_FIELD_CHANNEL.0 {
+                radiotap.frequency = Some(u16::from_le_bytes([field[0], field[1]]));
+            } else if bit == RADIOTAP_FIELD_SIGNAL.0 {
+                radiotap.signal = Some(field[0] as i8);
+            }
+            offset += size;
+        }
+
+        Some((radiotap, len))
+    }
+
+    /// The channel number of the frequency, e.g. 6 for 2437 MHz.
+    pub fn channel(&self) -> Option<u16> {
+        match self.frequency? {
+            2484 => Some(14),
+            frequency @ 2412..=2472 => Some((frequency - 2407) / 5),
+            frequency @ 5000..=5900 => Some((frequency - 5000) / 5),
+            frequency @ 5955..=7115 => Some((frequency - 5950) / 5),
+            _ => None,
+        }
+    }
+}
+
+/// An 802.11 frame, as captured by a monitor-mode interface.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WifiFrame<'a> {
+    /// The radiotap header, if the capture has one.
+    pub radiotap: Option<Radiotap>,
+    /// Management, control or data.
+    pub frame_type: u8,
+    pub subtype: u8,
+    /// The flags of the frame control field, such as To DS and From DS.
+    pub flags: u8,
+    /// The addresses of the header, of which control frames carry fewer than three.
+    pub addresses: Vec<[u8; 6]>,
+    /// The frame body following the header.
+    pub body: &'a [u8],
+}
+
+impl<'a> WifiFrame<'a> {
+    /// Parses the radiotap and 802.11 MAC headers of a frame.
+    ///
+    /// # Arguments
+    /// * `frame` - The raw bytes of the frame.
+    /// * `linktype` - The datalink type of the capture, with or without radiotap.
+    ///
+    /// # Returns
+    /// `Some(WifiFrame)` if the frame control field is complete, `None` otherwise.
+    pub fn from_bytes(frame: &'a [u8], linktype: Linktype) -> Option<Self> {
+        let (radiotap, frame) = if linktype == Linktype::IEEE802_11_RADIOTAP {
+            let (radiotap, len) = Radiotap::from_bytes(frame)?;
+            (Some(radiotap), &frame[len..])
+        } else {
+            (None, frame)
+        };
+
+        let frame_control = frame.first()?;
+        let flags = *frame.get(1)?;
+        let frame_type = (frame_control >> 2) & 0x03;
+        let subtype = frame_control >> 4;
+
+        // A fourth address is only present between two access points
+        let four_addresses =
+            frame_type == FRAME_TYPE_DATA && flags & FLAG_TO_DS != 0 && flags & FLAG_FROM_DS != 0;
+        let mut addresses = [4, 10, 16]
+            .into_iter()
+            .map_while(|offset| read_mac(frame, offset))
+            .collect::<Vec<[u8; 6]>>();
+
+        let mut header_len = MAC_HEADER_LEN;
+        if four_addresses {
+            addresses.extend(read_mac(frame, MAC_HEADER_LEN));
+            header_len += 6;
+        }
+        // QoS data subtypes carry a QoS control field
+        if frame_type == FRAME_TYPE_DATA && subtype & 0x08 != 0 {
+            header_len += QOS_CONTROL_LEN;
+        }
+
+        Some(Self {
+            radiotap,
+            frame_type,
+            subtype,
+            flags,
+            addresses,
+            body: frame.get(header_len..).unwrap_or_default(),
+        })
+    }
+
+    /// Returns `true` if the body of the frame is encrypted.
+    pub fn is_protected(&self) -> bool {
+        self.flags & FLAG_PROTECTED != 0
+    }
+
+    /// The destination and source addresses of a data frame, which depend on
+    /// whether it is sent to or from the distribution system.
+    fn data_addresses(&self) -> Option<([u8; 6], [u8; 6])> {
+        let address = |index: usize| self.addresses.get(index).copied();
+
+        match (self.flags & FLAG_TO_DS != 0, self.flags & FLAG_FROM_DS != 0) {
+            (false, false) => Some((address(0)?, address(1)?)),
+            (true, false) => Some((address(2)?, address(1)?)),
+            (false, true) => Some((address(0)?, address(2)?)),
+            (true, true) => Some((address(2)?, address(3)?)),
+        }
+    }
+
+    /// Rebuilds the Ethernet frame carried by an unencrypted data frame, with the
+    /// destination and source addresses of the 802.11 header, so that it can be
+    /// tracked and decoded like a captured Ethernet frame.
+    ///
+    /// # Returns
+    /// `Some(Vec<u8>)` for data frames with an LLC/SNAP header, `None` for other
+    /// frames and for encrypted data frames, whose body cannot be read.
+    pub fn to_ethernet(&self) -> Option<Vec<u8>> {
+        if self.frame_type != FRAME_TYPE_DATA || self.is_protected() {
+            return None;
+        }
+
+        let (destination, source) = self.data_addresses()?;
+        if self.body.get(..LLC_SNAP_HEADER.len())? != LLC_SNAP_HEADER {
+            return None;
+        }
+        let ether_type = read_u16(self.body, LLC_SNAP_HEADER.len())?;
+        let payload = &self.body[LLC_SNAP_HEADER.len() + 2..];
+
+        let mut frame = Vec::with_capacity(ETHERNET_HEADER_LEN + payload.len());
+        frame.extend_from_slice(&destination);
+        frame.extend_from_slice(&source);
+        frame.extend_from_slice(&ether_type.to_be_bytes());
+        frame.extend_from_slice(payload);
+        Some(frame)
+    }
+}
+
+/// Returns the name of an 802.11 frame type and subtype, e.g. `Beacon`.
+pub fn frame_name(frame_type: u8, subtype: u8) -> String {
+    let name = match (frame_type, subtype) {
+        (FRAME_TYPE_MANAGEMENT, 0) => "Association Request",
+        (FRAME_TYPE_MANAGEMENT, 1) => "Association Response",
+        (FRAME_TYPE_MANAGEMENT, 2) => "Reassociation Request",
+        (FRAME_TYPE_MANAGEMENT, 3) => "Reassociation Response",
+        (FRAME_TYPE_MANAGEMENT, 4) => "Probe Request",
+        (FRAME_TYPE_MANAGEMENT, 5) => "Probe Response",
+        (FRAME_TYPE_MANAGEMENT, 8) => "Beacon",
+        (FRAME_TYPE_MANAGEMENT, 10) => "Disassociation",
+        (FRAME_TYPE_MANAGEMENT, 11) => "Authentication",
+        (FRAME_TYPE_MANAGEMENT, 12) => "Deauthentication",
+        (FRAME_TYPE_MANAGEMENT, 13) => "Action",
+        (FRAME_TYPE_CONTROL, 8) => "Block Ack Request",
+        (FRAME_TYPE_CONTROL, 9) => "Block Ack",
+        (FRAME_TYPE_CONTROL, 10) => "PS-Poll",
+        (FRAME_TYPE_CONTROL, 11) => "RTS",
+        (FRAME_TYPE_CONTROL, 12) => "CTS",
+        (FRAME_TYPE_CONTROL, 13) => "ACK",
+        (FRAME_TYPE_DATA, 0) => "Data",
+        (FRAME_TYPE_DATA, 4) => "Null",
+        (FRAME_TYPE_DATA, 8) => "QoS Data",
+        (FRAME_TYPE_DATA, 12) => "QoS Null",
+        (FRAME_TYPE_MANAGEMENT, subtype) => return format!("Management ({})", subtype),
+        (FRAME_TYPE_CONTROL, subtype) => return format!("Control ({})", subtype),
+        (FRAME_TYPE_DATA, subtype) => return format!("Data ({})", subtype),
+        (frame_type, subtype) => return format!("Type {} ({})", frame_type, subtype),
+    };
+
+    name.to_string()
+}
+
+/// Reads a little-endian `u32` at `offset`, as radiotap fields are stored.
+fn read_u32_le(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A radiotap header with the flags, channel and antenna signal fields, for
+    /// channel 6 (2437 MHz) at -42 dBm.
+    const RADIOTAP_HEADER: [u8; 15] = [
+        0x00, 0x00, 0x0f, 0x00, // version, padding and length
+        0x2a, 0x00, 0x00, 0x00, // present: flags, channel, signal
+        0x00, // flags
+        0x00, // padding to align the channel
+        0x85, 0x09, 0xa0, 0x00, // channel frequency and flags
+        0xd6, // antenna signal
+    ];
+
+    /// A QoS data frame from the distribution system, carrying an IPv4 packet from
+    /// 66:77:88:99:aa:bb to 00:11:22:33:44:55 through the access point
+    /// 02:00:00:00:00:01.
+    fn qos_data_frame(flags: u8) -> Vec<u8> {
+        let mut frame = vec![
+            0x88, flags, 0x00, 0x00, // frame control and duration
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, // receiver, the destination
+            0x02, 0x00, 0x00, 0x00, 0x00, 0x01, // transmitter, the access point
+            0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, // source
+            0x10, 0x00, // sequence control
+            0x00, 0x00, // QoS control
+        ];
+        frame.extend_from_slice(&LLC_SNAP_HEADER);
+        frame.extend_from_slice(&[0x08, 0x00, 0x45, 0x00]);
+        frame
+    }
+
+    #[test]
+    fn parses_radiotap_header() {
+        let mut packet = RADIOTAP_HEADER.to_vec();
+        packet.extend(qos_data_frame(FLAG_FROM_DS));
+
+        let frame = WifiFrame::from_bytes(&packet, Linktype::IEEE802_11_RADIOTAP).unwrap();
+        let radiotap = frame.radiotap.unwrap();
+
+        assert_eq!(radiotap.frequency, Some(2437));
+        assert_eq!(radiotap.channel(), Some(6));
+        assert_eq!(radiotap.signal, Some(-42));
+        assert_eq!(frame_name(frame.frame_type, frame.subtype), "QoS Data");
+    }
+
+    #[test]
+    fn rebuilds_ethernet_frame_of_data_frame() {
+        let packet = qos_data_frame(FLAG_FROM_DS);
+        let frame = WifiFrame::from_bytes(&packet, Linktype::IEEE802_11).unwrap();
+
+        assert_eq!(
+            frame.to_ethernet().unwrap(),
+            [
+                0x00, 0x11, 0x22, 0x33, 0x44, 0x55, // destination
+                0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, // source
+                0x08, 0x00, 0x45, 0x00,
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_fourth_address_between_access_points() {
+        let mut packet = qos_data_frame(FLAG_TO_DS | FLAG_FROM_DS);
+        // The fourth address, the source, follows the sequence control field
+        let source = [0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f];
+        let qos_and_body = packet.split_off(24);
+        packet.extend_from_slice(&source);
+        packet.extend(qos_and_body);
+
+        let frame = WifiFrame::from_bytes(&packet, Linktype::IEEE802_11).unwrap();
+        let ethernet = frame.to_ethernet().unwrap();
+
+        assert_eq!(frame.addresses.len(), 4);
+        assert_eq!(ethernet[..6], [0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb]);
+        assert_eq!(ethernet[6..12], source);
+    }
+
+    #[test]
+    fn does_not_rebuild_protected_or_management_frames() {
+        let packet = qos_data_frame(FLAG_FROM_DS | FLAG_PROTECTED);
+        let frame = WifiFrame::from_bytes(&packet, Linktype::IEEE802_11).unwrap();
+        assert!(frame.is_protected());
+        assert_eq!(frame.to_ethernet(), None);
+
+        let mut beacon = qos_data_frame(0);
+        beacon[0] = 0x80;
+        let frame = WifiFrame::from_bytes(&beacon, Linktype::IEEE802_11).unwrap();
+        assert_eq!(frame_name(frame.frame_type, frame.subtype), "Beacon");
+        assert_eq!(frame.to_ethernet(), None);
+    }
+
+    #[test]
+    fn rejects_cut_radiotap_header() {
+        assert_eq!(
+            WifiFrame::from_bytes(&RADIOTAP_HEADER[..10], Linktype::IEEE802_11_RADIOTAP),
+            None
+        );
+    }
+}
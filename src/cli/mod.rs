@@ -1,18 +1,87 @@
 pub mod subcommands;
 
-use crate::analyzer::{analyzer::Analyzer, pcap_interface::PcapInterface};
-use clap::{Parser, Subcommand};
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand};
+use log::{error, info, LevelFilter};
+#[cfg(feature = "geoip")]
+use sniff_rs::analyzer::geoip::GeoIp;
+use sniff_rs::analyzer::{
+    analyzer::Analyzer,
+    definitions::{
+        CaptureLimits, CaptureOptions, CaptureSchedule, DecodeLevel, FileFormat, OutputFormat,
+        ParseOptions, SaveTarget, ScanThresholds, StreamOptions,
+    },
+    error::AnalyzerError,
+    filter::PacketFilter,
+    flows::Endpoint,
+    pcap_interface::PcapInterface,
+};
+use sniff_rs::logger;
+use std::{
+    io::{self, IsTerminal},
+    path::PathBuf,
+    process,
+    time::Duration,
+};
+#[cfg(feature = "geoip")]
+use std::{path::Path, sync::Arc};
 use subcommands::*;
 
+/// Exit code when the network interfaces could not be enumerated.
+const EXIT_LOOKUP_FAILED: i32 = 1;
+
+/// Exit code when enumeration succeeded but found no interface.
+const EXIT_NO_INTERFACE_FOUND: i32 = 2;
+
+/// Exit code of a `--strict` capture that saved no packets.
+const EXIT_NO_PACKETS_CAPTURED: i32 = 3;
+
+/// Exit code when no subcommand is given, matching clap's usage errors.
+const EXIT_USAGE: i32 = 2;
+
+/// Optional Cargo features, reported by `--version --verbose`.
+const OPTIONAL_FEATURES: [(&str, bool); 4] = [
+    ("pcapng", cfg!(feature = "pcapng")),
+    ("geoip", cfg!(feature = "geoip")),
+    ("tui", cfg!(feature = "tui")),
+    ("async", cfg!(feature = "async")),
+];
+
 #[derive(Debug, Parser)]
 #[clap(
     name = "wyre",
     author = "0xphen",
-    version
+    version,
+    disable_version_flag = true,
+    args_conflicts_with_subcommands = true
 )]
 struct Arguments {
+    /// Print version
+    #[clap(short = 'V', long)]
+    version: bool,
+
+    /// With --version, also print the libpcap version and the compiled-in features
+    #[clap(long, requires = "version")]
+    verbose: bool,
+
+    /// Also write the log to this file, without color codes
+    #[clap(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
+    /// The most detailed level to log: off, error, warn, info, debug or trace
+    #[clap(long, global = true, value_name = "LEVEL")]
+    #[clap(conflicts_with_all = ["quiet", "trace"])]
+    log_level: Option<LevelFilter>,
+
+    /// Only log warnings and errors, same as --log-level warn
+    #[clap(short, long, global = true, conflicts_with = "trace")]
+    quiet: bool,
+
+    /// Log everything, including trace output, same as --log-level trace
+    #[clap(short = 'v', global = true)]
+    trace: bool,
+
     #[clap(subcommand)]
-    sub: Subcommands,
+    sub: Option<Subcommands>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -28,6 +97,12 @@ enum Subcommands {
     )]
     Interfaces(InterfacesArgs),
 
+    #[clap(
+        name = "datalinks",
+        about = "List the datalink types an interface supports"
+    )]
+    Datalinks(DatalinksArgs),
+
     #[clap(
         name = "capture",
         about = "Capture network packets and save in a .pcap file"
@@ -35,36 +110,460 @@ enum Subcommands {
     BasicCapture(BasicCaptureArgs),
     #[clap(name = "stream", about = "Captures and live streams network packets")]
     LiveStream(LiveStreamArgs),
+
+    #[clap(name = "analyze", about = "Decode packets from a .pcap file")]
+    Analyze(AnalyzeArgs),
+
+    #[clap(name = "replay", about = "Replay the packets of a saved .pcap file")]
+    Replay(ReplayArgs),
+
+    #[clap(
+        name = "extract",
+        about = "Extract transferred files from the streams of a .pcap file"
+    )]
+    Extract(ExtractArgs),
+
+    #[clap(
+        name = "follow",
+        about = "Reassemble and print a TCP connection of a .pcap file"
+    )]
+    Follow(FollowArgs),
+}
+
+/// Prints the version, and with `verbose` the details needed to triage
+/// platform-specific capture issues.
+fn print_version(verbose: bool) {
+    println!("wyre {}", env!("CARGO_PKG_VERSION"));
+
+    if !verbose {
+        return;
+    }
+
+    let features = OPTIONAL_FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect::<Vec<&str>>();
+
+    println!("libpcap: {}", PcapInterface::lib_version());
+    println!(
+        "features: {}",
+        if features.is_empty() {
+            "none".to_string()
+        } else {
+            features.join(", ")
+        }
+    );
+}
+
+/// The file name that writes a capture to stdout as a pcap stream.
+const STDOUT_FILE_NAME: &str = "-";
+
+/// Returns `true` if the subcommand writes data to stdout, either a pcap stream
+/// or the packets decoded as JSON or CSV, which then cannot carry log output.
+fn writes_to_stdout(sub: &Subcommands) -> bool {
+    match sub {
+        Subcommands::BasicCapture(args) => {
+            args.format != OutputFormat::Text
+                || (args.fifo.is_none()
+                    && args.ring_file.is_none()
+                    && args.file_name.as_deref() == Some(STDOUT_FILE_NAME))
+        }
+        Subcommands::LiveStream(args) => args.format != OutputFormat::Text,
+        Subcommands::Analyze(args) => args.format != OutputFormat::Text,
+        _ => false,
+    }
+}
+
+/// Resolves the log level from `--log-level`, `-q` and `-v`, which clap keeps
+/// mutually exclusive, defaulting to debug.
+fn log_level(args: &Arguments) -> LevelFilter {
+    match args.log_level {
+        Some(level) => level,
+        None if args.quiet => LevelFilter::Warn,
+        None if args.trace => LevelFilter::Trace,
+        None => LevelFilter::Debug,
+    }
+}
+
+/// Resolves the decode level, where `--no-transport` is a shorthand for `summary`.
+fn decode_level(no_transport: bool, decode_level: DecodeLevel) -> DecodeLevel {
+    if no_transport {
+        DecodeLevel::Summary
+    } else {
+        decode_level
+    }
+}
+
+/// Resolves the decode level of a capture, which includes the transport layer
+/// whenever a preset is used, as the application decoders run on top of it.
+fn preset_decode_level(preset: Option<Preset>, decode_level: DecodeLevel) -> DecodeLevel {
+    match (preset, decode_level) {
+        (Some(_), DecodeLevel::Summary) => DecodeLevel::Standard,
+        (_, decode_level) => decode_level,
+    }
+}
+
+/// Combines the BPF expression of a preset with the one given by `--filter`, so
+/// that packets must match both.
+fn preset_filter(preset: Option<Preset>, filter: Option<String>) -> Option<String> {
+    match (preset, filter) {
+        (Some(preset), Some(filter)) => Some(format!("({}) and ({})", preset.filter(), filter)),
+        (Some(preset), None) => Some(preset.filter().to_string()),
+        (None, filter) => filter,
+    }
+}
+
+/// Opens the GeoIP database given by `--geoip`, exiting with an error if it
+/// cannot be read.
+#[cfg(feature = "geoip")]
+fn open_geoip(path: Option<&Path>) -> Option<Arc<GeoIp>> {
+    let path = path?;
+    match GeoIp::open(path) {
+        Ok(geoip) => Some(Arc::new(geoip)),
+        Err(err) => Arguments::command()
+            .error(ErrorKind::Io, format!("{}: {}", path.display(), err))
+            .exit(),
+    }
+}
+
+/// Resolves the payload length shown with `--payload`, or by a preset that shows
+/// payloads.
+fn payload_len(payload: bool, payload_len: usize, preset: Option<Preset>) -> Option<usize> {
+    (payload || preset.is_some_and(|preset| preset.shows_payload())).then_some(payload_len)
+}
+
+/// Exits with a usage error if `--only-errors` or `--errors-file` is given with
+/// `--format csv`, whose rows are written without decoding the packets, so none
+/// would ever fail.
+fn check_errors_format(options: &ParseOptions) {
+    if options.format == OutputFormat::Csv && (options.only_errors || options.errors_file.is_some())
+    {
+        Arguments::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--only-errors and --errors-file cannot be used with --format csv",
+            )
+            .exit();
+    }
 }
 
 pub fn run() {
     let args = Arguments::parse();
-    match args.sub {
+    if args.version {
+        print_version(args.verbose);
+        return;
+    }
+
+    let level = log_level(&args);
+    let sub = match args.sub {
+        Some(sub) => sub,
+        None => {
+            let _ = Arguments::command().print_help();
+            process::exit(EXIT_USAGE);
+        }
+    };
+
+    logger::log::setup(writes_to_stdout(&sub), args.log_file.as_deref(), level)
+        .expect("failed to initialize logger.");
+
+    // Packet lines are only colored on a terminal, and the log file is kept free
+    // of color codes
+    let color_console = io::stdout().is_terminal() && args.log_file.is_none();
+
+    match sub {
         Subcommands::Interfaces(interface_args) => {
-            if interface_args.list_option == List::All {
-                Analyzer::list_interfaces();
+            let result = if interface_args.list_option == List::All {
+                Analyzer::list_interfaces(interface_args.json)
             } else {
-                Analyzer::show_default_interface();
+                Analyzer::show_default_interface(interface_args.json)
+            };
+
+            match result {
+                Ok(()) => {}
+                Err(AnalyzerError::NoInterfaceFound) => {
+                    error!("No interface found");
+                    process::exit(EXIT_NO_INTERFACE_FOUND);
+                }
+                Err(_) => process::exit(EXIT_LOOKUP_FAILED),
+            }
+        }
+        Subcommands::Datalinks(args) => {
+            if let Err(err) = Analyzer::list_datalinks(&args.interface, args.json) {
+                error!("{:?}", err.to_string());
+                match err {
+                    AnalyzerError::NoInterfaceFound => process::exit(EXIT_NO_INTERFACE_FOUND),
+                    _ => process::exit(EXIT_LOOKUP_FAILED),
+                }
             }
         }
         Subcommands::BasicCapture(mut capture_args) => {
-            // if the user has not specified an interface, use the default
+            // if the user has not specified an interface, use the best one that is up
             if capture_args.interface.as_str() == "" {
-                match PcapInterface::default_interface() {
-                    Ok(Some(device)) => capture_args.interface = device.name,
-                    _ => panic!("Device not specified. Unable to get default device."),
+                match PcapInterface::best_interface() {
+                    Ok(Some(device)) => {
+                        info!("No interface given, capturing on {}", device.name);
+                        capture_args.interface = device.name;
+                    }
+                    Ok(None) => {
+                        error!("No interface given and no interface found");
+                        process::exit(EXIT_NO_INTERFACE_FOUND);
+                    }
+                    Err(err) => {
+                        error!("Failed to list interfaces {:?}", err.to_string());
+                        process::exit(EXIT_LOOKUP_FAILED);
+                    }
                 }
             }
 
-            Analyzer::basic_capture(
-                &capture_args.dir_name,
-                &capture_args.file_name,
-                capture_args.size,
+            let options = ParseOptions {
+                decode_level: preset_decode_level(
+                    capture_args.preset,
+                    decode_level(capture_args.no_transport, capture_args.decode_level),
+                ),
+                hex: capture_args.hex,
+                payload: payload_len(
+                    capture_args.payload,
+                    capture_args.payload_len,
+                    capture_args.preset,
+                ),
+                compact: capture_args.compact,
+                width: capture_args.width.map(|width| width as usize),
+                only_errors: capture_args.only_errors,
+                errors_file: capture_args.errors_file,
+                #[cfg(feature = "geoip")]
+                geoip: open_geoip(capture_args.geoip.as_deref()),
+                verify_checksums: capture_args.verify_checksums,
+                format: capture_args.format,
+                json_out: capture_args.json_out,
+                talker_alert: capture_args.talker_alert,
+                scan_detection: capture_args.detect_scans.then(|| ScanThresholds {
+                    ports: capture_args.scan_ports,
+                    syns: capture_args.syn_flood,
+                    window: capture_args.scan_window,
+                }),
+                top_talkers: capture_args.top,
+                parse_threads: capture_args.parse_threads,
+                ..Default::default()
+            };
+            check_errors_format(&options);
+
+            // A size given to --size works like --max-bytes, the lower one applying
+            let (packets, size_bytes) = match capture_args.size {
+                Some(Limit::Packets(packets)) => (packets, None),
+                Some(Limit::Bytes(bytes)) => (0, Some(bytes)),
+                None => (0, None),
+            };
+
+            let limits = CaptureLimits {
+                packets,
+                duration: capture_args.duration.map(Duration::from_secs),
+                bytes: size_bytes.into_iter().chain(capture_args.max_bytes).min(),
+                max_memory: capture_args.max_runtime_memory,
+            };
+
+            let schedule = CaptureSchedule {
+                runs: capture_args.repeat,
+                interval: capture_args.repeat_interval.unwrap_or_default(),
+                active_hours: capture_args.active_hours,
+                rotate: capture_args.rotate,
+            };
+
+            let capture_options = CaptureOptions {
+                promisc: capture_args.promisc,
+                filter: preset_filter(capture_args.preset, capture_args.filter),
+                snaplen: capture_args.snaplen,
+                buffer_size: capture_args.buffer_size,
+                immediate: capture_args.immediate,
+                timeout_ms: capture_args.read_timeout,
+                reconnect: capture_args.reconnect,
+                datalink: capture_args.datalink,
+                ..Default::default()
+            };
+
+            let target = match (capture_args.fifo, capture_args.ring_file) {
+                (Some(path), _) => SaveTarget::Fifo {
+                    path,
+                    mode: capture_args.fifo_mode,
+                },
+                // clap requires the size with the ring file
+                (None, Some(path)) => SaveTarget::Ring {
+                    path,
+                    size: capture_args.ring_size.unwrap_or_default(),
+                },
+                (None, None) => match (capture_args.dir_name, capture_args.file_name) {
+                    (_, Some(file_name)) if file_name == STDOUT_FILE_NAME => SaveTarget::Stdout,
+                    // clap requires the file name when no FIFO or ring file is given
+                    (Some(dir_name), file_name) => SaveTarget::File {
+                        dir_name,
+                        file_name: file_name.unwrap_or_default(),
+                        format: capture_args.file_format,
+                    },
+                    (None, _) => Arguments::command()
+                        .error(
+                            ErrorKind::MissingRequiredArgument,
+                            "--dir is required unless the capture is written to stdout with --file -",
+                        )
+                        .exit(),
+                },
+            };
+
+            // A second pcap stream or JSON output would corrupt the stream on stdout
+            if matches!(target, SaveTarget::Stdout)
+                && (schedule.runs > 1
+                    || schedule.rotate.is_some()
+                    || options.format != OutputFormat::Text)
+            {
+                Arguments::command()
+                    .error(
+                        ErrorKind::ArgumentConflict,
+                        "--file - cannot be used with --repeat, --rotate or --format json or csv",
+                    )
+                    .exit();
+            }
+
+            if matches!(target, SaveTarget::Stdout) && capture_args.file_format != FileFormat::Pcap
+            {
+                Arguments::command()
+                    .error(
+                        ErrorKind::ArgumentConflict,
+                        "--file - only writes pcap, not --file-format pcapng",
+                    )
+                    .exit();
+            }
+
+            let total_packets = Analyzer::basic_capture(
+                &target,
+                &limits,
+                &schedule,
                 &capture_args.interface,
+                &capture_options,
+                &options,
             );
+
+            if capture_args.strict && total_packets == 0 {
+                process::exit(EXIT_NO_PACKETS_CAPTURED);
+            }
         }
         Subcommands::LiveStream(args) => {
-            Analyzer::live_capture(&args.interface);
+            let options = ParseOptions {
+                decode_level: preset_decode_level(
+                    args.preset,
+                    decode_level(args.no_transport, args.decode_level),
+                ),
+                hex: args.hex,
+                payload: payload_len(args.payload, args.payload_len, args.preset),
+                compact: args.compact,
+                width: args.width.map(|width| width as usize),
+                only_errors: args.only_errors,
+                errors_file: args.errors_file,
+                color: color_console && !args.no_color,
+                #[cfg(feature = "geoip")]
+                geoip: open_geoip(args.geoip.as_deref()),
+                verify_checksums: args.verify_checksums,
+                filter: PacketFilter {
+                    src_ip: args.src_ip,
+                    dst_ip: args.dst_ip,
+                    port: args.port,
+                },
+                format: args.format,
+                talker_alert: args.talker_alert,
+                scan_detection: args.detect_scans.then(|| ScanThresholds {
+                    ports: args.scan_ports,
+                    syns: args.syn_flood,
+                    window: args.scan_window,
+                }),
+                top_talkers: args.top,
+                parse_threads: args.parse_threads,
+                ..Default::default()
+            };
+            check_errors_format(&options);
+
+            let stream_options = StreamOptions {
+                serve_http: args.serve_http,
+                max_memory: args.max_runtime_memory,
+                dashboard: args.dashboard,
+                metrics_addr: args.metrics_addr,
+                active_hours: args.active_hours,
+                stats_interval: args.stats_interval.map(Duration::from_secs),
+                stats_only: args.stats_only,
+            };
+
+            let capture_options = CaptureOptions {
+                promisc: args.promisc,
+                filter: preset_filter(args.preset, args.filter),
+                snaplen: args.snaplen,
+                buffer_size: args.buffer_size,
+                immediate: args.immediate,
+                timeout_ms: args.read_timeout,
+                reconnect: args.reconnect,
+                datalink: args.datalink,
+                ..Default::default()
+            };
+
+            Analyzer::live_capture(&args.interface, &capture_options, &options, &stream_options);
+        }
+        Subcommands::Analyze(args) => {
+            let options = ParseOptions {
+                decode_level: decode_level(args.no_transport, args.decode_level),
+                hex: args.hex,
+                payload: args.payload.then_some(args.payload_len),
+                compact: args.compact,
+                width: args.width.map(|width| width as usize),
+                only_errors: args.only_errors,
+                errors_file: args.errors_file,
+                #[cfg(feature = "geoip")]
+                geoip: open_geoip(args.geoip.as_deref()),
+                verify_checksums: args.verify_checksums,
+                filter: PacketFilter {
+                    src_ip: args.src_ip,
+                    dst_ip: args.dst_ip,
+                    port: args.port,
+                },
+                format: args.format,
+                top_talkers: args.top,
+                ..Default::default()
+            };
+            check_errors_format(&options);
+
+            Analyzer::analyze_files(&args.read, &options);
+        }
+        Subcommands::Replay(args) => {
+            let options = ParseOptions {
+                decode_level: decode_level(args.no_transport, args.decode_level),
+                hex: args.hex,
+                payload: args.payload.then_some(args.payload_len),
+                compact: args.compact,
+                width: args.width.map(|width| width as usize),
+                only_errors: args.only_errors,
+                errors_file: args.errors_file,
+                #[cfg(feature = "geoip")]
+                geoip: open_geoip(args.geoip.as_deref()),
+                verify_checksums: args.verify_checksums,
+                filter: PacketFilter {
+                    src_ip: args.src_ip,
+                    dst_ip: args.dst_ip,
+                    port: args.port,
+                },
+                ..Default::default()
+            };
+
+            Analyzer::read_file(&args.read, &options);
+        }
+        Subcommands::Extract(args) => {
+            Analyzer::extract_objects(&args.read, args.proto, &args.out);
+        }
+        Subcommands::Follow(args) => {
+            let source = Endpoint {
+                address: args.src_ip,
+                port: args.src_port,
+            };
+            let destination = Endpoint {
+                address: args.dst_ip,
+                port: args.dst_port,
+            };
+
+            Analyzer::follow_stream(&args.read, source, destination, args.hex);
         }
     }
-}
\ No newline at end of file
+}
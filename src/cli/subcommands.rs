@@ -1,11 +1,33 @@
 use clap::{Parser, ValueEnum};
 use derive_builder::Builder;
+use pcap::Linktype;
+use sniff_rs::analyzer::definitions::{
+    ActiveHours, DecodeLevel, ExtractProtocol, FifoMode, FileFormat, OutputFormat, RotateLimit,
+    TalkerAlert, TalkerMetric,
+};
+use std::{net::IpAddr, path::PathBuf, time::Duration};
 
 #[derive(Debug, Clone, Parser, Builder)]
 #[clap(about = "List default or all interfaces on a network")]
 pub struct InterfacesArgs {
     #[clap(value_enum)]
     pub list_option: List,
+
+    /// Print the interfaces as a JSON array to stdout
+    #[clap(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(about = "List the datalink types an interface supports")]
+pub struct DatalinksArgs {
+    /// The interface to list the datalink types of
+    #[clap(long, short)]
+    pub interface: String,
+
+    /// Print the datalink types as a JSON array to stdout
+    #[clap(long)]
+    pub json: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, ValueEnum)]
@@ -14,35 +36,812 @@ pub enum List {
     Default,
 }
 
+/// A named capture filter for common traffic, which also turns on the decoding
+/// that traffic needs.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Preset {
+    /// DNS queries and responses, with their questions and answers.
+    Dns,
+    /// HTTP and HTTPS, with the TLS server name and the plaintext payload.
+    Web,
+    /// DHCP leases, with their message types and addresses.
+    Dhcp,
+}
+
+impl Preset {
+    /// The BPF expression selecting the traffic of the preset.
+    pub fn filter(&self) -> &'static str {
+        match self {
+            Preset::Dns => "udp port 53 or tcp port 53",
+            Preset::Web => "tcp port 80 or tcp port 443",
+            Preset::Dhcp => "udp port 67 or udp port 68",
+        }
+    }
+
+    /// Returns `true` if the preset shows the payload of each packet as text.
+    pub fn shows_payload(&self) -> bool {
+        *self == Preset::Web
+    }
+}
+
+/// When a capture stops, as given to `--size`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Limit {
+    /// After this many packets, or never with 0.
+    Packets(usize),
+    /// Once the saved packets add up to this many bytes.
+    Bytes(u64),
+}
+
 #[derive(Debug, Clone, Parser, Builder)]
 #[clap(about = "Stream packets live")]
 pub struct LiveStreamArgs {
-    /// The interface to stream packets from
-    // #[clap(required = true)]
-    #[clap(long, short)]
-    pub interface: String,
+    /// The interfaces to stream packets from, repeated or comma-separated
+    #[clap(required = true)]
+    #[clap(long, short, value_delimiter = ',')]
+    pub interface: Vec<String>,
+
+    /// Only capture packets matching this BPF expression (e.g. "tcp port 443")
+    #[clap(long)]
+    pub filter: Option<String>,
+
+    /// Capture common traffic without writing BPF, combined with --filter if both are set
+    #[clap(long, value_enum)]
+    pub preset: Option<Preset>,
+
+    /// Capture all frames seen by the interface, not only those addressed to this host
+    #[clap(long)]
+    pub promisc: bool,
+
+    /// The maximum number of bytes captured per packet (e.g. 96 to capture only headers)
+    #[clap(long, allow_negative_numbers = true)]
+    pub snaplen: Option<i32>,
+
+    /// The size in bytes of the kernel capture buffer; raise it if packets are
+    /// dropped on busy links
+    #[clap(long, allow_negative_numbers = true)]
+    pub buffer_size: Option<i32>,
+
+    /// Deliver packets as soon as they arrive rather than in buffered batches
+    #[clap(long)]
+    pub immediate: bool,
+
+    /// Reopen the interface, backing off between attempts, if the capture fails
+    /// because it went away
+    #[clap(long)]
+    pub reconnect: bool,
+
+    /// Capture with this datalink type instead of the interface default
+    /// (e.g. IEEE802_11_RADIO), see `datalinks`
+    #[clap(long, value_name = "NAME", value_parser = parse_datalink)]
+    pub datalink: Option<Linktype>,
+
+    /// How long a read waits for packets, in milliseconds, before checking for Ctrl-C
+    /// and time limits
+    #[clap(long, value_name = "MS", default_value_t = 200)]
+    #[clap(value_parser = clap::value_parser!(i32).range(1..))]
+    pub read_timeout: i32,
+
+    /// Only show the Ethernet and IP layers, same as --decode-level summary
+    #[clap(long, conflicts_with = "decode_level")]
+    pub no_transport: bool,
+
+    /// How much detail to decode for each packet
+    #[clap(long, value_enum, default_value_t = DecodeLevel::Standard)]
+    pub decode_level: DecodeLevel,
+
+    /// Show a hexdump of the raw bytes of each packet after its decoded layers
+    #[clap(long)]
+    pub hex: bool,
+
+    /// Show the application payload of each packet as text, non-printable bytes as `.`
+    #[clap(long)]
+    pub payload: bool,
+
+    /// Show at most this many bytes of each payload with --payload
+    #[clap(
+        long,
+        value_name = "BYTES",
+        default_value_t = 256,
+        requires = "payload"
+    )]
+    pub payload_len: usize,
+
+    /// Abbreviate the field labels of each packet line, e.g. `S:` and `D:` for
+    /// source and destination
+    #[clap(long)]
+    pub compact: bool,
+
+    /// Cut packet lines longer than this many characters, ending them with an ellipsis
+    #[clap(long, value_name = "COLUMNS")]
+    #[clap(value_parser = clap::value_parser!(u64).range(2..))]
+    pub width: Option<u64>,
+
+    /// Only show the packets that fail to decode, each with a hexdump of its bytes
+    #[clap(long)]
+    pub only_errors: bool,
+
+    /// Save the packets that fail to decode to this .pcap file, as Ethernet frames
+    #[clap(long, value_name = "FILE")]
+    pub errors_file: Option<PathBuf>,
+
+    /// Do not color packet lines by protocol, which is otherwise done on a terminal
+    #[clap(long)]
+    pub no_color: bool,
+
+    /// Annotate public IP addresses with their country from this MaxMind database (.mmdb)
+    #[cfg(feature = "geoip")]
+    #[clap(long, value_name = "MMDB")]
+    pub geoip: Option<PathBuf>,
+
+    /// Recompute the IPv4, TCP and UDP checksums of each packet and flag mismatches
+    #[clap(long)]
+    pub verify_checksums: bool,
+
+    /// Only show packets sent from this IP address
+    #[clap(long)]
+    pub src_ip: Option<IpAddr>,
+
+    /// Only show packets sent to this IP address
+    #[clap(long)]
+    pub dst_ip: Option<IpAddr>,
+
+    /// Only show TCP and UDP packets from or to this port
+    #[clap(long)]
+    pub port: Option<u16>,
+
+    /// The format of the decoded output
+    #[clap(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Serve the live capture as a pcap stream over HTTP on this address (e.g. 0.0.0.0:8080)
+    #[clap(long)]
+    pub serve_http: Option<String>,
+
+    /// Stop streaming once the process uses more than this much memory (e.g. 512MB)
+    #[clap(long, value_parser = parse_byte_size)]
+    pub max_runtime_memory: Option<u64>,
+
+    /// Serve a live statistics dashboard on this address (e.g. 127.0.0.1:8088)
+    #[clap(long)]
+    pub dashboard: Option<String>,
+
+    /// Serve Prometheus metrics of the capture at /metrics on this address (e.g. 127.0.0.1:9100)
+    #[clap(long, value_name = "HOST:PORT")]
+    pub metrics_addr: Option<String>,
+
+    /// Warn when a host sends more than this within a window (e.g. bytes=100MB/min, packets=5000/s)
+    #[clap(long, value_parser = parse_talker_alert)]
+    pub talker_alert: Option<TalkerAlert>,
+
+    /// Warn about sources whose unanswered TCP SYNs look like a port scan or SYN flood
+    #[clap(long)]
+    pub detect_scans: bool,
+
+    /// Unanswered SYNs to this many distinct ports within the scan window flag a port scan
+    #[clap(
+        long,
+        value_name = "N",
+        default_value_t = 100,
+        requires = "detect_scans"
+    )]
+    #[clap(value_parser = clap::value_parser!(u64).range(1..))]
+    pub scan_ports: u64,
+
+    /// This many unanswered SYNs to a single port within the scan window flag a SYN flood
+    #[clap(
+        long,
+        value_name = "N",
+        default_value_t = 1000,
+        requires = "detect_scans"
+    )]
+    #[clap(value_parser = clap::value_parser!(u64).range(1..))]
+    pub syn_flood: u64,
+
+    /// The sliding window over which unanswered SYNs are counted (e.g. 10s, 1min)
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "10s")]
+    #[clap(requires = "detect_scans")]
+    pub scan_window: Duration,
+
+    /// List this many host pairs that exchanged the most traffic when the capture ends
+    #[clap(long, value_name = "N", default_value_t = 10)]
+    pub top: usize,
+
+    /// Only show packets captured within this daily window of local time (e.g. 09:00-17:00)
+    #[clap(long, value_parser = parse_active_hours)]
+    pub active_hours: Option<ActiveHours>,
+
+    /// Decode packets on this many threads, keeping the output in capture order
+    #[clap(long, default_value_t = 1)]
+    #[clap(value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub parse_threads: usize,
+
+    /// Log the packet and bandwidth rates every this many seconds
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub stats_interval: Option<u64>,
+
+    /// Only log the rates instead of each packet, requires --stats-interval
+    #[clap(long, requires = "stats_interval")]
+    pub stats_only: bool,
 }
 
 #[derive(Debug, Clone, Parser, Builder)]
 #[clap(about = "Capture network packets and save in a .pcap file")]
 pub struct BasicCaptureArgs {
-    /// The directory where the packets will be saved
-    #[clap(required = true)]
+    /// The directory where the packets will be saved, required unless writing to stdout
     #[clap(long = "dir", short = 'd')]
-    pub dir_name: String,
+    pub dir_name: Option<String>,
 
-    /// The name of the .pcap file
-    #[clap(required = true)]
+    /// The name of the .pcap file, or - to write the pcap stream to stdout
+    #[clap(required_unless_present_any = ["fifo", "ring_file"])]
     #[clap(long = "file", short = 'f')]
-    pub file_name: String,
+    pub file_name: Option<String>,
 
-    /// The number of packets to be captured
-    #[clap(required = true)]
-    #[clap(long, short)]
-    pub size: usize,
+    /// The format of the capture file; pcapng keeps the interface name and nanosecond timestamps
+    #[clap(long, value_enum, default_value_t = FileFormat::Pcap)]
+    #[clap(conflicts_with_all = ["fifo", "ring_file"])]
+    pub file_format: FileFormat,
+
+    /// Write the pcap stream to this named pipe instead of a file
+    #[clap(long, conflicts_with_all = ["dir_name", "file_name"])]
+    pub fifo: Option<String>,
+
+    /// Keep only the most recent packets in this single file, overwriting the oldest
+    #[clap(long, conflicts_with_all = ["dir_name", "file_name", "fifo", "repeat"])]
+    #[clap(requires = "ring_size")]
+    pub ring_file: Option<String>,
+
+    /// The size of the ring file (e.g. 100MB)
+    #[clap(long, value_parser = parse_byte_size, requires = "ring_file")]
+    pub ring_size: Option<u64>,
+
+    /// Whether to wait for the FIFO reader before capturing or buffer packets meanwhile
+    #[clap(long, value_enum, default_value_t = FifoMode::Block)]
+    pub fifo_mode: FifoMode,
+
+    /// The number of packets to be captured, or 0 to capture until Ctrl-C, or the size
+    /// the saved packets add up to (e.g. 500KB, 10MB)
+    #[clap(required_unless_present_any = ["duration", "max_bytes"])]
+    #[clap(long, short, value_parser = parse_limit)]
+    pub size: Option<Limit>,
+
+    /// Stop capturing after this many seconds, with or without --size
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub duration: Option<u64>,
+
+    /// Stop capturing once the saved packets add up to this size (e.g. 100MB)
+    #[clap(long, value_parser = parse_byte_size)]
+    pub max_bytes: Option<u64>,
 
     /// The interface to capture packets
     // #[clap(required = true)]
     #[clap(long, short)]
     pub interface: String,
+
+    /// Only capture packets matching this BPF expression (e.g. "tcp port 443")
+    #[clap(long)]
+    pub filter: Option<String>,
+
+    /// Capture common traffic without writing BPF, combined with --filter if both are set
+    #[clap(long, value_enum)]
+    pub preset: Option<Preset>,
+
+    /// Capture all frames seen by the interface, not only those addressed to this host
+    #[clap(long)]
+    pub promisc: bool,
+
+    /// The maximum number of bytes captured per packet (e.g. 96 to capture only headers)
+    #[clap(long, allow_negative_numbers = true)]
+    pub snaplen: Option<i32>,
+
+    /// The size in bytes of the kernel capture buffer; raise it if packets are
+    /// dropped on busy links
+    #[clap(long, allow_negative_numbers = true)]
+    pub buffer_size: Option<i32>,
+
+    /// Deliver packets as soon as they arrive rather than in buffered batches
+    #[clap(long)]
+    pub immediate: bool,
+
+    /// Reopen the interface, backing off between attempts, if the capture fails
+    /// because it went away
+    #[clap(long)]
+    pub reconnect: bool,
+
+    /// Capture with this datalink type instead of the interface default
+    /// (e.g. IEEE802_11_RADIO), see `datalinks`
+    #[clap(long, value_name = "NAME", value_parser = parse_datalink)]
+    pub datalink: Option<Linktype>,
+
+    /// How long a read waits for packets, in milliseconds, before checking for Ctrl-C
+    /// and time limits
+    #[clap(long, value_name = "MS", default_value_t = 200)]
+    #[clap(value_parser = clap::value_parser!(i32).range(1..))]
+    pub read_timeout: i32,
+
+    /// Only show the Ethernet and IP layers, same as --decode-level summary
+    #[clap(long, conflicts_with = "decode_level")]
+    pub no_transport: bool,
+
+    /// How much detail to decode for each packet
+    #[clap(long, value_enum, default_value_t = DecodeLevel::Standard)]
+    pub decode_level: DecodeLevel,
+
+    /// Show a hexdump of the raw bytes of each packet after its decoded layers
+    #[clap(long)]
+    pub hex: bool,
+
+    /// Show the application payload of each packet as text, non-printable bytes as `.`
+    #[clap(long)]
+    pub payload: bool,
+
+    /// Show at most this many bytes of each payload with --payload
+    #[clap(
+        long,
+        value_name = "BYTES",
+        default_value_t = 256,
+        requires = "payload"
+    )]
+    pub payload_len: usize,
+
+    /// Abbreviate the field labels of each packet line, e.g. `S:` and `D:` for
+    /// source and destination
+    #[clap(long)]
+    pub compact: bool,
+
+    /// Cut packet lines longer than this many characters, ending them with an ellipsis
+    #[clap(long, value_name = "COLUMNS")]
+    #[clap(value_parser = clap::value_parser!(u64).range(2..))]
+    pub width: Option<u64>,
+
+    /// Only show the packets that fail to decode, each with a hexdump of its bytes
+    #[clap(long)]
+    pub only_errors: bool,
+
+    /// Save the packets that fail to decode to this .pcap file, as Ethernet frames
+    #[clap(long, value_name = "FILE")]
+    pub errors_file: Option<PathBuf>,
+
+    /// Annotate public IP addresses with their country from this MaxMind database (.mmdb)
+    #[cfg(feature = "geoip")]
+    #[clap(long, value_name = "MMDB")]
+    pub geoip: Option<PathBuf>,
+
+    /// Recompute the IPv4, TCP and UDP checksums of each packet and flag mismatches
+    #[clap(long)]
+    pub verify_checksums: bool,
+
+    /// The format of the decoded output
+    #[clap(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Also append each captured packet as a JSON record to this file (NDJSON)
+    #[clap(long, value_name = "PATH")]
+    pub json_out: Option<PathBuf>,
+
+    /// Stop capturing once the process uses more than this much memory (e.g. 512MB)
+    #[clap(long, value_parser = parse_byte_size)]
+    pub max_runtime_memory: Option<u64>,
+
+    /// Warn when a host sends more than this within a window (e.g. bytes=100MB/min, packets=5000/s)
+    #[clap(long, value_parser = parse_talker_alert)]
+    pub talker_alert: Option<TalkerAlert>,
+
+    /// Warn about sources whose unanswered TCP SYNs look like a port scan or SYN flood
+    #[clap(long)]
+    pub detect_scans: bool,
+
+    /// Unanswered SYNs to this many distinct ports within the scan window flag a port scan
+    #[clap(
+        long,
+        value_name = "N",
+        default_value_t = 100,
+        requires = "detect_scans"
+    )]
+    #[clap(value_parser = clap::value_parser!(u64).range(1..))]
+    pub scan_ports: u64,
+
+    /// This many unanswered SYNs to a single port within the scan window flag a SYN flood
+    #[clap(
+        long,
+        value_name = "N",
+        default_value_t = 1000,
+        requires = "detect_scans"
+    )]
+    #[clap(value_parser = clap::value_parser!(u64).range(1..))]
+    pub syn_flood: u64,
+
+    /// The sliding window over which unanswered SYNs are counted (e.g. 10s, 1min)
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "10s")]
+    #[clap(requires = "detect_scans")]
+    pub scan_window: Duration,
+
+    /// List this many host pairs that exchanged the most traffic when the capture ends
+    #[clap(long, value_name = "N", default_value_t = 10)]
+    pub top: usize,
+
+    /// Run the capture this many times, saving each run to a numbered file (e.g. capture-001.pcap)
+    #[clap(long, default_value_t = 1, conflicts_with = "fifo")]
+    #[clap(value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub repeat: usize,
+
+    /// Split the capture into numbered files of at most N packets or a size
+    /// (e.g. packets=10000, bytes=100MB)
+    #[clap(long, value_parser = parse_rotate, conflicts_with_all = ["fifo", "ring_file"])]
+    pub rotate: Option<RotateLimit>,
+
+    /// The time between the starts of repeated runs (e.g. 30s, 10m, 1h)
+    #[clap(long, value_parser = humantime::parse_duration, requires = "repeat")]
+    pub repeat_interval: Option<Duration>,
+
+    /// Only save packets captured within this daily window of local time (e.g. 09:00-17:00)
+    #[clap(long, value_parser = parse_active_hours)]
+    pub active_hours: Option<ActiveHours>,
+
+    /// Exit with a non-zero code if no packets were captured
+    #[clap(long)]
+    pub strict: bool,
+
+    /// Decode packets on this many threads, keeping the output in capture order
+    #[clap(long, default_value_t = 1)]
+    #[clap(value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub parse_threads: usize,
+}
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(about = "Decode packets from a .pcap file")]
+pub struct AnalyzeArgs {
+    /// The .pcap or ring files to read packets from, e.g. the files of a rotated capture
+    #[clap(required = true)]
+    #[clap(long, short, num_args = 1..)]
+    pub read: Vec<String>,
+
+    /// The format of the decoded output
+    #[clap(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// List this many host pairs that exchanged the most traffic when the capture ends
+    #[clap(long, value_name = "N", default_value_t = 10)]
+    pub top: usize,
+
+    /// Only show the Ethernet and IP layers, same as --decode-level summary
+    #[clap(long, conflicts_with = "decode_level")]
+    pub no_transport: bool,
+
+    /// How much detail to decode for each packet
+    #[clap(long, value_enum, default_value_t = DecodeLevel::Standard)]
+    pub decode_level: DecodeLevel,
+
+    /// Show a hexdump of the raw bytes of each packet after its decoded layers
+    #[clap(long)]
+    pub hex: bool,
+
+    /// Show the application payload of each packet as text, non-printable bytes as `.`
+    #[clap(long)]
+    pub payload: bool,
+
+    /// Show at most this many bytes of each payload with --payload
+    #[clap(
+        long,
+        value_name = "BYTES",
+        default_value_t = 256,
+        requires = "payload"
+    )]
+    pub payload_len: usize,
+
+    /// Abbreviate the field labels of each packet line, e.g. `S:` and `D:` for
+    /// source and destination
+    #[clap(long)]
+    pub compact: bool,
+
+    /// Cut packet lines longer than this many characters, ending them with an ellipsis
+    #[clap(long, value_name = "COLUMNS")]
+    #[clap(value_parser = clap::value_parser!(u64).range(2..))]
+    pub width: Option<u64>,
+
+    /// Only show the packets that fail to decode, each with a hexdump of its bytes
+    #[clap(long)]
+    pub only_errors: bool,
+
+    /// Save the packets that fail to decode to this .pcap file, as Ethernet frames
+    #[clap(long, value_name = "FILE")]
+    pub errors_file: Option<PathBuf>,
+
+    /// Annotate public IP addresses with their country from this MaxMind database (.mmdb)
+    #[cfg(feature = "geoip")]
+    #[clap(long, value_name = "MMDB")]
+    pub geoip: Option<PathBuf>,
+
+    /// Recompute the IPv4, TCP and UDP checksums of each packet and flag mismatches
+    #[clap(long)]
+    pub verify_checksums: bool,
+
+    /// Only show packets sent from this IP address
+    #[clap(long)]
+    pub src_ip: Option<IpAddr>,
+
+    /// Only show packets sent to this IP address
+    #[clap(long)]
+    pub dst_ip: Option<IpAddr>,
+
+    /// Only show TCP and UDP packets from or to this port
+    #[clap(long)]
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(about = "Replay the packets of a saved .pcap file")]
+pub struct ReplayArgs {
+    /// The .pcap file to replay
+    #[clap(required = true)]
+    #[clap(long, short)]
+    pub read: String,
+
+    /// Only show the Ethernet and IP layers, same as --decode-level summary
+    #[clap(long, conflicts_with = "decode_level")]
+    pub no_transport: bool,
+
+    /// How much detail to decode for each packet
+    #[clap(long, value_enum, default_value_t = DecodeLevel::Standard)]
+    pub decode_level: DecodeLevel,
+
+    /// Show a hexdump of the raw bytes of each packet after its decoded layers
+    #[clap(long)]
+    pub hex: bool,
+
+    /// Show the application payload of each packet as text, non-printable bytes as `.`
+    #[clap(long)]
+    pub payload: bool,
+
+    /// Show at most this many bytes of each payload with --payload
+    #[clap(
+        long,
+        value_name = "BYTES",
+        default_value_t = 256,
+        requires = "payload"
+    )]
+    pub payload_len: usize,
+
+    /// Abbreviate the field labels of each packet line, e.g. `S:` and `D:` for
+    /// source and destination
+    #[clap(long)]
+    pub compact: bool,
+
+    /// Cut packet lines longer than this many characters, ending them with an ellipsis
+    #[clap(long, value_name = "COLUMNS")]
+    #[clap(value_parser = clap::value_parser!(u64).range(2..))]
+    pub width: Option<u64>,
+
+    /// Only show the packets that fail to decode, each with a hexdump of its bytes
+    #[clap(long)]
+    pub only_errors: bool,
+
+    /// Save the packets that fail to decode to this .pcap file, as Ethernet frames
+    #[clap(long, value_name = "FILE")]
+    pub errors_file: Option<PathBuf>,
+
+    /// Annotate public IP addresses with their country from this MaxMind database (.mmdb)
+    #[cfg(feature = "geoip")]
+    #[clap(long, value_name = "MMDB")]
+    pub geoip: Option<PathBuf>,
+
+    /// Recompute the IPv4, TCP and UDP checksums of each packet and flag mismatches
+    #[clap(long)]
+    pub verify_checksums: bool,
+
+    /// Only show packets sent from this IP address
+    #[clap(long)]
+    pub src_ip: Option<IpAddr>,
+
+    /// Only show packets sent to this IP address
+    #[clap(long)]
+    pub dst_ip: Option<IpAddr>,
+
+    /// Only show TCP and UDP packets from or to this port
+    #[clap(long)]
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(about = "Extract transferred files from the streams of a .pcap file")]
+pub struct ExtractArgs {
+    /// The .pcap files to read packets from
+    #[clap(required = true)]
+    #[clap(long, short, num_args = 1..)]
+    pub read: Vec<String>,
+
+    /// The protocol to extract objects from
+    #[clap(long, value_enum, default_value_t = ExtractProtocol::Http)]
+    pub proto: ExtractProtocol,
+
+    /// The directory to write the extracted objects to
+    #[clap(required = true)]
+    #[clap(long, short)]
+    pub out: String,
+}
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(about = "Reassemble and print a TCP connection of a .pcap file")]
+pub struct FollowArgs {
+    /// The .pcap files to read packets from
+    #[clap(required = true)]
+    #[clap(long, short, num_args = 1..)]
+    pub read: Vec<String>,
+
+    /// The IP address the connection was opened from
+    #[clap(long)]
+    pub src_ip: IpAddr,
+
+    /// The port the connection was opened from
+    #[clap(long)]
+    pub src_port: u16,
+
+    /// The IP address the connection was opened to
+    #[clap(long)]
+    pub dst_ip: IpAddr,
+
+    /// The port the connection was opened to
+    #[clap(long)]
+    pub dst_port: u16,
+
+    /// Print a hexdump of the streams instead of text
+    #[clap(long)]
+    pub hex: bool,
+}
+
+/// Parses the name of a datalink type, such as `EN10MB` or `IEEE802_11_RADIO`, as
+/// listed by the `datalinks` subcommand. Names are case-insensitive.
+pub fn parse_datalink(value: &str) -> Result<Linktype, String> {
+    Linktype::from_name(&value.to_ascii_uppercase())
+        .map_err(|_| format!("unknown datalink type `{}`", value))
+}
+
+/// Parses a byte quantity such as `512`, `64KB`, `100MB` or `2GB`.
+///
+/// Units are binary multiples (1KB = 1024 bytes) and are case-insensitive.
+pub fn parse_byte_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let number = number
+        .parse::<u64>()
+        .map_err(|_| format!("invalid size `{}`", value))?;
+
+    let multiplier = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1024,
+        "M" | "MB" => 1024 * 1024,
+        "G" | "GB" => 1024 * 1024 * 1024,
+        _ => return Err(format!("invalid size unit `{}`", unit)),
+    };
+
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("size `{}` is too large", value))
+}
+
+/// Parses the limit of `--size`. A plain number is a packet count, while a number
+/// with a unit, such as `500KB` or `10MB`, is a size as parsed by `parse_byte_size`.
+pub fn parse_limit(value: &str) -> Result<Limit, String> {
+    let value = value.trim();
+    if value.chars().all(|c| c.is_ascii_digit()) {
+        return value
+            .parse::<usize>()
+            .map(Limit::Packets)
+            .map_err(|_| format!("invalid packet count `{}`", value));
+    }
+
+    match parse_byte_size(value)? {
+        0 => Err("the size limit must be greater than 0".to_string()),
+        bytes => Ok(Limit::Bytes(bytes)),
+    }
+}
+
+/// Parses a talker alert threshold of the form `<metric>=<amount>/<window>`, such
+/// as `bytes=100MB/min` or `packets=5000/s`.
+///
+/// The metric is `bytes` or `packets`, byte amounts accept the units of
+/// `parse_byte_size` and the window is one of `s`, `min` or `h`.
+pub fn parse_talker_alert(value: &str) -> Result<TalkerAlert, String> {
+    let (metric, rate) = value.split_once('=').ok_or_else(|| {
+        format!(
+            "invalid talker alert `{}`, expected e.g. bytes=100MB/min",
+            value
+        )
+    })?;
+    let (amount, window) = rate
+        .split_once('/')
+        .ok_or_else(|| format!("missing window in talker alert `{}`", value))?;
+
+    let (metric, threshold) = match metric.trim().to_ascii_lowercase().as_str() {
+        "bytes" => (TalkerMetric::Bytes, parse_byte_size(amount)?),
+        "packets" => (
+            TalkerMetric::Packets,
+            amount
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("invalid packet count `{}`", amount))?,
+        ),
+        other => return Err(format!("invalid talker alert metric `{}`", other)),
+    };
+
+    let window = match window.trim().to_ascii_lowercase().as_str() {
+        "s" | "sec" => Duration::from_secs(1),
+        "m" | "min" => Duration::from_secs(60),
+        "h" | "hour" => Duration::from_secs(60 * 60),
+        other => return Err(format!("invalid talker alert window `{}`", other)),
+    };
+
+    Ok(TalkerAlert {
+        metric,
+        threshold,
+        window,
+    })
+}
+
+/// Parses a rotation limit of the form `packets=<count>` or `bytes=<size>`, such
+/// as `packets=10000` or `bytes=100MB`.
+pub fn parse_rotate(value: &str) -> Result<RotateLimit, String> {
+    let (metric, amount) = value.split_once('=').ok_or_else(|| {
+        format!(
+            "invalid rotation `{}`, expected e.g. packets=10000 or bytes=100MB",
+            value
+        )
+    })?;
+
+    let limit = match metric.trim().to_ascii_lowercase().as_str() {
+        "bytes" => RotateLimit::Bytes(parse_byte_size(amount)?),
+        "packets" => RotateLimit::Packets(
+            amount
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("invalid packet count `{}`", amount))?,
+        ),
+        other => return Err(format!("invalid rotation metric `{}`", other)),
+    };
+
+    match limit {
+        RotateLimit::Packets(0) | RotateLimit::Bytes(0) => {
+            Err("the rotation limit must be greater than 0".to_string())
+        }
+        limit => Ok(limit),
+    }
+}
+
+/// Parses a daily window of local time of the form `HH:MM-HH:MM`, such as
+/// `09:00-17:00`. A window that ends before it starts spans midnight.
+pub fn parse_active_hours(value: &str) -> Result<ActiveHours, String> {
+    let (start, end) = value.split_once('-').ok_or_else(|| {
+        format!(
+            "invalid active hours `{}`, expected e.g. 09:00-17:00",
+            value
+        )
+    })?;
+
+    let start = parse_time_of_day(start)?;
+    let end = parse_time_of_day(end)?;
+    if start == end {
+        return Err(format!("active hours `{}` are empty", value));
+    }
+
+    Ok(ActiveHours { start, end })
+}
+
+/// Parses a `HH:MM` time of day into minutes since midnight.
+fn parse_time_of_day(value: &str) -> Result<u32, String> {
+    let invalid = || format!("invalid time of day `{}`, expected HH:MM", value);
+    let (hours, minutes) = value.trim().split_once(':').ok_or_else(invalid)?;
+
+    let hours = hours.parse::<u32>().map_err(|_| invalid())?;
+    let minutes = minutes.parse::<u32>().map_err(|_| invalid())?;
+
+    // 24:00 is accepted as the end of the day
+    if hours > 24 || minutes > 59 || (hours == 24 && minutes != 0) {
+        return Err(invalid());
+    }
+
+    Ok(hours * 60 + minutes)
 }
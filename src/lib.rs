@@ -0,0 +1,20 @@
+//! Packet capture and decoding, as used by the `wyre` binary.
+//!
+//! Capture handles are opened with [`PcapInterface`], and their packets read with
+//! [`PcapInterface::packets`] or sent over a channel as [`ReadPacketResult`]s by
+//! [`PcapInterface::read_packets`]. [`Analyzer::capture_with`] captures on an
+//! interface and hands each packet to a closure as a [`ParsedFrame`].
+//! [`Analyzer::decode`] and [`format_packets`] turn raw Ethernet frames into log
+//! lines without logging them. The other `Analyzer` methods run the subcommands of
+//! the binary and log their output.
+
+pub mod analyzer;
+pub mod logger;
+pub mod parser;
+
+pub use analyzer::{
+    analyzer::Analyzer,
+    definitions::{ParsedFrame, ReadPacketResult},
+    pcap_interface::PcapInterface,
+};
+pub use logger::format_packets::format_packets;
@@ -1,12 +1,66 @@
 pub mod log {
     use fern::{
         colors::{Color, ColoredLevelConfig},
-        Dispatch, InitError,
+        Dispatch, FormatCallback, InitError,
     };
     use humantime;
-    use std::time::SystemTime;
+    use std::{
+        fmt,
+        io::{self, IsTerminal},
+        path::Path,
+        time::SystemTime,
+    };
+
+    /// Sets up logging to stdout, or to stderr when stdout carries other output
+    /// such as a pcap stream.
+    ///
+    /// Log lines are colored only when the console is a terminal, so that output
+    /// piped into a file or pager holds no escape sequences.
+    ///
+    /// # Arguments
+    /// * `to_stderr` - Log to stderr instead of stdout.
+    /// * `log_file` - A file that also receives every log line, appended to and
+    ///   without the color codes of the console output.
+    /// * `level` - The most detailed level that is logged.
+    pub fn setup(
+        to_stderr: bool,
+        log_file: Option<&Path>,
+        level: log::LevelFilter,
+    ) -> Result<(), InitError> {
+        let is_terminal = if to_stderr {
+            io::stderr().is_terminal()
+        } else {
+            io::stdout().is_terminal()
+        };
+
+        let console = if is_terminal {
+            colored_console()
+        } else {
+            Dispatch::new().format(format_plain)
+        };
+
+        let console = if to_stderr {
+            console.chain(io::stderr())
+        } else {
+            console.chain(io::stdout())
+        };
 
-    pub fn setup() -> Result<(), InitError> {
+        let mut dispatch = Dispatch::new().level(level).chain(console);
+
+        if let Some(path) = log_file {
+            let file = Dispatch::new()
+                .format(format_plain)
+                .chain(fern::log_file(path)?);
+
+            dispatch = dispatch.chain(file);
+        }
+
+        dispatch.apply()?;
+        Ok(())
+    }
+
+    /// A dispatch that colors each log line by its level.
+    fn colored_console() -> Dispatch {
         let colors_line = ColoredLevelConfig::new()
             .info(Color::White)
             .warn(Color::Yellow)
@@ -15,32 +69,103 @@ pub mod log {
             .trace(Color::Magenta);
 
         let colors_level = colors_line.info(Color::Green);
-        Dispatch::new()
-            .format(move |out, message, record| {
-                out.finish(format_args!(
-                    "{color_line}[{date} {level} {color_line}] {message}\x1B[0m",
-                    color_line = format_args!(
-                        "\x1B[{}m",
-                        colors_line.get_color(&record.level()).to_fg_str()
-                    ),
-                    date = humantime::format_rfc3339_seconds(SystemTime::now()),
-                    level = colors_level.color(record.level()),
-                    message = message,
-                ));
-            })
-            .level(log::LevelFilter::Debug)
-            .chain(std::io::stdout())
-            .apply()?;
-        Ok(())
+        Dispatch::new().format(move |out, message, record| {
+            out.finish(format_args!(
+                "{color_line}[{date} {level} {color_line}] {message}\x1B[0m",
+                color_line = format_args!(
+                    "\x1B[{}m",
+                    colors_line.get_color(&record.level()).to_fg_str()
+                ),
+                date = humantime::format_rfc3339_seconds(SystemTime::now()),
+                level = colors_level.color(record.level()),
+                message = message,
+            ));
+        })
+    }
+
+    /// Formats a log line without color codes.
+    fn format_plain(out: FormatCallback, message: &fmt::Arguments, record: &log::Record) {
+        out.finish(format_args!(
+            "[{date} {level}] {message}",
+            date = humantime::format_rfc3339_seconds(SystemTime::now()),
+            level = record.level(),
+            message = message,
+        ));
     }
 }
 
 pub mod format_packets {
+    use crate::analyzer::{
+        arp::{ArpPacket, ARP_REPLY, ARP_REQUEST, ETHER_TYPE_ARP},
+        checksum::Checksum,
+        definitions::{DecodeLevel, ParseOptions},
+        dhcp::{self, DhcpMessage},
+        dns::{self, DnsMessage},
+        encapsulation::{
+            self, read_u16, read_u32, IpHeader, IpTunnel, Ipv4Fragment, ETHERNET_HEADER_LEN,
+            IPV6_DESTINATION_OPTIONS, IPV6_FRAGMENT, IPV6_HOP_BY_HOP, IPV6_NO_NEXT_HEADER,
+            IPV6_ROUTING, IP_PROTOCOL_IPV4, IP_PROTOCOL_IPV6,
+        },
+        flows::{Counter, Endpoint, FlowKey, FlowStats, IP_PROTOCOL_TCP, IP_PROTOCOL_UDP},
+        geneve::{GenevePacket, ETHER_TYPE_TRANSPARENT_BRIDGING},
+        gre::{GreHeader, IP_PROTOCOL_GRE},
+        igmp::{self, IgmpMessage, IP_PROTOCOL_IGMP},
+        ipsec::{IpsecHeader, IP_PROTOCOL_AH, IP_PROTOCOL_ESP},
+        loopback::LoopbackFrame,
+        oui::oui_vendor,
+        pppoe::{self, PppoeSession, ETHER_TYPE_PPPOE_SESSION},
+        reassembly::TcpSegment,
+        stats::{Stats, IP_PROTOCOL_ICMP, IP_PROTOCOL_ICMPV6},
+        tls,
+        vlan::{self, VlanTag, ETHER_TYPE_QINQ, ETHER_TYPE_VLAN},
+        wifi::{self, WifiFrame, FRAME_TYPE_MANAGEMENT},
+    };
+    use ansi_term::Colour;
     use net_sift::parsers::{
         definitions::LayeredData,
         ethernet_frame::{EthernetFrame, EthernetFrameHeader},
         icmp, ipv4, ipv6, tcp, udp,
     };
+    use pcap::{Address, Device, Linktype, PacketHeader, Stat};
+    use serde_json::{json, Value};
+    use std::{
+        net::IpAddr,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    /// Shown in place of the transport layer of IPv4 fragments other than the first.
+    const NON_FIRST_FRAGMENT: &str = "Fragment: transport header is in the first fragment";
+
+    /// The last second representable in RFC 3339, at the end of year 9999.
+    const MAX_RFC3339_SECS: u64 = 253_402_300_799;
+
+    /// Formats the capture timestamp of a packet as RFC 3339 with microseconds, e.g.
+    /// `2024-05-01T12:00:00.123456Z`.
+    ///
+    /// Timestamps before the epoch or beyond year 9999, which only corrupt capture
+    /// files produce, are shown as `-` rather than overflowing the conversion.
+    pub fn format_timestamp(header: &PacketHeader) -> String {
+        let seconds = match u64::try_from(header.ts.tv_sec) {
+            Ok(seconds) if seconds <= MAX_RFC3339_SECS => seconds,
+            _ => return "-".to_string(),
+        };
+        let micros = u32::try_from(header.ts.tv_usec)
+            .unwrap_or_default()
+            .min(999_999);
+
+        match UNIX_EPOCH.checked_add(Duration::new(seconds, micros * 1000)) {
+            Some(time) => humantime::format_rfc3339_micros(time).to_string(),
+            None => "-".to_string(),
+        }
+    }
+
+    /// Formats the time elapsed since the previous packet, e.g. `Δ 0.004200s`.
+    ///
+    /// Packets merged from several interfaces can arrive slightly out of order,
+    /// in which case the delta is negative.
+    pub fn format_delta(delta_micros: i64) -> String {
+        format!("Δ {:.6}s", delta_micros as f64 / 1_000_000.0)
+    }
 
     /// Formats the different layers of an Ethernet frame for logging.
     ///
@@ -48,34 +173,675 @@ pub mod format_packets {
     /// This includes Ethernet, IP (both IPv4 and IPv6), and transport layer (TCP/UDP/ICMP) data.
     ///
     /// # Arguments
-    /// * `frame` - An `EthernetFrame` struct representing the captured frame.
+    /// * `frame` - The parsed Ethernet frame, which is only borrowed.
+    /// * `packet` - The raw bytes of the frame, used for protocols net_sift does not parse.
+    /// * `vlan_tags` - The VLAN tags removed from the frame before it was parsed.
+    /// * `options` - Options controlling which layers are formatted.
     ///
     /// # Returns
     /// Returns a `String` with the formatted output of each layer in the Ethernet frame.
-    pub fn format_packets(frame: EthernetFrame) -> String {
+    pub fn format_packets(
+        frame: &EthernetFrame,
+        packet: &[u8],
+        vlan_tags: &[VlanTag],
+        options: &ParseOptions,
+    ) -> String {
+        let mut output = format_ether_frame(&frame.header, packet, vlan_tags, options);
+
+        // ARP frames carry no IP layer, so they are decoded from the raw bytes
+        let layers = match ArpPacket::from_frame(packet) {
+            Some(arp) => format_colored(format_compact(format_arp(&arp), options), options),
+            None if !carries_ip(packet) => format_compact(format_non_ip(packet), options),
+            None => format_layers(&frame.data, packet, options),
+        };
+
+        output.push_str(&format!(" | {}", layers));
+        output
+    }
+
+    /// Returns `true` if the EtherType of a raw Ethernet frame is IPv4 or IPv6.
+    fn carries_ip(packet: &[u8]) -> bool {
+        matches!(
+            encapsulation::ether_type(packet),
+            Some(encapsulation::ETHER_TYPE_IPV4 | encapsulation::ETHER_TYPE_IPV6)
+        )
+    }
+
+    /// Formats the payload of an Ethernet frame that carries neither IP nor ARP,
+    /// e.g. `EtherType 0x888e (EAPOL): non-IP payload (95 bytes)`.
+    pub fn format_non_ip(packet: &[u8]) -> String {
+        let ether_type = encapsulation::ether_type(packet).unwrap_or_default();
+        let name = match ether_type_name(ether_type) {
+            Some(name) => format!(" ({})", name),
+            None => String::new(),
+        };
+
+        let payload_len = packet.len().saturating_sub(ETHERNET_HEADER_LEN);
+        format!(
+            "EtherType {:#06x}{}: non-IP payload ({} bytes)",
+            ether_type, name, payload_len
+        )
+    }
+
+    /// Names the protocols commonly carried by Ethernet frames other than IP, e.g.
+    /// `LLDP` for 0x88cc.
+    fn ether_type_name(ether_type: u16) -> Option<&'static str> {
+        let name = match ether_type {
+            ETHER_TYPE_ARP => "ARP",
+            ETHER_TYPE_TRANSPARENT_BRIDGING => "Transparent Ethernet Bridging",
+            0x8035 => "RARP",
+            ETHER_TYPE_VLAN => "802.1Q",
+            0x8808 => "Ethernet Flow Control",
+            0x8809 => "Slow Protocols",
+            0x8847 => "MPLS",
+            0x8848 => "MPLS Multicast",
+            0x8863 => "PPPoE Discovery",
+            ETHER_TYPE_PPPOE_SESSION => "PPPoE Session",
+            0x888e => "EAPOL",
+            ETHER_TYPE_QINQ => "802.1ad",
+            0x88cc => "LLDP",
+            0x88e5 => "MACsec",
+            0x88f7 => "PTP",
+            0x8906 => "FCoE",
+            _ => return None,
+        };
+
+        Some(name)
+    }
+
+    /// Formats an ARP request or reply in the style of tcpdump, e.g.
+    /// `ARP: Request who-has 10.0.0.1 tell 10.0.0.2 (aa:bb:cc:dd:ee:ff)`.
+    pub fn format_arp(arp: &ArpPacket) -> String {
+        let description = match arp.operation {
+            ARP_REQUEST => format!(
+                "Request who-has {} tell {} ({})",
+                arp.target_ip,
+                arp.sender_ip,
+                encapsulation::format_mac(&arp.sender_mac)
+            ),
+            ARP_REPLY => format!(
+                "Reply {} is-at {} (to {} {})",
+                arp.sender_ip,
+                encapsulation::format_mac(&arp.sender_mac),
+                arp.target_ip,
+                encapsulation::format_mac(&arp.target_mac)
+            ),
+            operation => format!(
+                "Op {} Sender {} ({}) Target {} ({})",
+                operation,
+                arp.sender_ip,
+                encapsulation::format_mac(&arp.sender_mac),
+                arp.target_ip,
+                encapsulation::format_mac(&arp.target_mac)
+            ),
+        };
+
+        format!(
+            "ARP: {}, HType {}, PType {:#06x}",
+            description, arp.hardware_type, arp.protocol_type
+        )
+    }
+
+    /// Formats the different layers of an Ethernet frame as a JSON object.
+    ///
+    /// Each layer is emitted as a nested object keyed by protocol name, e.g.
+    /// `ethernet.mac_src`, `ipv4.ttl` or `tcp.seq`. Layers that are not present in
+    /// the frame are omitted.
+    ///
+    /// # Arguments
+    /// * `frame` - The parsed Ethernet frame, which is only borrowed.
+    /// * `packet` - The raw bytes of the frame, whose payload is included at the verbose level.
+    /// * `options` - Options controlling which layers are formatted.
+    pub fn format_packets_json(
+        frame: &EthernetFrame,
+        packet: &[u8],
+        vlan_tags: &[VlanTag],
+        options: &ParseOptions,
+    ) -> Value {
         let EthernetFrame {
             header,
             data: ethernet_frame_data,
         } = frame;
 
-        let ipv4_packet = parse_ipv4(&ethernet_frame_data);
-        let ipv6_packet = parse_ipv6(&ethernet_frame_data);
+        let mut output = json!({
+            "ethernet": {
+                "mac_src": header.mac_source.to_string(),
+                "mac_dst": header.mac_destination.to_string(),
+                "ether_type": format!("{:?}", header.ether_type),
+            }
+        });
+
+        if !vlan_tags.is_empty() {
+            output["ethernet"]["vlan"] = vlan_tags
+                .iter()
+                .map(|tag| json!({ "id": tag.vid, "pcp": tag.pcp, "dei": tag.dei }))
+                .collect();
+        }
+
+        let transport_data = if let Some(ipv4) = parse_ipv4(ethernet_frame_data) {
+            output["ipv4"] = json!({
+                "version": ipv4.header.version,
+                "src": ipv4.header.source_address.to_string(),
+                "dst": ipv4.header.destination_address.to_string(),
+                "protocol": format!("{:?}", ipv4.header.protocol),
+                "ttl": ipv4.header.time_to_live,
+            });
+            Some(&ipv4.data)
+        } else if let Some(ipv6) = parse_ipv6(ethernet_frame_data) {
+            output["ipv6"] = json!({
+                "version": ipv6.header.version,
+                "src": ipv6.header.source_address.to_string(),
+                "dst": ipv6.header.destination_address.to_string(),
+                "next_header": format!("{:?}", ipv6.header.next_header),
+            });
+            Some(&ipv6.data)
+        } else {
+            None
+        };
+
+        if let Some(data) = transport_data.filter(|_| options.decode_level != DecodeLevel::Summary)
+        {
+            if let Some((key, transport)) = format_transports_json(data) {
+                output[key] = transport;
+            }
+        }
+
+        if options.decode_level == DecodeLevel::Verbose {
+            if let Some(payload) = transport_payload(packet) {
+                output["payload"] = json!(format_hex(payload));
+            }
+        }
+
+        output
+    }
+
+    /// The header row of the CSV output, naming the columns of `format_packets_csv`.
+    pub const CSV_HEADER: &str =
+        "timestamp,src_mac,dst_mac,src_ip,dst_ip,protocol,src_port,dst_port,length";
+
+    /// Formats a raw Ethernet frame as a row of the CSV output, with the columns of
+    /// `CSV_HEADER`.
+    ///
+    /// Fields the frame does not carry, such as the addresses of an ARP frame or the
+    /// ports of an ICMP packet, are left as empty cells. VLAN tags are skipped.
+    ///
+    /// # Arguments
+    /// * `frame` - The raw bytes of the Ethernet frame.
+    /// * `header` - The capture header of the frame, holding its timestamp.
+    pub fn format_packets_csv(frame: &[u8], header: &PacketHeader) -> String {
+        let length = frame.len();
+        let (_, untagged) = vlan::strip_tags(frame);
+        let frame = &*untagged;
+
+        let mac = |offset| {
+            encapsulation::read_mac(frame, offset)
+                .map(|mac| encapsulation::format_mac(&mac))
+                .unwrap_or_default()
+        };
+
+        let mut cells = vec![format_timestamp(header), mac(6), mac(0)];
+
+        match encapsulation::parse_ip(frame) {
+            Some((ip, segment)) => {
+                let port = |offset| match ip.protocol {
+                    IP_PROTOCOL_TCP | IP_PROTOCOL_UDP => read_u16(segment, offset)
+                        .map(|port| port.to_string())
+                        .unwrap_or_default(),
+                    _ => String::new(),
+                };
+
+                cells.extend([
+                    ip.source_address.to_string(),
+                    ip.destination_address.to_string(),
+                    ip_protocol_name(ip.protocol),
+                    port(0),
+                    port(2),
+                ]);
+            }
+            None => {
+                let protocol = encapsulation::ether_type(frame)
+                    .and_then(ether_type_name)
+                    .unwrap_or_default();
+                cells.extend([
+                    String::new(),
+                    String::new(),
+                    protocol.to_string(),
+                    String::new(),
+                    String::new(),
+                ]);
+            }
+        }
+
+        cells.push(length.to_string());
+        cells.join(",")
+    }
+
+    /// Formats transport layer data from the given `LayeredData` as a JSON object,
+    /// returned along with the key it is stored under.
+    fn format_transports_json(layered_data: &LayeredData) -> Option<(&'static str, Value)> {
+        match layered_data {
+            LayeredData::TcpData(tcp) => {
+                let mut value = json!({
+                    "src_port": tcp.header.source_port,
+                    "dst_port": tcp.header.destination_port,
+                    "seq": tcp.header.sequence_number,
+                    "syn": tcp.header.flags.syn,
+                    "ack": tcp.header.flags.ack,
+                });
+
+                if tcp.header.flags.urg {
+                    value["urgent_pointer"] = json!(tcp.header.urgent_pointer);
+                }
+
+                Some(("tcp", value))
+            }
+            LayeredData::UdpData(udp) => Some((
+                "udp",
+                json!({
+                    "src_port": udp.header.source_port,
+                    "dst_port": udp.header.destination_port,
+                }),
+            )),
+            LayeredData::IcmpData(icmp) => Some((
+                "icmp",
+                json!({
+                    "type": icmp.header.icmp_type,
+                    "code": icmp.header.icmp_code,
+                    "checksum": icmp.header.checksum,
+                }),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Formats the IP and transport layers carried by an Ethernet frame.
+    ///
+    /// # Arguments
+    /// * `layered_data` - The parsed payload of an Ethernet frame.
+    /// * `packet` - The raw bytes of the Ethernet frame.
+    /// * `options` - Options controlling which layers are formatted.
+    ///
+    /// # Returns
+    /// Returns a `String` of the form `<ip> | <transport>`, or just `<ip>` when
+    /// transport parsing is disabled.
+    pub fn format_layers(
+        layered_data: &LayeredData,
+        packet: &[u8],
+        options: &ParseOptions,
+    ) -> String {
+        let ipv4_packet = parse_ipv4(layered_data);
+        let ipv6_packet = parse_ipv6(layered_data);
 
         let mut transport_msg = String::new();
         let mut ip_msg = String::new();
+        let with_transport = options.decode_level != DecodeLevel::Summary;
 
-        let mut output = format_ether_frame(&header);
+        // The payload of a later fragment is not a transport header
+        let non_first_fragment = encapsulation::ipv4_fragment(packet)
+            .filter(Ipv4Fragment::is_non_first)
+            .is_some();
 
         if let Some(ipv4) = ipv4_packet {
-            transport_msg = format_transports(&ipv4.data);
-            ip_msg = format_ipv4(ipv4);
+            if non_first_fragment {
+                transport_msg = NON_FIRST_FRAGMENT.to_string();
+            } else if with_transport {
+                transport_msg = format_transports(&ipv4.data, packet);
+            }
+            ip_msg = format_ipv4(ipv4, packet, options);
         } else if let Some(ipv6) = ipv6_packet {
-            transport_msg = format_transports(&ipv6.data);
-            ip_msg = format_ipv6(ipv6);
+            if with_transport {
+                transport_msg = format_transports(&ipv6.data, packet);
+            }
+            ip_msg = format_ipv6(ipv6, packet, options);
         }
 
-        output.push_str(&format!(" | {} | {}", ip_msg, transport_msg));
-        output
+        if options.decode_level == DecodeLevel::Verbose {
+            if let Some(ip_options) = encapsulation::ipv4_options(packet).filter(|o| !o.is_empty())
+            {
+                ip_msg.push_str(&format!(" Opts: {}", format_hex(ip_options)));
+            }
+
+            if let Some(segment) = TcpSegment::from_frame(packet)
+                .filter(|s| !s.options.is_empty() && !non_first_fragment)
+            {
+                transport_msg.push_str(&format!(" Opts: {}", format_tcp_options(segment.options)));
+            }
+        }
+
+        let ip_msg = format_compact(ip_msg, options);
+        if !with_transport {
+            return ip_msg;
+        }
+
+        format!(
+            "{} | {}",
+            ip_msg,
+            format_colored(format_compact(transport_msg, options), options)
+        )
+    }
+
+    /// Formats the result of checksum verification: `[checksum OK]` if every
+    /// checksum matches, or the stored and recomputed values of each mismatch,
+    /// e.g. `[TCP checksum BAD: 0x1234 != 0x5678]`.
+    pub fn format_checksums(checksums: &[Checksum]) -> String {
+        if checksums.iter().all(Checksum::is_valid) {
+            return "[checksum OK]".to_string();
+        }
+
+        checksums
+            .iter()
+            .filter(|checksum| !checksum.is_valid())
+            .map(|checksum| {
+                format!(
+                    "[{} checksum BAD: {:#06x} != {:#06x}]",
+                    checksum.layer, checksum.stored, checksum.computed
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// Describes the verified checksums of a packet as a JSON array.
+    pub fn format_checksums_json(checksums: &[Checksum]) -> Value {
+        checksums
+            .iter()
+            .map(|checksum| {
+                json!({
+                    "layer": checksum.layer,
+                    "stored": checksum.stored,
+                    "computed": checksum.computed,
+                    "valid": checksum.is_valid(),
+                })
+            })
+            .collect()
+    }
+
+    /// Formats the payload carried by the transport layer of a raw Ethernet frame
+    /// as printable ASCII, with other bytes shown as `.`, e.g.
+    /// ` | Payload (16 bytes): GET / HTTP/1.1..`.
+    ///
+    /// # Arguments
+    /// * `packet` - The raw bytes of the Ethernet frame.
+    /// * `max_len` - The number of bytes shown, after which the payload is cut off
+    ///   with `...`.
+    ///
+    /// # Returns
+    /// The payload with a leading separator, or an empty string if there is none.
+    pub fn format_payload_text(packet: &[u8], max_len: usize) -> String {
+        let payload = match transport_payload(packet).filter(|payload| !payload.is_empty()) {
+            Some(payload) => payload,
+            None => return String::new(),
+        };
+
+        let text = payload
+            .iter()
+            .take(max_len)
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+        let ellipsis = if payload.len() > max_len { "..." } else { "" };
+
+        format!(" | Payload ({} bytes): {}{}", payload.len(), text, ellipsis)
+    }
+
+    /// Formats the payload carried by the transport layer of a raw Ethernet frame
+    /// as a hexdump, or an empty string if there is none.
+    pub fn format_payload(packet: &[u8]) -> String {
+        transport_payload(packet)
+            .filter(|payload| !payload.is_empty())
+            .map(hexdump)
+            .unwrap_or_default()
+    }
+
+    /// Returns the bytes following the TCP or UDP header, or the IP header for other
+    /// protocols, of a raw Ethernet frame.
+    fn transport_payload(packet: &[u8]) -> Option<&[u8]> {
+        let (header, payload) = encapsulation::parse_ip(packet)?;
+
+        match header.protocol {
+            IP_PROTOCOL_TCP => TcpSegment::from_frame(packet).map(|segment| segment.payload),
+            IP_PROTOCOL_UDP => payload.get(8..),
+            _ => Some(payload),
+        }
+    }
+
+    /// The field labels abbreviated by `--compact`, longest first so that a label
+    /// is not replaced by a shorter one it starts with.
+    const COMPACT_LABELS: [(&str, &str); 14] = [
+        ("Src Port: ", "S:"),
+        ("Dest Port: ", "D:"),
+        ("Src Port ", "S:"),
+        ("Dest Port ", "D:"),
+        ("Ethernet: ", "Eth "),
+        ("Flags: ", "F:"),
+        ("Next: ", "N:"),
+        ("Ver: ", "v"),
+        ("Src: ", "S:"),
+        ("Dest: ", "D:"),
+        ("Ver ", "v"),
+        ("Src ", "S:"),
+        ("Dest ", "D:"),
+        ("Prot ", "P:"),
+    ];
+
+    /// Abbreviates the field labels of a header segment if `options` asks for
+    /// compact output, e.g. `S:` for `Src Port`, and drops the commas between its
+    /// fields. Only headers are passed through it, never payloads or hexdumps.
+    fn format_compact(segment: String, options: &ParseOptions) -> String {
+        if !options.compact {
+            return segment;
+        }
+
+        let mut segment = segment;
+        for (label, abbreviation) in COMPACT_LABELS {
+            segment = segment.replace(label, abbreviation);
+        }
+        segment.replace(", ", " ")
+    }
+
+    /// Fits the text of a decoded packet to a narrow terminal: with a `width`,
+    /// each line longer than it is cut and ends with an ellipsis.
+    pub fn format_fitted(text: &str, options: &ParseOptions) -> String {
+        let width = match options.width {
+            Some(width) => width,
+            None => return text.to_string(),
+        };
+
+        text.split('\n')
+            .map(|line| fit_line(line, width))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Cuts a line longer than `width` characters, ending it with an ellipsis. The
+    /// color codes of colored output take no space, so they are not counted and
+    /// are never cut.
+    fn fit_line(line: &str, width: usize) -> String {
+        if visible_len(line) <= width {
+            return line.to_string();
+        }
+
+        let mut fitted = String::new();
+        let mut visible = 0;
+        let mut in_escape = false;
+        for c in line.chars() {
+            if c == '\x1B' || in_escape {
+                in_escape = c != 'm';
+                fitted.push(c);
+                continue;
+            }
+
+            if visible + 1 == width {
+                break;
+            }
+            fitted.push(c);
+            visible += 1;
+        }
+
+        fitted.push('\u{2026}');
+        // The color of a cut segment would otherwise run on past the line
+        if line.contains('\x1B') {
+            fitted.push_str("\x1B[0m");
+        }
+        fitted
+    }
+
+    /// The number of characters of a line shown on a terminal, without its color
+    /// codes.
+    fn visible_len(line: &str) -> usize {
+        let mut len = 0;
+        let mut in_escape = false;
+        for c in line.chars() {
+            if c == '\x1B' || in_escape {
+                in_escape = c != 'm';
+            } else {
+                len += 1;
+            }
+        }
+        len
+    }
+
+    /// The color of each protocol in colored output, by the name its segment of a
+    /// packet line starts with, e.g. `TCP` for `TCP: Src Port: ...`.
+    const PROTOCOL_COLORS: [(&str, Colour); 4] = [
+        ("TCP", Colour::Green),
+        ("UDP", Colour::Blue),
+        ("ICMP", Colour::Yellow),
+        ("ARP", Colour::Purple),
+    ];
+
+    /// Colors the segment of a packet line by its protocol, as listed in
+    /// `PROTOCOL_COLORS`, if `options` asks for colored output.
+    fn format_colored(segment: String, options: &ParseOptions) -> String {
+        if !options.color {
+            return segment;
+        }
+
+        match PROTOCOL_COLORS
+            .iter()
+            .find(|(name, _)| segment.starts_with(name))
+        {
+            Some((_, colour)) => colour.paint(segment).to_string(),
+            None => segment,
+        }
+    }
+
+    /// Formats bytes as a classic hexdump of 16 bytes per line, with offsets and an
+    /// ASCII column. Each line starts on a new line.
+    pub fn hexdump(data: &[u8]) -> String {
+        data.chunks(16)
+            .enumerate()
+            .map(|(line, chunk)| {
+                let ascii = chunk
+                    .iter()
+                    .map(|&b| {
+                        if b.is_ascii_graphic() || b == b' ' {
+                            b as char
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect::<String>();
+
+                format!(
+                    "\n    {:04x}  {:<47}  {}",
+                    line * 16,
+                    chunk
+                        .iter()
+                        .map(|b| format!("{:02x}", b))
+                        .collect::<Vec<String>>()
+                        .join(" "),
+                    ascii
+                )
+            })
+            .collect()
+    }
+
+    fn format_hex(data: &[u8]) -> String {
+        data.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Formats the options of a TCP header, naming the common kinds.
+    fn format_tcp_options(options: &[u8]) -> String {
+        let mut formatted = Vec::new();
+        let mut position = 0;
+
+        while let Some(&kind) = options.get(position) {
+            match kind {
+                0 => {
+                    formatted.push("EOL".to_string());
+                    break;
+                }
+                1 => {
+                    formatted.push("NOP".to_string());
+                    position += 1;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let len = match options.get(position + 1).map(|len| usize::from(*len)) {
+                Some(len) if len >= 2 && position + len <= options.len() => len,
+                _ => {
+                    formatted.push(format!("Kind {} (malformed)", kind));
+                    break;
+                }
+            };
+
+            let data = &options[position + 2..position + len];
+            formatted.push(match (kind, data.len()) {
+                (2, 2) => format!("MSS {}", read_u16(data, 0).unwrap_or_default()),
+                (3, 1) => format!("WS {}", data[0]),
+                (4, 0) => "SACK_PERM".to_string(),
+                (5, _) => {
+                    let blocks = data
+                        .chunks_exact(8)
+                        .map(|block| {
+                            format!(
+                                "{}-{}",
+                                read_u32(block, 0).unwrap_or_default(),
+                                read_u32(block, 4).unwrap_or_default()
+                            )
+                        })
+                        .collect::<Vec<String>>();
+                    format!("SACK {}", blocks.join(" "))
+                }
+                (8, 8) => format!(
+                    "TS {}/{}",
+                    read_u32(data, 0).unwrap_or_default(),
+                    read_u32(data, 4).unwrap_or_default()
+                ),
+                _ => format!("Kind {} Len {}", kind, len),
+            });
+            position += len;
+        }
+
+        format!("[{}]", formatted.join(", "))
+    }
+
+    /// Formats the outer header of an IP tunnel, labelled by the versions of the
+    /// outer and inner IP headers.
+    pub fn format_tunnel(tunnel: &IpTunnel, options: &ParseOptions) -> String {
+        let inner_ipv4 = tunnel.inner_ether_type == encapsulation::ETHER_TYPE_IPV4;
+        let kind = match (tunnel.outer.source_address.is_ipv4(), inner_ipv4) {
+            (true, true) => "IP-in-IP",
+            (true, false) => "6in4",
+            (false, true) => "4in6",
+            (false, false) => "6in6",
+        };
+
+        format_compact(
+            format!(
+                "Tunnel ({}): Src {}, Dest {}",
+                kind, tunnel.outer.source_address, tunnel.outer.destination_address
+            ),
+            options,
+        )
     }
 
     /// Parses IPv4 data from the given `LayeredData`
@@ -94,69 +860,1160 @@ pub mod format_packets {
         }
     }
 
-    /// Formats transport layer data from the given `LayeredData`.
-    fn format_transports(layered_data: &LayeredData) -> String {
+    /// Formats transport layer data from the given `LayeredData`, falling back to
+    /// the raw packet for protocols net_sift does not parse.
+    fn format_transports(layered_data: &LayeredData, packet: &[u8]) -> String {
         match layered_data {
-            LayeredData::TcpData(data) => format_tcp(data),
-            LayeredData::UdpData(data) => format_udp(data),
+            LayeredData::TcpData(data) => format_tcp(data, packet),
+            LayeredData::UdpData(data) => {
+                let mut output = format_udp(data);
+                let datagram = encapsulation::parse_ip(packet).map(|(_, datagram)| datagram);
+                if let Some(dns) = datagram.and_then(DnsMessage::from_udp) {
+                    output.push_str(&format!(" | {}", format_dns(&dns)));
+                } else if let Some(dhcp) = datagram.and_then(DhcpMessage::from_udp) {
+                    output.push_str(&format!(" | {}", format_dhcp(&dhcp)));
+                }
+                output
+            }
             LayeredData::IcmpData(data) => format_icmp(data),
-            _ => String::new(),
+            _ => format_raw_transport(packet).unwrap_or_default(),
         }
     }
 
-    /// Formats an Ethernet frame header.
-    fn format_ether_frame(header: &EthernetFrameHeader) -> String {
+    /// Formats the questions of a DNS query, or the response code and answers of
+    /// a DNS response.
+    pub fn format_dns(dns: &DnsMessage) -> String {
+        let questions = dns
+            .questions
+            .iter()
+            .map(|question| {
+                format!(
+                    "{} {}",
+                    dns::record_type_name(question.record_type),
+                    question.name
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        if !dns.response {
+            return format!("DNS: Query {:#06x} {}", dns.id, questions);
+        }
+
+        let answers = dns
+            .answers
+            .iter()
+            .map(|answer| {
+                format!(
+                    "{} {}",
+                    dns::record_type_name(answer.record_type),
+                    answer.data
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+
         format!(
-            "Ethernet: Src {:?}, Dest {:?}, Prot {:?}",
-            header.mac_destination.to_string(),
-            header.mac_source.to_string(),
-            header.ether_type,
+            "DNS: Response {:#06x} {} {} -> [{}]",
+            dns.id,
+            dns::rcode_name(dns.rcode),
+            questions,
+            answers
         )
     }
 
-    fn format_ipv4(ipv4_packet: &ipv4::Ipv4Packet) -> String {
+    /// Formats the message type of a DHCP message along with the client's MAC
+    /// address and the requested, assigned and server addresses it carries.
+    pub fn format_dhcp(dhcp: &DhcpMessage) -> String {
+        let mut output = match dhcp.message_type {
+            Some(message_type) => format!("DHCP: {}", dhcp::message_type_name(message_type)),
+            None if dhcp.op == 2 => "BOOTP: Reply".to_string(),
+            None => "BOOTP: Request".to_string(),
+        };
+        output.push_str(&format!(" Xid: {:#010x}", dhcp.transaction_id));
+
+        if let Some(mac) = &dhcp.client_mac {
+            output.push_str(&format!(" Client: {}", encapsulation::format_mac(mac)));
+        }
+        if let Some(requested_ip) = dhcp.requested_ip {
+            output.push_str(&format!(" Requested: {}", requested_ip));
+        }
+        if !dhcp.your_ip.is_unspecified() {
+            output.push_str(&format!(" Assigned: {}", dhcp.your_ip));
+        }
+        if let Some(server) = dhcp.server_identifier {
+            output.push_str(&format!(" Server: {}", server));
+        }
+
+        output
+    }
+
+    /// Formats the protocol carried by an IP packet from its raw bytes, for
+    /// protocols that net_sift does not parse.
+    fn format_raw_transport(packet: &[u8]) -> Option<String> {
+        let (header, payload) = encapsulation::parse_ip(packet)?;
+
+        // net_sift does not follow IPv6 extension headers to the TCP or UDP header
+        match header.protocol {
+            IP_PROTOCOL_TCP => {
+                let segment = TcpSegment::from_frame(packet)?;
+                Some(format!(
+                    "TCP: Src Port: {} Dest Port: {} Seq: {} Flags: [{}] ({:#04x}) Win: {}",
+                    format_port(read_u16(payload, 0)?, IP_PROTOCOL_TCP),
+                    format_port(read_u16(payload, 2)?, IP_PROTOCOL_TCP),
+                    segment.sequence,
+                    format_tcp_flags(segment.flags),
+                    segment.flags,
+                    segment.window
+                ))
+            }
+            IP_PROTOCOL_UDP => Some(format!(
+                "UDP: Src Port {}, Dest Port {}",
+                format_port(read_u16(payload, 0)?, IP_PROTOCOL_UDP),
+                format_port(read_u16(payload, 2)?, IP_PROTOCOL_UDP)
+            )),
+            IP_PROTOCOL_IGMP => IgmpMessage::parse(payload).map(|igmp| format_igmp(&igmp)),
+            IP_PROTOCOL_GRE => GreHeader::parse(payload).map(|gre| format_gre(&gre)),
+            _ => IpsecHeader::parse(header.protocol, payload).map(|ipsec| format_ipsec(&ipsec)),
+        }
+    }
+
+    /// Formats the message type of an IGMP message and the group it concerns, or
+    /// the group records of an IGMPv3 report, e.g. `239.1.1.1 To Exclude`.
+    fn format_igmp(igmp: &IgmpMessage) -> String {
+        let mut output = match igmp::message_type_name(igmp.message_type) {
+            Some(name) => format!("IGMP: {}", name),
+            None => format!("IGMP: Type {:#04x}", igmp.message_type),
+        };
+
+        match igmp.group {
+            // A query for no group in particular asks about every group
+            Some(group) if group.is_unspecified() => output.push_str(", General"),
+            Some(group) => output.push_str(&format!(", Group {}", group)),
+            None => {
+                let records = igmp
+                    .records
+                    .iter()
+                    .map(|record| {
+                        let record_type = match igmp::record_type_name(record.record_type) {
+                            Some(name) => name.to_string(),
+                            None => format!("Type {}", record.record_type),
+                        };
+                        format!("{} {} ({} src)", record.group, record_type, record.sources)
+                    })
+                    .collect::<Vec<String>>();
+
+                output.push_str(&format!(", Records [{}]", records.join("; ")));
+            }
+        }
+
+        output
+    }
+
+    /// Formats a GRE header with the protocol of the packet it carries, e.g.
+    /// `GRE: Ver 0, Prot 0x0800 (IPv4), Key 0x00000001`.
+    fn format_gre(gre: &GreHeader) -> String {
+        let name = match gre.protocol_type {
+            encapsulation::ETHER_TYPE_IPV4 => Some("IPv4"),
+            encapsulation::ETHER_TYPE_IPV6 => Some("IPv6"),
+            // The enhanced GRE of PPTP carries PPP frames
+            0x880b => Some("PPP"),
+            0x88be => Some("ERSPAN"),
+            other => ether_type_name(other),
+        };
+        let mut output = match name {
+            Some(name) => format!(
+                "GRE: Ver {}, Prot {:#06x} ({})",
+                gre.version, gre.protocol_type, name
+            ),
+            None => format!("GRE: Ver {}, Prot {:#06x}", gre.version, gre.protocol_type),
+        };
+
+        match gre.key {
+            // PPTP puts the payload length and call ID of the session in the key
+            Some(key) if gre.version == 1 => output.push_str(&format!(", Call {}", key & 0xffff)),
+            Some(key) => output.push_str(&format!(", Key {:#010x}", key)),
+            None => {}
+        }
+        if let Some(sequence) = gre.sequence {
+            output.push_str(&format!(", Seq {}", sequence));
+        }
+        if let Some(acknowledgment) = gre.acknowledgment {
+            output.push_str(&format!(", Ack {}", acknowledgment));
+        }
+
+        output
+    }
+
+    /// Formats a frame that net_sift could not parse from its raw bytes.
+    ///
+    /// # Returns
+    /// `Some(String)` with the Ethernet, IP and, where known, the upper layer
+    /// protocol if the frame carries an IP packet, `None` otherwise.
+    pub fn format_raw_packet(
+        packet: &[u8],
+        vlan_tags: &[VlanTag],
+        options: &ParseOptions,
+    ) -> Option<String> {
+        if let Some(arp) = ArpPacket::from_frame(packet) {
+            return Some(format!(
+                "{} | {}",
+                format_raw_ether_frame(packet, vlan_tags, options),
+                format_colored(format_compact(format_arp(&arp), options), options)
+            ));
+        }
+
+        if !carries_ip(packet) {
+            return Some(format!(
+                "{} | {}",
+                format_raw_ether_frame(packet, vlan_tags, options),
+                format_compact(format_non_ip(packet), options)
+            ));
+        }
+
+        let (header, _) = encapsulation::parse_ip(packet)?;
+        let mut ip_msg = format_ip_header(&header, packet, options);
+
+        let fragment = encapsulation::ipv4_fragment(packet);
+        if let Some(fragment) = &fragment {
+            ip_msg.push_str(&format_fragment(fragment));
+        }
+
+        let mut output = format!(
+            "{} | {}",
+            format_raw_ether_frame(packet, vlan_tags, options),
+            format_compact(ip_msg, options)
+        );
+
+        if fragment.is_some_and(|fragment| fragment.is_non_first()) {
+            output.push_str(&format!(" | {}", NON_FIRST_FRAGMENT));
+        } else if options.decode_level != DecodeLevel::Summary {
+            let transport = format_raw_transport(packet).unwrap_or_default();
+            output.push_str(&format!(
+                " | {}",
+                format_colored(format_compact(transport, options), options)
+            ));
+        }
+
+        Some(output)
+    }
+
+    /// Formats an IP header parsed from the raw Ethernet frame in `packet`, which
+    /// shows the extension header chain of IPv6 packets.
+    fn format_ip_header(header: &IpHeader, packet: &[u8], options: &ParseOptions) -> String {
+        let version = if header.source_address.is_ipv4() {
+            "IPv4"
+        } else {
+            "IPV6"
+        };
+        let protocol =
+            format_ipv6_extensions(packet).unwrap_or_else(|| header.protocol.to_string());
+
+        format!(
+            "{}: Src {}, Dest {}, Prot {}",
+            version,
+            format_address(header.source_address, options),
+            format_address(header.destination_address, options),
+            protocol
+        )
+    }
+
+    /// Formats the SPI and sequence number of an ESP or AH header.
+    fn format_ipsec(ipsec: &IpsecHeader) -> String {
+        match ipsec {
+            IpsecHeader::Esp { spi, sequence } => {
+                format!("ESP: SPI {:#010x}, Seq {}", spi, sequence)
+            }
+            IpsecHeader::Ah {
+                next_header,
+                spi,
+                sequence,
+            } => format!(
+                "AH: SPI {:#010x}, Seq {}, Next {}",
+                spi, sequence, next_header
+            ),
+        }
+    }
+
+    /// Formats a byte count with a binary unit suffix, e.g. `1.2MB`.
+    pub fn format_bytes(bytes: u64) -> String {
+        const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+        if bytes < 1024 {
+            return format!("{}B", bytes);
+        }
+
+        let mut value = bytes as f64 / 1024.0;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+
+        format!("{:.1}{}", value, UNITS[unit])
+    }
+
+    /// Formats packet and byte rates, e.g. `rate: 1204 pkts/s, 8.3 MB/s`. The
+    /// bandwidth is always shown in MB so that consecutive lines line up.
+    pub fn format_rate(packets_per_sec: f64, bytes_per_sec: f64) -> String {
+        format!(
+            "rate: {:.0} pkts/s, {:.1} MB/s",
+            packets_per_sec,
+            bytes_per_sec / (1024.0 * 1024.0)
+        )
+    }
+
+    /// Formats the packet counts of libpcap, e.g.
+    /// `Received: 1000, Dropped: 120 (12.0%), Dropped by interface: 0`.
+    ///
+    /// Packets are dropped by the kernel when its capture buffer fills up, and by
+    /// the interface or its driver, which not every platform reports.
+    pub fn format_capture_stats(stats: &Stat) -> String {
+        let dropped_percent = if stats.received > 0 {
+            stats.dropped as f64 / stats.received as f64 * 100.0
+        } else {
+            0.0
+        };
+
+        format!(
+            "Received: {}, Dropped: {} ({:.1}%), Dropped by interface: {}",
+            stats.received, stats.dropped, dropped_percent, stats.if_dropped
+        )
+    }
+
+    /// Formats the protocol breakdown and volume of captured frames, e.g.
+    /// `Packets: 1000 (1.2MB) | IPv4: 990, IPv6: 10 | TCP: 812, UDP: 170, ICMP: 18, Other: 0`.
+    pub fn format_protocol_summary(stats: &Stats) -> String {
+        format!(
+            "Packets: {} ({}) | IPv4: {}, IPv6: {} | TCP: {}, UDP: {}, ICMP: {}, Other: {}",
+            stats.packets,
+            format_bytes(stats.bytes),
+            stats.ipv4,
+            stats.ipv6,
+            stats.tcp,
+            stats.udp,
+            stats.icmp,
+            stats.other
+        )
+    }
+
+    /// Formats the unicast/multicast/broadcast breakdown of captured frames, e.g.
+    /// `Unicast: 950 (95.0%), Multicast: 40 (4.0%), Broadcast: 10 (1.0%)`.
+    pub fn format_cast_summary(stats: &Stats) -> String {
+        let percent = |count: u64| count as f64 * 100.0 / stats.packets.max(1) as f64;
+
+        format!(
+            "Unicast: {} ({:.1}%), Multicast: {} ({:.1}%), Broadcast: {} ({:.1}%)",
+            stats.unicast,
+            percent(stats.unicast),
+            stats.multicast,
+            percent(stats.multicast),
+            stats.broadcast,
+            percent(stats.broadcast)
+        )
+    }
+
+    /// Formats network interfaces as a table with their description, addresses and
+    /// status flags, one row per address, e.g.
+    ///
+    /// ```text
+    /// NAME  DESCRIPTION  ADDRESSES        FLAGS
+    /// eth0  -            192.168.1.10/24  UP,RUNNING
+    ///                    fe80::1/64
+    /// lo    -            127.0.0.1/8      UP,RUNNING,LOOPBACK
+    /// ```
+    pub fn format_interfaces(devices: &[Device]) -> String {
+        let rows = devices
+            .iter()
+            .map(|device| {
+                let addresses = device
+                    .addresses
+                    .iter()
+                    .map(format_interface_address)
+                    .collect::<Vec<String>>();
+                (
+                    device.name.as_str(),
+                    device.desc.as_deref().unwrap_or("-"),
+                    addresses,
+                    format_interface_flags(device),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let name_width = rows.iter().map(|row| row.0.len()).max().unwrap_or(0).max(4);
+        let desc_width = rows
+            .iter()
+            .map(|row| row.1.len())
+            .max()
+            .unwrap_or(0)
+            .max(11);
+        let address_width = rows
+            .iter()
+            .flat_map(|row| row.2.iter().map(String::len))
+            .max()
+            .unwrap_or(0)
+            .max(9);
+
+        let mut lines = vec![format!(
+            "{:<name_width$}  {:<desc_width$}  {:<address_width$}  FLAGS",
+            "NAME", "DESCRIPTION", "ADDRESSES"
+        )];
+        for (name, desc, addresses, flags) in &rows {
+            let first_address = addresses.first().map(String::as_str).unwrap_or("-");
+            lines.push(format!(
+                "{:<name_width$}  {:<desc_width$}  {:<address_width$}  {}",
+                name, desc, first_address, flags
+            ));
+
+            for address in addresses.iter().skip(1) {
+                lines.push(format!(
+                    "{:<name_width$}  {:<desc_width$}  {}",
+                    "", "", address
+                ));
+            }
+        }
+
+        lines
+            .iter()
+            .map(|line| line.trim_end())
+            .collect::<Vec<&str>>()
+            .join("\n")
+    }
+
+    /// Formats the datalink types of an interface as a table, marking the one it
+    /// captures with by default, e.g.
+    ///
+    /// ```text
+    /// NAME              DESCRIPTION
+    /// EN10MB            Ethernet (default)
+    /// IEEE802_11_RADIO  802.11 plus radiotap header
+    /// ```
+    pub fn format_datalinks(datalinks: &[Linktype], default: Linktype) -> String {
+        let rows = datalinks
+            .iter()
+            .map(|datalink| {
+                let name = datalink
+                    .get_name()
+                    .unwrap_or_else(|_| datalink.0.to_string());
+                let mut description = datalink.get_description().unwrap_or_default();
+                if *datalink == default {
+                    description.push_str(" (default)");
+                }
+                (name, description)
+            })
+            .collect::<Vec<(String, String)>>();
+
+        let name_width = rows.iter().map(|row| row.0.len()).max().unwrap_or(0).max(4);
+
+        let mut lines = vec![format!("{:<name_width$}  DESCRIPTION", "NAME")];
+        for (name, description) in &rows {
+            lines.push(format!("{:<name_width$}  {}", name, description));
+        }
+
+        lines
+            .iter()
+            .map(|line| line.trim_end())
+            .collect::<Vec<&str>>()
+            .join("\n")
+    }
+
+    /// Formats an interface address with its netmask as a prefix length, e.g.
+    /// `192.168.1.10/24`, or with the netmask itself if it is not contiguous.
+    fn format_interface_address(address: &Address) -> String {
+        let netmask = match address.netmask {
+            Some(netmask) => netmask,
+            None => return address.addr.to_string(),
+        };
+
+        // Align IPv4 masks with the top bits so that both count from the left
+        let bits = match netmask {
+            IpAddr::V4(mask) => u128::from(u32::from(mask)) << 96,
+            IpAddr::V6(mask) => u128::from(mask),
+        };
+        let prefix = bits.leading_ones();
+
+        if bits.count_ones() == prefix {
+            format!("{}/{}", address.addr, prefix)
+        } else {
+            format!("{} netmask {}", address.addr, netmask)
+        }
+    }
+
+    /// Lists the status flags of an interface, e.g. `UP,RUNNING`.
+    fn format_interface_flags(device: &Device) -> String {
+        let flags = [
+            (device.flags.is_up(), "UP"),
+            (device.flags.is_running(), "RUNNING"),
+            (device.flags.is_loopback(), "LOOPBACK"),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, name)| *name)
+        .collect::<Vec<&str>>();
+
+        if flags.is_empty() {
+            "-".to_string()
+        } else {
+            flags.join(",")
+        }
+    }
+
+    /// Formats a flow and its per-direction counters for the flow summary, e.g.
+    /// `TCP 10.0.0.2:51544 <-> 1.1.1.1:443 | ↑ 1.2MB / 45 pkts  ↓ 300.0KB / 40 pkts`.
+    ///
+    /// The initiator of the flow is shown first; `↑` is the traffic it sent and `↓`
+    /// the traffic it received.
+    pub fn format_flow(key: &FlowKey, stats: &FlowStats) -> String {
+        let (initiator, responder) = stats.endpoints(key);
+        let (upload, download) = (stats.upload(), stats.download());
+
+        format!(
+            "{} {} <-> {} | \u{2191} {} / {} pkts  \u{2193} {} / {} pkts",
+            format_flow_protocol(key.protocol),
+            format_endpoint(&initiator, key.protocol),
+            format_endpoint(&responder, key.protocol),
+            format_bytes(upload.bytes),
+            upload.packets,
+            format_bytes(download.bytes),
+            download.packets
+        )
+    }
+
+    /// Formats the traffic exchanged between a pair of hosts, e.g.
+    /// `10.0.0.1 <-> 10.0.0.2 | 1.2MB / 845 pkts`.
+    pub fn format_conversation(hosts: &(IpAddr, IpAddr), counter: &Counter) -> String {
+        format!(
+            "{} <-> {} | {} / {} pkts",
+            hosts.0,
+            hosts.1,
+            format_bytes(counter.bytes),
+            counter.packets
+        )
+    }
+
+    /// Formats the protocol and endpoints of a flow.
+    pub fn format_flow_key(key: &FlowKey) -> String {
         format!(
+            "{} {} <-> {}",
+            format_flow_protocol(key.protocol),
+            format_endpoint(&key.a, key.protocol),
+            format_endpoint(&key.b, key.protocol)
+        )
+    }
+
+    /// Formats the direction of a reassembled TCP stream, e.g.
+    /// `10.0.0.1:51234 -> 93.184.216.34:80: 1.2KB`.
+    pub fn format_stream_direction(from: &Endpoint, to: &Endpoint, bytes: usize) -> String {
+        format!(
+            "{} -> {}: {}",
+            format_endpoint(from, IP_PROTOCOL_TCP),
+            format_endpoint(to, IP_PROTOCOL_TCP),
+            format_bytes(bytes as u64)
+        )
+    }
+
+    /// Renders the bytes of a stream as text, keeping line breaks and tabs and
+    /// replacing other non-printable bytes with `.`.
+    pub fn format_stream_text(data: &[u8]) -> String {
+        data.iter()
+            .filter(|&&b| b != b'\r')
+            .map(|&b| {
+                if b.is_ascii_graphic() || matches!(b, b' ' | b'\n' | b'\t') {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect()
+    }
+
+    fn format_flow_protocol(protocol: u8) -> String {
+        match protocol {
+            IP_PROTOCOL_TCP => "TCP".to_string(),
+            IP_PROTOCOL_UDP => "UDP".to_string(),
+            other => format!("IP({})", other),
+        }
+    }
+
+    fn format_endpoint(endpoint: &Endpoint, protocol: u8) -> String {
+        match (protocol, endpoint.address) {
+            (IP_PROTOCOL_TCP | IP_PROTOCOL_UDP, std::net::IpAddr::V6(address)) => {
+                format!("[{}]:{}", address, endpoint.port)
+            }
+            (IP_PROTOCOL_TCP | IP_PROTOCOL_UDP, address) => {
+                format!("{}:{}", address, endpoint.port)
+            }
+            (_, address) => address.to_string(),
+        }
+    }
+
+    /// Formats the PPPoE session header and PPP protocol of a frame.
+    pub fn format_pppoe(session: &PppoeSession, options: &ParseOptions) -> String {
+        let protocol = match pppoe::ppp_protocol_name(session.ppp_protocol) {
+            Some(name) => format!("{:#06x} ({})", session.ppp_protocol, name),
+            None => format!("{:#06x}", session.ppp_protocol),
+        };
+
+        format_compact(
+            format!(
+                "PPPoE: Ver {}, Session {:#06x}, Len {}, PPP Prot {}",
+                session.version, session.session_id, session.length, protocol
+            ),
+            options,
+        )
+    }
+
+    /// Formats the VNI, flags and option TLVs of a Geneve header.
+    pub fn format_geneve(geneve: &GenevePacket, options: &ParseOptions) -> String {
+        let mut output = format!(
+            "Geneve: Ver {}, VNI {}, Prot {:#06x}",
+            geneve.version, geneve.vni, geneve.protocol_type
+        );
+
+        if geneve.oam {
+            output.push_str(", OAM");
+        }
+
+        if geneve.critical {
+            output.push_str(", Critical");
+        }
+
+        if !geneve.options.is_empty() {
+            let tlvs = geneve
+                .options
+                .iter()
+                .map(|option| {
+                    format!(
+                        "Class {:#06x} Type {:#04x} Len {}",
+                        option.class,
+                        option.option_type,
+                        option.data.len()
+                    )
+                })
+                .collect::<Vec<String>>();
+
+            output.push_str(&format!(", Options [{}]", tlvs.join("; ")));
+        }
+
+        format_compact(output, options)
+    }
+
+    /// Formats the address family header of a loopback frame, e.g.
+    /// `Loopback: Family IPv6 (30)`.
+    pub fn format_loopback(frame: &LoopbackFrame, options: &ParseOptions) -> String {
+        let output = match frame.ether_type() {
+            Some(encapsulation::ETHER_TYPE_IPV4) => {
+                format!("Loopback: Family IPv4 ({})", frame.family)
+            }
+            Some(encapsulation::ETHER_TYPE_IPV6) => {
+                format!("Loopback: Family IPv6 ({})", frame.family)
+            }
+            _ => format!("Loopback: Family {}", frame.family),
+        };
+
+        format_compact(output, options)
+    }
+
+    /// Formats the radiotap and 802.11 headers of a Wi-Fi frame, e.g.
+    /// `802.11: Beacon, Channel 6 (2437 MHz), Signal -42 dBm, Receiver ..., Transmitter ..., BSSID ...`.
+    ///
+    /// The first two addresses are the receiver and transmitter of every frame type;
+    /// the third is only named for management frames, where it is the BSSID.
+    pub fn format_wifi(frame: &WifiFrame, options: &ParseOptions) -> String {
+        let mut output = format!(
+            "802.11: {}",
+            wifi::frame_name(frame.frame_type, frame.subtype)
+        );
+
+        if let Some(radiotap) = &frame.radiotap {
+            match (radiotap.channel(), radiotap.frequency) {
+                (Some(channel), Some(frequency)) => {
+                    output.push_str(&format!(", Channel {} ({} MHz)", channel, frequency))
+                }
+                (None, Some(frequency)) => output.push_str(&format!(", {} MHz", frequency)),
+                _ => {}
+            }
+            if let Some(signal) = radiotap.signal {
+                output.push_str(&format!(", Signal {} dBm", signal));
+            }
+        }
+
+        let names = if frame.frame_type == FRAME_TYPE_MANAGEMENT {
+            &["Receiver", "Transmitter", "BSSID"][..]
+        } else {
+            &["Receiver", "Transmitter"][..]
+        };
+        for (name, mac) in names.iter().zip(&frame.addresses) {
+            output.push_str(&format!(", {} {:?}", name, encapsulation::format_mac(mac)));
+        }
+
+        if frame.is_protected() {
+            output.push_str(", Protected");
+        }
+
+        format_compact(output, options)
+    }
+
+    /// Formats the header of a raw Ethernet frame that net_sift cannot parse.
+    pub fn format_raw_ether_frame(
+        frame: &[u8],
+        vlan_tags: &[VlanTag],
+        options: &ParseOptions,
+    ) -> String {
+        format_compact(
+            format!(
+                "Ethernet: Src {}, Dest {}{}, Prot {:#06x}",
+                format_frame_mac(frame, 6),
+                format_frame_mac(frame, 0),
+                format_vlan_tags(vlan_tags),
+                encapsulation::ether_type(frame).unwrap_or_default()
+            ),
+            options,
+        )
+    }
+
+    /// Formats the MAC address at `offset` of a raw Ethernet frame, followed by its
+    /// vendor if the OUI is known, e.g. `"00:00:0c:12:34:56" (Cisco)`.
+    fn format_frame_mac(frame: &[u8], offset: usize) -> String {
+        let formatted = encapsulation::read_mac(frame, offset)
+            .map(|mac| encapsulation::format_mac(&mac))
+            .unwrap_or_default();
+
+        format!("{:?}{}", formatted, format_frame_vendor(frame, offset))
+    }
+
+    /// Formats the vendor of the MAC address at `offset` of a raw Ethernet frame as
+    /// it follows the address, e.g. ` (Cisco)`, or gives an empty string if the OUI
+    /// is unknown.
+    fn format_frame_vendor(frame: &[u8], offset: usize) -> String {
+        encapsulation::read_mac(frame, offset)
+            .and_then(|mac| oui_vendor(&mac))
+            .map(|vendor| format!(" ({})", vendor))
+            .unwrap_or_default()
+    }
+
+    /// Formats an Ethernet frame header. The vendors of the addresses are read from
+    /// the raw frame in `packet`, and only shown if they are known.
+    fn format_ether_frame(
+        header: &EthernetFrameHeader,
+        packet: &[u8],
+        vlan_tags: &[VlanTag],
+        options: &ParseOptions,
+    ) -> String {
+        format_compact(
+            format!(
+                "Ethernet: Src {:?}{}, Dest {:?}{}{}, Prot {:?}",
+                header.mac_source.to_string(),
+                format_frame_vendor(packet, 6),
+                header.mac_destination.to_string(),
+                format_frame_vendor(packet, 0),
+                format_vlan_tags(vlan_tags),
+                header.ether_type,
+            ),
+            options,
+        )
+    }
+
+    /// Formats the VLAN tags of a frame, outermost first, as they are listed after
+    /// its addresses, e.g. `, VLAN 100 PCP 5`. Untagged frames give an empty string.
+    fn format_vlan_tags(vlan_tags: &[VlanTag]) -> String {
+        vlan_tags
+            .iter()
+            .map(|tag| {
+                let mut output = format!(", VLAN {}", tag.vid);
+                if tag.pcp != 0 {
+                    output.push_str(&format!(" PCP {}", tag.pcp));
+                }
+                if tag.dei {
+                    output.push_str(" DEI");
+                }
+                output
+            })
+            .collect()
+    }
+
+    fn format_ipv4(
+        ipv4_packet: &ipv4::Ipv4Packet,
+        packet: &[u8],
+        options: &ParseOptions,
+    ) -> String {
+        let mut output = format!(
             "IPv4: Ver {}, Src {}, Dest {}, Prot {:?}, TTL {}",
             ipv4_packet.header.version,
-            ipv4_packet.header.source_address,
-            ipv4_packet.header.destination_address,
+            format_address(IpAddr::from(ipv4_packet.header.source_address), options),
+            format_address(
+                IpAddr::from(ipv4_packet.header.destination_address),
+                options
+            ),
             ipv4_packet.header.protocol,
             ipv4_packet.header.time_to_live
-        )
+        );
+
+        if let Some(fragment) = encapsulation::ipv4_fragment(packet) {
+            output.push_str(&format_fragment(&fragment));
+        }
+
+        let ip_options = encapsulation::ipv4_options(packet)
+            .map(format_ipv4_options)
+            .unwrap_or_default();
+        if !ip_options.is_empty() {
+            output.push_str(&format!(", Opts [{}]", ip_options));
+        }
+        output
     }
 
-    fn format_ipv6(ipv6_packet: &ipv6::Ipv6Packet) -> String {
+    /// Formats the types of the options of an IPv4 header, e.g.
+    /// `RecordRoute, Timestamp`. The NOP and End of Options List padding between
+    /// and after the options is left out.
+    fn format_ipv4_options(options: &[u8]) -> String {
+        let mut formatted = Vec::new();
+        let mut position = 0;
+
+        while let Some(&kind) = options.get(position) {
+            match kind {
+                0 => break,
+                1 => {
+                    position += 1;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let len = match options.get(position + 1).map(|len| usize::from(*len)) {
+                Some(len) if len >= 2 && position + len <= options.len() => len,
+                _ => {
+                    formatted.push(format!("Type {} (malformed)", kind));
+                    break;
+                }
+            };
+
+            formatted.push(match kind {
+                7 => "RecordRoute".to_string(),
+                68 => "Timestamp".to_string(),
+                130 => "Security".to_string(),
+                131 => "LooseSourceRoute".to_string(),
+                136 => "StreamId".to_string(),
+                137 => "StrictSourceRoute".to_string(),
+                148 => "RouterAlert".to_string(),
+                kind => format!("Type {}", kind),
+            });
+            position += len;
+        }
+
+        formatted.join(", ")
+    }
+
+    /// Formats an IP address, followed by its country code if a GeoIP database is
+    /// configured and knows the address, e.g. `8.8.8.8 (US)`.
+    #[cfg(feature = "geoip")]
+    fn format_address(address: IpAddr, options: &ParseOptions) -> String {
+        match options
+            .geoip
+            .as_ref()
+            .and_then(|geoip| geoip.country(address))
+        {
+            Some(country) => format!("{} ({})", address, country),
+            None => address.to_string(),
+        }
+    }
+
+    /// Formats an IP address. Without the `geoip` feature, no country is shown.
+    #[cfg(not(feature = "geoip"))]
+    fn format_address(address: IpAddr, _options: &ParseOptions) -> String {
+        address.to_string()
+    }
+
+    /// Formats the fragmentation fields of an IPv4 header with a leading space, e.g.
+    /// ` [DF]` or ` [MF offset=1480]`, or an empty string if no flag is set.
+    fn format_fragment(fragment: &Ipv4Fragment) -> String {
+        let mut fields = Vec::new();
+        if fragment.dont_fragment {
+            fields.push("DF".to_string());
+        }
+        if fragment.more_fragments {
+            fields.push("MF".to_string());
+        }
+        if fragment.is_fragment() {
+            fields.push(format!("offset={}", fragment.offset));
+        }
+
+        if fields.is_empty() {
+            return String::new();
+        }
+        format!(" [{}]", fields.join(" "))
+    }
+
+    /// Formats an IPv6 header. If the packet has extension headers, the whole chain
+    /// is shown in place of the Next Header field, e.g. `Next: HopByHop -> TCP`.
+    fn format_ipv6(
+        ipv6_packet: &ipv6::Ipv6Packet,
+        packet: &[u8],
+        options: &ParseOptions,
+    ) -> String {
+        let next = match format_ipv6_extensions(packet) {
+            Some(chain) => chain,
+            None => format!("{:?}", ipv6_packet.header.next_header),
+        };
+
         format!(
-            "IPV6: Ver: {} Src: {} Dest: {} Next: {:?}",
+            "IPV6: Ver: {} Src: {} Dest: {} Next: {}",
             ipv6_packet.header.version,
-            ipv6_packet.header.source_address,
-            ipv6_packet.header.destination_address,
-            ipv6_packet.header.next_header
+            format_address(IpAddr::from(ipv6_packet.header.source_address), options),
+            format_address(
+                IpAddr::from(ipv6_packet.header.destination_address),
+                options
+            ),
+            next
         )
     }
 
-    fn format_tcp(tcp_segment: &tcp::TcpSegment) -> String {
-        format!(
-            "TCP: Src Port: {} Dest Port: {} Seq: {} Syn: {} Ack: {}",
-            tcp_segment.header.source_port,
-            tcp_segment.header.destination_port,
-            tcp_segment.header.sequence_number,
-            tcp_segment.header.flags.syn,
-            tcp_segment.header.flags.ack
-        )
+    /// Formats the extension header chain of the IPv6 packet of a raw Ethernet
+    /// frame, ending with the upper-layer protocol, or `None` if it has none.
+    fn format_ipv6_extensions(packet: &[u8]) -> Option<String> {
+        let extensions = encapsulation::ipv6_extensions(packet)?;
+        if extensions.headers.is_empty() {
+            return None;
+        }
+
+        let chain = extensions
+            .headers
+            .iter()
+            .chain([&extensions.protocol])
+            .map(|&protocol| ip_protocol_name(protocol))
+            .collect::<Vec<String>>();
+        Some(chain.join(" -> "))
+    }
+
+    /// Names an IP protocol or IPv6 extension header, e.g. `TCP` or `HopByHop`.
+    pub fn ip_protocol_name(protocol: u8) -> String {
+        let name = match protocol {
+            IPV6_HOP_BY_HOP => "HopByHop",
+            IP_PROTOCOL_ICMP => "ICMP",
+            IP_PROTOCOL_IGMP => "IGMP",
+            IP_PROTOCOL_IPV4 => "IPv4",
+            IP_PROTOCOL_TCP => "TCP",
+            IP_PROTOCOL_UDP => "UDP",
+            IP_PROTOCOL_IPV6 => "IPv6",
+            IPV6_ROUTING => "Routing",
+            IPV6_FRAGMENT => "Fragment",
+            IP_PROTOCOL_GRE => "GRE",
+            IP_PROTOCOL_ESP => "ESP",
+            IP_PROTOCOL_AH => "AH",
+            IP_PROTOCOL_ICMPV6 => "ICMPv6",
+            IPV6_NO_NEXT_HEADER => "NoNext",
+            IPV6_DESTINATION_OPTIONS => "DestOpts",
+            other => return other.to_string(),
+        };
+
+        name.to_string()
+    }
+
+    /// Formats a TCP segment with its set flags, e.g. `Flags: [SYN,ACK] (0x12)`.
+    /// The flags byte and window are read from the raw frame in `packet`.
+    fn format_tcp(tcp_segment: &tcp::TcpSegment, packet: &[u8]) -> String {
+        let mut output = format!(
+            "TCP: Src Port: {} Dest Port: {} Seq: {}",
+            format_port(tcp_segment.header.source_port, IP_PROTOCOL_TCP),
+            format_port(tcp_segment.header.destination_port, IP_PROTOCOL_TCP),
+            tcp_segment.header.sequence_number
+        );
+
+        let raw = TcpSegment::from_frame(packet);
+        match &raw {
+            Some(raw) => output.push_str(&format!(
+                " Flags: [{}] ({:#04x}) Win: {}",
+                format_tcp_flags(raw.flags),
+                raw.flags,
+                raw.window
+            )),
+            None => output.push_str(&format!(
+                " Syn: {} Ack: {}",
+                tcp_segment.header.flags.syn, tcp_segment.header.flags.ack
+            )),
+        }
+
+        // The urgent pointer is only meaningful, and rarely seen, with URG set
+        if tcp_segment.header.flags.urg {
+            output.push_str(&format!(" Urg Ptr: {}", tcp_segment.header.urgent_pointer));
+        }
+
+        if let Some(sni) = raw.and_then(|raw| tls::client_hello_sni(raw.payload)) {
+            output.push_str(&format!(" | SNI: {}", sni));
+        }
+
+        output
+    }
+
+    /// Lists the names of the flags set in a TCP flags byte, e.g. `SYN,ACK`.
+    fn format_tcp_flags(flags: u8) -> String {
+        const FLAG_NAMES: [(u8, &str); 8] = [
+            (0x02, "SYN"),
+            (0x10, "ACK"),
+            (0x01, "FIN"),
+            (0x04, "RST"),
+            (0x08, "PSH"),
+            (0x20, "URG"),
+            (0x40, "ECE"),
+            (0x80, "CWR"),
+        ];
+
+        FLAG_NAMES
+            .iter()
+            .filter(|(bit, _)| flags & bit != 0)
+            .map(|(_, name)| *name)
+            .collect::<Vec<&str>>()
+            .join(",")
     }
 
     fn format_udp(udp_datagram: &udp::UdpDatagram) -> String {
         format!(
             "UDP: Src Port {}, Dest Port {}",
-            udp_datagram.header.source_port, udp_datagram.header.destination_port
+            format_port(udp_datagram.header.source_port, IP_PROTOCOL_UDP),
+            format_port(udp_datagram.header.destination_port, IP_PROTOCOL_UDP)
         )
     }
 
+    /// Well-known TCP and UDP services in the style of /etc/services, as
+    /// `(port, IP protocol, name)`, sorted by port and protocol.
+    const SERVICES: &[(u16, u8, &str)] = &[
+        (20, IP_PROTOCOL_TCP, "ftp-data"),
+        (21, IP_PROTOCOL_TCP, "ftp"),
+        (22, IP_PROTOCOL_TCP, "ssh"),
+        (23, IP_PROTOCOL_TCP, "telnet"),
+        (25, IP_PROTOCOL_TCP, "smtp"),
+        (53, IP_PROTOCOL_TCP, "domain"),
+        (53, IP_PROTOCOL_UDP, "domain"),
+        (67, IP_PROTOCOL_UDP, "bootps"),
+        (68, IP_PROTOCOL_UDP, "bootpc"),
+        (69, IP_PROTOCOL_UDP, "tftp"),
+        (80, IP_PROTOCOL_TCP, "http"),
+        (80, IP_PROTOCOL_UDP, "http"),
+        (88, IP_PROTOCOL_TCP, "kerberos"),
+        (88, IP_PROTOCOL_UDP, "kerberos"),
+        (110, IP_PROTOCOL_TCP, "pop3"),
+        (111, IP_PROTOCOL_TCP, "sunrpc"),
+        (111, IP_PROTOCOL_UDP, "sunrpc"),
+        (119, IP_PROTOCOL_TCP, "nntp"),
+        (123, IP_PROTOCOL_UDP, "ntp"),
+        (135, IP_PROTOCOL_TCP, "epmap"),
+        (137, IP_PROTOCOL_UDP, "netbios-ns"),
+        (138, IP_PROTOCOL_UDP, "netbios-dgm"),
+        (139, IP_PROTOCOL_TCP, "netbios-ssn"),
+        (143, IP_PROTOCOL_TCP, "imap"),
+        (161, IP_PROTOCOL_UDP, "snmp"),
+        (162, IP_PROTOCOL_UDP, "snmp-trap"),
+        (179, IP_PROTOCOL_TCP, "bgp"),
+        (389, IP_PROTOCOL_TCP, "ldap"),
+        (443, IP_PROTOCOL_TCP, "https"),
+        (443, IP_PROTOCOL_UDP, "https"),
+        (445, IP_PROTOCOL_TCP, "microsoft-ds"),
+        (465, IP_PROTOCOL_TCP, "submissions"),
+        (500, IP_PROTOCOL_UDP, "isakmp"),
+        (514, IP_PROTOCOL_TCP, "shell"),
+        (514, IP_PROTOCOL_UDP, "syslog"),
+        (520, IP_PROTOCOL_UDP, "router"),
+        (546, IP_PROTOCOL_UDP, "dhcpv6-client"),
+        (547, IP_PROTOCOL_UDP, "dhcpv6-server"),
+        (587, IP_PROTOCOL_TCP, "submission"),
+        (631, IP_PROTOCOL_TCP, "ipp"),
+        (636, IP_PROTOCOL_TCP, "ldaps"),
+        (853, IP_PROTOCOL_TCP, "domain-s"),
+        (873, IP_PROTOCOL_TCP, "rsync"),
+        (993, IP_PROTOCOL_TCP, "imaps"),
+        (995, IP_PROTOCOL_TCP, "pop3s"),
+        (1194, IP_PROTOCOL_UDP, "openvpn"),
+        (1433, IP_PROTOCOL_TCP, "ms-sql-s"),
+        (1812, IP_PROTOCOL_UDP, "radius"),
+        (1813, IP_PROTOCOL_UDP, "radius-acct"),
+        (1883, IP_PROTOCOL_TCP, "mqtt"),
+        (2049, IP_PROTOCOL_TCP, "nfs"),
+        (2049, IP_PROTOCOL_UDP, "nfs"),
+        (3306, IP_PROTOCOL_TCP, "mysql"),
+        (3389, IP_PROTOCOL_TCP, "ms-wbt-server"),
+        (4500, IP_PROTOCOL_UDP, "ipsec-nat-t"),
+        (5353, IP_PROTOCOL_UDP, "mdns"),
+        (5432, IP_PROTOCOL_TCP, "postgresql"),
+        (5672, IP_PROTOCOL_TCP, "amqp"),
+        (6379, IP_PROTOCOL_TCP, "redis"),
+        (8080, IP_PROTOCOL_TCP, "http-alt"),
+        (27017, IP_PROTOCOL_TCP, "mongodb"),
+    ];
+
+    /// Returns the name of the well-known service on a port, e.g. `https` for TCP
+    /// port 443.
+    ///
+    /// # Arguments
+    /// * `port` - The TCP or UDP port.
+    /// * `protocol` - The IP protocol number of the transport, 6 for TCP or 17 for UDP.
+    ///
+    /// # Returns
+    /// The service name, or `None` if the port has no well-known service.
+    pub fn service_name(port: u16, protocol: u8) -> Option<&'static str> {
+        SERVICES
+            .binary_search_by_key(&(port, protocol), |&(port, protocol, _)| (port, protocol))
+            .ok()
+            .map(|index| SERVICES[index].2)
+    }
+
+    /// Formats a port followed by its service name if it has one, e.g. `443(https)`.
+    fn format_port(port: u16, protocol: u8) -> String {
+        match service_name(port, protocol) {
+            Some(service) => format!("{}({})", port, service),
+            None => port.to_string(),
+        }
+    }
+
     fn format_icmp(icmp_packet: &icmp::IcmpPacket) -> String {
         format!(
-            "ICMP: Type: {} Code: {}  Checksum: {}",
-            icmp_packet.header.icmp_type, icmp_packet.header.icmp_code, icmp_packet.header.checksum
+            "ICMP: {}, Type: {} Code: {}  Checksum: {}",
+            icmp_name(icmp_packet.header.icmp_type, icmp_packet.header.icmp_code),
+            icmp_packet.header.icmp_type,
+            icmp_packet.header.icmp_code,
+            icmp_packet.header.checksum
         )
     }
+
+    /// Names an ICMP message by its type and, where it refines the type, its code,
+    /// e.g. `Destination Unreachable (Fragmentation Needed)` for type 3, code 4.
+    pub fn icmp_name(icmp_type: u8, code: u8) -> String {
+        let (name, code_name) = match (icmp_type, code) {
+            (0, _) => ("Echo Reply", None),
+            (3, 0) => ("Destination Unreachable", Some("Net Unreachable")),
+            (3, 1) => ("Destination Unreachable", Some("Host Unreachable")),
+            (3, 2) => ("Destination Unreachable", Some("Protocol Unreachable")),
+            (3, 3) => ("Destination Unreachable", Some("Port Unreachable")),
+            (3, 4) => ("Destination Unreachable", Some("Fragmentation Needed")),
+            (3, 5) => ("Destination Unreachable", Some("Source Route Failed")),
+            (3, 6) => ("Destination Unreachable", Some("Network Unknown")),
+            (3, 7) => ("Destination Unreachable", Some("Host Unknown")),
+            (3, 9) => ("Destination Unreachable", Some("Network Prohibited")),
+            (3, 10) => ("Destination Unreachable", Some("Host Prohibited")),
+            (3, 13) => ("Destination Unreachable", Some("Communication Prohibited")),
+            (3, _) => ("Destination Unreachable", None),
+            (4, _) => ("Source Quench", None),
+            (5, 0) => ("Redirect", Some("Network")),
+            (5, 1) => ("Redirect", Some("Host")),
+            (5, 2) => ("Redirect", Some("TOS and Network")),
+            (5, 3) => ("Redirect", Some("TOS and Host")),
+            (5, _) => ("Redirect", None),
+            (8, _) => ("Echo Request", None),
+            (9, _) => ("Router Advertisement", None),
+            (10, _) => ("Router Solicitation", None),
+            (11, 0) => ("Time Exceeded", Some("TTL Exceeded in Transit")),
+            (11, 1) => ("Time Exceeded", Some("Fragment Reassembly Time Exceeded")),
+            (11, _) => ("Time Exceeded", None),
+            (12, 0) => ("Parameter Problem", Some("Pointer Indicates Error")),
+            (12, 1) => ("Parameter Problem", Some("Missing Required Option")),
+            (12, 2) => ("Parameter Problem", Some("Bad Length")),
+            (12, _) => ("Parameter Problem", None),
+            (13, _) => ("Timestamp", None),
+            (14, _) => ("Timestamp Reply", None),
+            _ => ("Unknown", None),
+        };
+
+        match code_name {
+            Some(code_name) => format!("{} ({})", name, code_name),
+            None => name.to_string(),
+        }
+    }
 }
@@ -1,8 +1,6 @@
-mod analyzer;
 mod cli;
-mod logger;
 
 fn main() {
-    logger::log::setup().expect("failed to initialize logger.");
+    // The logger is set up by `cli::run` once it knows where output may go
     cli::run();
 }
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ParserError {
+    #[error("Expected at least {expected} bytes, got {actual}")]
+    TooShort { expected: usize, actual: usize },
+
+    #[error("Unexpected IP version {0}")]
+    InvalidVersion(u8),
+
+    #[error("Invalid header length of {0} words")]
+    InvalidHeaderLength(u8),
+}
@@ -0,0 +1,190 @@
+use super::{error::ParserError, ParsedPacket, ProtocolParser};
+use std::net::Ipv4Addr;
+
+/// The length of an IPv4 header without options.
+const MIN_HEADER_LEN: usize = 20;
+
+/// An IPv4 packet, with every header field kept as received so that it can be
+/// serialized back unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ipv4Packet {
+    pub version: u8,
+    /// The header length in 32-bit words.
+    pub ihl: u8,
+    pub dscp: u8,
+    pub ecn: u8,
+    pub total_length: u16,
+    pub identification: u16,
+    pub flags: u8,
+    pub fragment_offset: u16,
+    pub ttl: u8,
+    pub protocol: u8,
+    pub checksum: u16,
+    pub source: Ipv4Addr,
+    pub destination: Ipv4Addr,
+    pub options: Vec<u8>,
+    /// Everything following the header, including any trailing padding.
+    pub payload: Vec<u8>,
+}
+
+impl Ipv4Packet {
+    /// Encodes the packet, header first, as it would appear on the wire.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(MIN_HEADER_LEN + self.options.len() + self.payload.len());
+        bytes.push((self.version << 4) | (self.ihl & 0x0f));
+        bytes.push((self.dscp << 2) | (self.ecn & 0x03));
+        bytes.extend_from_slice(&self.total_length.to_be_bytes());
+        bytes.extend_from_slice(&self.identification.to_be_bytes());
+        bytes.extend_from_slice(
+            &((u16::from(self.flags) << 13) | (self.fragment_offset & 0x1fff)).to_be_bytes(),
+        );
+        bytes.push(self.ttl);
+        bytes.push(self.protocol);
+        bytes.extend_from_slice(&self.checksum.to_be_bytes());
+        bytes.extend_from_slice(&self.source.octets());
+        bytes.extend_from_slice(&self.destination.octets());
+        bytes.extend_from_slice(&self.options);
+        bytes.extend_from_slice(&self.payload);
+        bytes
+    }
+}
+
+/// Parses IPv4 packets into an `Ipv4Packet`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ipv4Parser;
+
+impl ProtocolParser for Ipv4Parser {
+    fn parse(&self, data: &[u8]) -> Result<ParsedPacket, ParserError> {
+        if data.len() < MIN_HEADER_LEN {
+            return Err(ParserError::TooShort {
+                expected: MIN_HEADER_LEN,
+                actual: data.len(),
+            });
+        }
+
+        let version = data[0] >> 4;
+        if version != 4 {
+            return Err(ParserError::InvalidVersion(version));
+        }
+
+        let ihl = data[0] & 0x0f;
+        let header_len = usize::from(ihl) * 4;
+        if header_len < MIN_HEADER_LEN {
+            return Err(ParserError::InvalidHeaderLength(ihl));
+        }
+        if data.len() < header_len {
+            return Err(ParserError::TooShort {
+                expected: header_len,
+                actual: data.len(),
+            });
+        }
+
+        let word = |offset: usize| u16::from_be_bytes([data[offset], data[offset + 1]]);
+        let fragment = word(6);
+
+        Ok(ParsedPacket::Ipv4(Ipv4Packet {
+            version,
+            ihl,
+            dscp: data[1] >> 2,
+            ecn: data[1] & 0x03,
+            total_length: word(2),
+            identification: word(4),
+            flags: (fragment >> 13) as u8,
+            fragment_offset: fragment & 0x1fff,
+            ttl: data[8],
+            protocol: data[9],
+            checksum: word(10),
+            source: Ipv4Addr::new(data[12], data[13], data[14], data[15]),
+            destination: Ipv4Addr::new(data[16], data[17], data[18], data[19]),
+            options: data[MIN_HEADER_LEN..header_len].to_vec(),
+            payload: data[header_len..].to_vec(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A TCP SYN from 192.168.1.10 to 93.184.216.34 with DF set.
+    const HEADER: [u8; 20] = [
+        0x45, 0x00, 0x00, 0x3c, 0x1c, 0x46, 0x40, 0x00, 0x40, 0x06, 0xa6, 0xec, 0xc0, 0xa8, 0x01,
+        0x0a, 0x5d, 0xb8, 0xd8, 0x22,
+    ];
+
+    fn parse_ipv4(bytes: &[u8]) -> Ipv4Packet {
+        match Ipv4Parser.parse(bytes) {
+            Ok(ParsedPacket::Ipv4(packet)) => packet,
+            other => panic!("expected an IPv4 packet, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_header_fields() {
+        let packet = parse_ipv4(&HEADER);
+
+        assert_eq!(packet.ihl, 5);
+        assert_eq!(packet.total_length, 60);
+        assert_eq!(packet.identification, 0x1c46);
+        assert_eq!(packet.flags, 0b010);
+        assert_eq!(packet.fragment_offset, 0);
+        assert_eq!(packet.ttl, 64);
+        assert_eq!(packet.protocol, 6);
+        assert_eq!(packet.source, Ipv4Addr::new(192, 168, 1, 10));
+        assert_eq!(packet.destination, Ipv4Addr::new(93, 184, 216, 34));
+        assert!(packet.options.is_empty());
+    }
+
+    #[test]
+    fn serialize_round_trips_header() {
+        let parsed = Ipv4Parser.parse(&HEADER).unwrap();
+        assert_eq!(parsed.serialize(), HEADER);
+    }
+
+    #[test]
+    fn serialize_round_trips_options_and_payload() {
+        let mut bytes = HEADER.to_vec();
+        // Six header words, with a record route option padded by an end-of-list
+        bytes[0] = 0x46;
+        bytes.extend_from_slice(&[0x07, 0x03, 0x04, 0x00]);
+        bytes.extend_from_slice(b"payload");
+
+        let packet = parse_ipv4(&bytes);
+        assert_eq!(packet.options, [0x07, 0x03, 0x04, 0x00]);
+        assert_eq!(packet.payload, b"payload");
+        assert_eq!(ParsedPacket::Ipv4(packet).serialize(), bytes);
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        assert_eq!(
+            Ipv4Parser.parse(&HEADER[..19]),
+            Err(ParserError::TooShort {
+                expected: 20,
+                actual: 19
+            })
+        );
+
+        let mut ipv6 = HEADER;
+        ipv6[0] = 0x65;
+        assert_eq!(Ipv4Parser.parse(&ipv6), Err(ParserError::InvalidVersion(6)));
+
+        let mut short_ihl = HEADER;
+        short_ihl[0] = 0x44;
+        assert_eq!(
+            Ipv4Parser.parse(&short_ihl),
+            Err(ParserError::InvalidHeaderLength(4))
+        );
+
+        let mut long_ihl = HEADER;
+        long_ihl[0] = 0x46;
+        assert_eq!(
+            Ipv4Parser.parse(&long_ihl),
+            Err(ParserError::TooShort {
+                expected: 24,
+                actual: 20
+            })
+        );
+    }
+}
@@ -0,0 +1,34 @@
+pub mod error;
+pub mod ipv4;
+
+use error::ParserError;
+use ipv4::Ipv4Packet;
+
+/// A packet decoded by one of the parsers in this module.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParsedPacket {
+    Ipv4(Ipv4Packet),
+}
+
+impl ParsedPacket {
+    /// Encodes the packet back into the bytes it was parsed from.
+    pub fn serialize(&self) -> Vec<u8> {
+        match self {
+            ParsedPacket::Ipv4(packet) => packet.serialize(),
+        }
+    }
+}
+
+/// A parser for a protocol that net-sift does not decode, or decodes differently
+/// from what is needed.
+pub trait ProtocolParser {
+    /// Parses a packet of the protocol.
+    ///
+    /// # Arguments
+    /// * `data` - The bytes of the packet, starting with its header.
+    ///
+    /// # Returns
+    /// * `Ok(ParsedPacket)` - The decoded packet.
+    /// * `Err(ParserError)` - The bytes do not hold a valid packet of the protocol.
+    fn parse(&self, data: &[u8]) -> Result<ParsedPacket, ParserError>;
+}
@@ -0,0 +1,47 @@
+//! Checks that every subcommand shares a single packet formatter.
+//!
+//! `capture`, `stream` and `analyze` all decode packets with `Analyzer::decode`,
+//! so a plain Ethernet frame must decode to exactly the output of the exported
+//! `format_packets`. A second formatter drifting away from it fails this test.
+
+use net_sift::parsers::{definitions::LayeredData, ethernet_frame::EthernetFrame};
+use sniff_rs::{analyzer::definitions::ParseOptions, format_packets, Analyzer};
+
+/// An Ethernet frame carrying a TCP SYN from 192.168.1.10:50000 to 93.184.216.34:80.
+fn tcp_syn_frame() -> Vec<u8> {
+    let mut frame = vec![
+        0x00, 0x11, 0x22, 0x33, 0x44, 0x55, // destination MAC
+        0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, // source MAC
+        0x08, 0x00, // IPv4
+    ];
+    frame.extend_from_slice(&[
+        0x45, 0x00, 0x00, 0x28, 0x00, 0x01, 0x40, 0x00, 0x40, 0x06, 0x00, 0x00, // header
+        192, 168, 1, 10, // source address
+        93, 184, 216, 34, // destination address
+    ]);
+    frame.extend_from_slice(&[
+        0xc3, 0x50, 0x00, 0x50, // ports
+        0x00, 0x00, 0x00, 0x01, // sequence number
+        0x00, 0x00, 0x00, 0x00, // acknowledgment number
+        0x50, 0x02, 0xff, 0xff, // data offset, flags and window
+        0x00, 0x00, 0x00, 0x00, // checksum and urgent pointer
+    ]);
+    frame
+}
+
+#[test]
+fn decode_uses_the_exported_formatter() {
+    let packet = tcp_syn_frame();
+    let options = ParseOptions::default();
+
+    let frame = EthernetFrame::from_bytes(&packet, false)
+        .and_then(|frame| frame.parse_next_layer())
+        .expect("failed to parse test frame");
+    let expected = match frame {
+        LayeredData::EthernetFrameData(frame) => format_packets(&frame, &packet, &[], &options),
+        _ => panic!("test frame is not parsed as an Ethernet frame"),
+    };
+
+    let decoded = Analyzer::decode(&packet, &options).expect("failed to decode test frame");
+    assert_eq!(decoded, Some(expected));
+}
@@ -0,0 +1,66 @@
+//! Replay-and-compare regression tests for the packet decoder.
+//!
+//! Every `tests/golden/<name>.pcap` fixture is decoded with
+//! `wyre analyze --read <name>.pcap --format json` and the NDJSON output is
+//! compared against `tests/golden/<name>.ndjson`.
+//!
+//! A fixture without a golden file fails the test. Golden files are written with
+//! `UPDATE_GOLDEN=1 cargo test`, both for new fixtures and after an intentional
+//! change to parsing or formatting; review the diff before committing them.
+//!
+//! `encapsulated.pcap` holds an IP-in-IP, a PPPoE session and an ESP frame, which
+//! the JSON output decodes differently from the text output.
+
+use std::{env, fs, path::Path, process::Command};
+
+fn analyze_json(fixture: &Path) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_sniff-rs"))
+        .args(["analyze", "--format", "json", "--read"])
+        .arg(fixture)
+        .output()
+        .expect("failed to run analyze");
+
+    assert!(output.status.success(), "analyze failed for {:?}", fixture);
+    String::from_utf8(output.stdout).expect("analyze output is not UTF-8")
+}
+
+#[test]
+fn decoded_fixtures_match_golden_output() {
+    let golden_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let update = env::var_os("UPDATE_GOLDEN").is_some();
+
+    let mut fixtures = fs::read_dir(&golden_dir)
+        .expect("missing tests/golden directory")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "pcap"))
+        .collect::<Vec<_>>();
+    fixtures.sort();
+    assert!(
+        !fixtures.is_empty(),
+        "no .pcap fixtures in {:?}",
+        golden_dir
+    );
+
+    for fixture in fixtures {
+        let actual = analyze_json(&fixture);
+        let golden = fixture.with_extension("ndjson");
+
+        if update {
+            fs::write(&golden, &actual).expect("failed to write golden file");
+            continue;
+        }
+
+        assert!(
+            golden.exists(),
+            "missing golden file {:?}, write it with UPDATE_GOLDEN=1 cargo test",
+            golden
+        );
+
+        let expected = fs::read_to_string(&golden).expect("failed to read golden file");
+        assert_eq!(
+            actual, expected,
+            "decoded output of {:?} differs from {:?}",
+            fixture, golden
+        );
+    }
+}